@@ -0,0 +1,224 @@
+use std::fmt;
+
+use crate::tree::Span;
+
+/*
+     @Description: Enum for an expression node; operators are held as grammar terminals, e.g. "+" or "not",
+                   so new operators need no new variants, and every node keeps the span of its first token. A call
+                   records whether its arguments end in a comma, which the grammar allows
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+     Number { value: String, span: Span },
+     Ident { name: String, span: Span },
+     Paren { inner: Box<Expr>, span: Span },
+     Unary { op: String, operand: Box<Expr>, span: Span },
+     Binary { op: String, left: Box<Expr>, right: Box<Expr>, span: Span },
+     Index { target: Box<Expr>, index: Box<Expr>, span: Span },
+     Call { callee: Box<Expr>, args: Vec<Expr>, trailing_comma: bool, span: Span },
+     Missing { span: Span },
+     Error { tokens: Vec<(String, Span)>, span: Span },
+}
+
+impl Expr {
+     /*
+          @Description: Gets the position of the expression
+          @Params: None
+          @Returns: The span of its first token
+     */
+     pub fn span(&self) -> Span {
+          match self {
+               Expr::Number { span, .. }
+               | Expr::Ident { span, .. }
+               | Expr::Paren { span, .. }
+               | Expr::Unary { span, .. }
+               | Expr::Binary { span, .. }
+               | Expr::Index { span, .. }
+               | Expr::Call { span, .. }
+               | Expr::Missing { span }
+               | Expr::Error { span, .. } => *span,
+          }
+     }
+
+     /*
+          @Description: Moves the expression to another position, leaving its children where they are
+          @Params: span - the new span
+          @Returns: The expression with the span replaced
+     */
+     pub fn with_span(mut self, span: Span) -> Expr {
+          match &mut self {
+               Expr::Number { span: old, .. }
+               | Expr::Ident { span: old, .. }
+               | Expr::Paren { span: old, .. }
+               | Expr::Unary { span: old, .. }
+               | Expr::Binary { span: old, .. }
+               | Expr::Index { span: old, .. }
+               | Expr::Call { span: old, .. }
+               | Expr::Missing { span: old }
+               | Expr::Error { span: old, .. } => *old = span,
+          }
+          self
+     }
+}
+
+/*
+     @Description: String representation of an expression with every operator application parenthesised,
+                   e.g. "((-a) + (b * c))", so the grouping the parser chose is visible
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for Expr {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          match self {
+               Expr::Number { value, .. } => write!(f, "{}", value),
+               Expr::Ident { name, .. } => write!(f, "{}", name),
+               Expr::Paren { inner, .. } => write!(f, "{}", inner),
+               Expr::Unary { op, operand, .. } => {
+                    if op.chars().all(char::is_alphabetic) {
+                         write!(f, "({} {})", op, operand)
+                    } else {
+                         write!(f, "({}{})", op, operand)
+                    }
+               }
+               Expr::Binary { op, left, right, .. } => write!(f, "({} {} {})", left, op, right),
+               Expr::Index { target, index, .. } => write!(f, "{}[{}]", target, index),
+               Expr::Call { callee, args, trailing_comma, .. } => {
+                    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                    write!(f, "{}({}{})", callee, args.join(", "), if *trailing_comma { "," } else { "" })
+               }
+               Expr::Missing { .. } => write!(f, "<missing>"),
+               Expr::Error { tokens, .. } => write!(f, "<error: {}>", error_text(tokens)),
+          }
+     }
+}
+
+/*
+     @Description: Joins the lexemes of skipped tokens
+     @Params: tokens - the lexeme and span of each token
+     @Returns: The lexemes separated by spaces
+*/
+fn error_text(tokens: &[(String, Span)]) -> String {
+     tokens.iter().map(|(lexeme, _)| lexeme.as_str()).collect::<Vec<&str>>().join(" ")
+}
+
+/*
+     @Description: Enum for the type of a declaration, parameter or function; Missing stands for a type the parser had to insert
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+     Int,
+     Double,
+     Missing,
+}
+
+/*
+     @Description: Struct for a declaration of one or more variables, each an identifier or an indexed identifier
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decl {
+     pub ty: Type,
+     pub vars: Vec<Expr>,
+     pub span: Span,
+}
+
+/*
+     @Description: Struct for a function parameter
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+     pub ty: Type,
+     pub var: Expr,
+     pub span: Span,
+}
+
+/*
+     @Description: Struct for a function definition, from def to fed; the name is an identifier or a Missing node,
+                   and trailing_comma records a comma after the last parameter, which the grammar allows
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnDef {
+     pub return_type: Type,
+     pub name: Expr,
+     pub params: Vec<Param>,
+     pub trailing_comma: bool,
+     pub declarations: Vec<Decl>,
+     pub body: Vec<Stmt>,
+     pub span: Span,
+}
+
+/*
+     @Description: Enum for a statement node; Empty is the empty statement the grammar allows between semicolons,
+                   and Error holds tokens skipped where a statement was expected
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+     Assign { target: Expr, value: Expr, span: Span },
+     If { cond: Expr, then_branch: Vec<Stmt>, else_branch: Option<Vec<Stmt>>, span: Span },
+     While { cond: Expr, body: Vec<Stmt>, span: Span },
+     Print { value: Expr, span: Span },
+     Return { value: Expr, span: Span },
+     Empty { span: Span },
+     Error { tokens: Vec<(String, Span)>, span: Span },
+}
+
+impl Stmt {
+     /*
+          @Description: Gets the position of the statement
+          @Params: None
+          @Returns: The span of its first token
+     */
+     pub fn span(&self) -> Span {
+          match self {
+               Stmt::Assign { span, .. }
+               | Stmt::If { span, .. }
+               | Stmt::While { span, .. }
+               | Stmt::Print { span, .. }
+               | Stmt::Return { span, .. }
+               | Stmt::Empty { span }
+               | Stmt::Error { span, .. } => *span,
+          }
+     }
+
+     /*
+          @Description: Moves the statement to another position, leaving its children where they are
+          @Params: span - the new span
+          @Returns: The statement with the span replaced
+     */
+     pub fn with_span(mut self, span: Span) -> Stmt {
+          match &mut self {
+               Stmt::Assign { span: old, .. }
+               | Stmt::If { span: old, .. }
+               | Stmt::While { span: old, .. }
+               | Stmt::Print { span: old, .. }
+               | Stmt::Return { span: old, .. }
+               | Stmt::Empty { span: old }
+               | Stmt::Error { span: old, .. } => *old = span,
+          }
+          self
+     }
+}
+
+/*
+     @Description: Struct for a whole program: function definitions, then global declarations, then the main statements
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+     pub functions: Vec<FnDef>,
+     pub declarations: Vec<Decl>,
+     pub body: Vec<Stmt>,
+     pub span: Span,
+}
@@ -0,0 +1,197 @@
+use crate::grammar::split_rule;
+use crate::table::Ll1Table;
+
+/*
+     @Description: Formats a list of string constants for a generated array
+     @Params: items - the strings
+     @Returns: The array body, one item per line
+*/
+fn string_items(items: &[String]) -> String {
+     items.iter().map(|item| format!("     {:?},\n", item)).collect()
+}
+
+/*
+     @Description: Generates a Rust module holding a precomputed LL(1) table and a table driven `parse` function;
+                   conflicting cells keep the earliest rule, and are listed in CONFLICTS so the parser can report them
+     @Params: grammar - the grammar rules, source - the grammar file named in the header comment
+     @Returns: The Rust source
+*/
+pub fn generate_parser_module(grammar: &[String], source: &str) -> String {
+     let table = Ll1Table::new(grammar);
+     let terminal_count = table.terminals.len();
+     let symbol_index = |symbol: &str| -> usize {
+          match table.terminals.iter().position(|terminal| terminal == symbol) {
+               Some(index) => index,
+               None => terminal_count + table.nonterminals.iter().position(|n| n == symbol).unwrap(),
+          }
+     };
+
+     let mut output = format!(
+          "// Generated from {} by `cargo run generate`; do not edit.\n\
+           // Symbols are numbered terminals first, so a symbol s >= TERMINALS.len() is the nonterminal\n\
+           // NONTERMINALS[s - TERMINALS.len()]. TERMINALS ends with \"$\", the end of input.\n\n\
+           #![allow(dead_code)]\n",
+          source
+     );
+
+     output.push_str(&format!("\npub const TERMINALS: [&str; {}] = [\n", terminal_count));
+     output.push_str(&string_items(&table.terminals));
+     output.push_str("];\n\n");
+
+     output.push_str(&format!("pub const NONTERMINALS: [&str; {}] = [\n", table.nonterminals.len()));
+     output.push_str(&string_items(&table.nonterminals));
+     output.push_str("];\n\n");
+
+     output.push_str(&format!("pub const PRODUCTIONS: [&str; {}] = [\n", grammar.len()));
+     output.push_str(&string_items(grammar));
+     output.push_str("];\n\n");
+
+     output.push_str(&format!("pub const PRODUCTION_LHS: [u16; {}] = [", grammar.len()));
+     let lhs: Vec<String> = grammar
+          .iter()
+          .map(|rule| table.nonterminals.iter().position(|n| *n == split_rule(rule).0).unwrap().to_string())
+          .collect();
+     output.push_str(&lhs.join(", "));
+     output.push_str("];\n\n");
+
+     output.push_str(&format!("pub const PRODUCTION_RHS: [&[u16]; {}] = [\n", grammar.len()));
+     for rule in grammar {
+          let rhs: Vec<String> = split_rule(rule).1.iter().map(|symbol| symbol_index(symbol).to_string()).collect();
+          output.push_str(&format!("     &[{}],\n", rhs.join(", ")));
+     }
+     output.push_str("];\n\n");
+
+     output.push_str("// TABLE[nonterminal][terminal] is the rule to expand with, or -1 for a syntax error.\n");
+     output.push_str(&format!(
+          "pub const TABLE: [[i16; {}]; {}] = [\n",
+          terminal_count,
+          table.nonterminals.len()
+     ));
+     for nonterminal in &table.nonterminals {
+          let row: Vec<String> = table
+               .terminals
+               .iter()
+               .map(|terminal| match table.find_cell(nonterminal, terminal).first() {
+                    Some(rule) => rule.to_string(),
+                    None => String::from("-1"),
+               })
+               .collect();
+          output.push_str(&format!("     [{}],\n", row.join(", ")));
+     }
+     output.push_str("];\n\n");
+
+     let conflicts = table.conflicts();
+     output.push_str("// CONFLICTS holds (nonterminal, terminal, rules) for each cell predicting more than one rule; TABLE keeps the first.\n");
+     output.push_str(&format!("pub const CONFLICTS: [(usize, usize, &[u16]); {}] = [\n", conflicts.len()));
+     for (nonterminal, terminal) in &conflicts {
+          let rules: Vec<String> = table.find_cell(nonterminal, terminal).iter().map(|rule| rule.to_string()).collect();
+          output.push_str(&format!(
+               "     ({}, {}, &[{}]),\n",
+               table.nonterminals.iter().position(|n| n == nonterminal).unwrap(),
+               symbol_index(terminal),
+               rules.join(", ")
+          ));
+     }
+     output.push_str("];\n");
+
+     output.push_str(PARSE_FUNCTION);
+     output
+}
+
+/*
+     @Description: The driver copied into every generated module; it only depends on the constants above it
+     @Params: None
+     @Returns: None
+*/
+const PARSE_FUNCTION: &str = r#"
+/*
+     @Description: Finds the number of a terminal
+     @Params: terminal - the terminal name
+     @Returns: The symbol number, if the grammar uses the terminal
+*/
+pub fn terminal_index(terminal: &str) -> Option<usize> {
+     TERMINALS.iter().position(|t| *t == terminal)
+}
+
+/*
+     @Description: Adds the last conflicting cell the parser used to an error, since the input may have needed the rule
+                   the table dropped there
+     @Params: error - the error message, conflict - the nonterminal, terminal and location of the cell, if any
+     @Returns: The message
+*/
+fn with_conflict(error: String, conflict: &Option<(usize, usize, String)>) -> String {
+     match conflict {
+          Some((nonterminal, terminal, location)) => {
+               let rules = CONFLICTS
+                    .iter()
+                    .find(|(n, t, _)| n == nonterminal && t == terminal)
+                    .map_or(&[][..], |(_, _, rules)| *rules);
+               format!(
+                    "{} (conflict: {} on {} {} predicts rules {:?}, the table used rule {})",
+                    error, NONTERMINALS[*nonterminal], TERMINALS[*terminal], location, rules, rules[0]
+               )
+          }
+          None => error,
+     }
+}
+
+/*
+     @Description: Parses a sequence of terminals with the precomputed table; the end of input is implied
+     @Params: input - the terminal name, line and column of each token, e.g. [("ID", 1, 1), ("=", 1, 2)]
+     @Returns: The rules of the leftmost derivation or an error message naming the token's line:col
+*/
+pub fn parse<S: AsRef<str>>(input: &[(S, usize, usize)]) -> Result<Vec<usize>, String> {
+     let end = TERMINALS.len() - 1;
+     let mut lookaheads = Vec::with_capacity(input.len() + 1);
+     for (terminal, line, column) in input {
+          match terminal_index(terminal.as_ref()) {
+               Some(index) if index != end => lookaheads.push(index),
+               _ => return Err(format!("Error: Unknown terminal {} at {}:{}", terminal.as_ref(), line, column)),
+          }
+     }
+     lookaheads.push(end);
+     let location = |position: usize| match input.get(position) {
+          Some((_, line, column)) => format!("at {}:{}", line, column),
+          None => String::from("at the end of input"),
+     };
+
+     let mut derivation = Vec::new();
+     let mut conflict = None;
+     let mut stack: Vec<usize> = vec![end, TERMINALS.len()];
+     let mut position = 0;
+     while let Some(symbol) = stack.pop() {
+          let lookahead = lookaheads[position];
+          if symbol < TERMINALS.len() {
+               if symbol != lookahead {
+                    let error =
+                         format!("Error: Expected {} {}, found {}", TERMINALS[symbol], location(position), TERMINALS[lookahead]);
+                    return Err(with_conflict(error, &conflict));
+               }
+               position += 1;
+               continue;
+          }
+
+          let nonterminal = symbol - TERMINALS.len();
+          let row = &TABLE[nonterminal];
+          if row[lookahead] < 0 {
+               let expected: Vec<&str> =
+                    (0..TERMINALS.len()).filter(|&terminal| row[terminal] >= 0).map(|terminal| TERMINALS[terminal]).collect();
+               let error = format!(
+                    "Error: Expected one of {} in {} {}, found {}",
+                    expected.join(" "),
+                    NONTERMINALS[nonterminal],
+                    location(position),
+                    TERMINALS[lookahead]
+               );
+               return Err(with_conflict(error, &conflict));
+          }
+          if CONFLICTS.iter().any(|(n, t, _)| *n == nonterminal && *t == lookahead) {
+               conflict = Some((nonterminal, lookahead, location(position)));
+          }
+          let rule = row[lookahead] as usize;
+          derivation.push(rule);
+          stack.extend(PRODUCTION_RHS[rule].iter().rev().map(|&s| s as usize));
+     }
+     Ok(derivation)
+}
+"#;
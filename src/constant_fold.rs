@@ -0,0 +1,69 @@
+use crate::ast::Expr;
+use crate::visit::{fold_expr, replace_expr, Fold};
+
+/*
+     @Description: Struct for the constant folding pass: integer arithmetic on literals is evaluated, innermost first,
+                   and the result takes the position of the expression it replaces; folded counts the operations evaluated
+     @Params: None
+     @Returns: None
+*/
+#[derive(Default)]
+pub struct ConstantFolder {
+     pub folded: usize,
+}
+
+impl ConstantFolder {
+     pub fn new() -> ConstantFolder {
+          ConstantFolder { folded: 0 }
+     }
+}
+
+/*
+     @Description: Gets the value of an integer literal as the 32-bit int EZ computes with
+     @Params: expr - the expression
+     @Returns: The value, or None for anything else, doubles and literals out of range included
+*/
+fn integer(expr: &Expr) -> Option<i32> {
+     match expr {
+          Expr::Number { value, .. } => value.parse().ok(),
+          _ => None,
+     }
+}
+
+/*
+     @Description: Evaluates an integer operation; division and remainder by zero and overflow are left for run time
+     @Params: op - the operator terminal, left - the left operand, right - the right operand
+     @Returns: The result, or None if it cannot be folded
+*/
+fn evaluate(op: &str, left: i32, right: i32) -> Option<i32> {
+     match op {
+          "+" => left.checked_add(right),
+          "-" => left.checked_sub(right),
+          "*" => left.checked_mul(right),
+          "/" => left.checked_div(right),
+          "%" => left.checked_rem(right),
+          _ => None,
+     }
+}
+
+impl Fold for ConstantFolder {
+     fn fold_expr(&mut self, expr: Expr) -> Expr {
+          let expr = fold_expr(self, expr);
+          let value = match &expr {
+               Expr::Paren { inner, .. } if integer(inner).is_some() => return replace_expr(&expr, inner.as_ref().clone()),
+               Expr::Unary { op, operand, .. } if op == "-" => integer(operand).and_then(i32::checked_neg),
+               Expr::Binary { op, left, right, .. } => match (integer(left), integer(right)) {
+                    (Some(left), Some(right)) => evaluate(op, left, right),
+                    _ => None,
+               },
+               _ => None,
+          };
+          match value {
+               Some(value) => {
+                    self.folded += 1;
+                    Expr::Number { value: value.to_string(), span: expr.span() }
+               }
+               None => expr,
+          }
+     }
+}
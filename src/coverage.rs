@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use crate::table::Ll1Table;
+
+/*
+     @Description: Struct counting how often each production and each parsing table cell was used while parsing a corpus
+     @Params: None
+     @Returns: None
+*/
+#[derive(Default)]
+pub struct Coverage {
+     pub productions: HashMap<usize, usize>,
+     pub cells: HashMap<(String, String), usize>,
+}
+
+impl Coverage {
+     pub fn new() -> Coverage {
+          Coverage { productions: HashMap::new(), cells: HashMap::new() }
+     }
+
+     /*
+          @Description: Counts one expansion of a nonterminal
+          @Params: rule - the index of the production used, nonterminal - the table row, terminal - the lookahead, the table column
+          @Returns: None
+     */
+     pub fn record(&mut self, rule: usize, nonterminal: &str, terminal: &str) {
+          *self.productions.entry(rule).or_insert(0) += 1;
+          *self.cells.entry((nonterminal.to_string(), terminal.to_string())).or_insert(0) += 1;
+     }
+
+     /*
+          @Description: Gets the number of times a production was used
+          @Params: rule - the index of the production
+          @Returns: The count, 0 if never used
+     */
+     pub fn production_count(&self, rule: usize) -> usize {
+          self.productions.get(&rule).cloned().unwrap_or(0)
+     }
+
+     /*
+          @Description: Gets the number of times a parsing table cell was used
+          @Params: nonterminal - the row, terminal - the column
+          @Returns: The count, 0 if never used
+     */
+     pub fn cell_count(&self, nonterminal: &str, terminal: &str) -> usize {
+          self.cells.get(&(nonterminal.to_string(), terminal.to_string())).cloned().unwrap_or(0)
+     }
+}
+
+/*
+     @Description: Lists the non-empty cells of a table, rows in definition order and columns in table order
+     @Params: table - the table
+     @Returns: The (nonterminal, terminal) pairs
+*/
+fn filled_cells(table: &Ll1Table) -> Vec<(String, String)> {
+     let mut cells = Vec::new();
+     for nonterminal in &table.nonterminals {
+          for terminal in &table.terminals {
+               if !table.find_cell(nonterminal, terminal).is_empty() {
+                    cells.push((nonterminal.clone(), terminal.clone()));
+               }
+          }
+     }
+     cells
+}
+
+/*
+     @Description: Formats a count out of a total with its percentage
+     @Params: covered - the count, total - the total
+     @Returns: The text, e.g. "45/62 (72.6%)"
+*/
+fn ratio(covered: usize, total: usize) -> String {
+     let percent = if total == 0 { 100.0 } else { covered as f64 * 100.0 / total as f64 };
+     format!("{}/{} ({:.1}%)", covered, total, percent)
+}
+
+/*
+     @Description: Formats the coverage totals followed by every production and table cell the corpus never used
+     @Params: coverage - the counts, table - the parsing table of the grammar the corpus was parsed with
+     @Returns: The report text
+*/
+pub fn format_uncovered(coverage: &Coverage, table: &Ll1Table) -> String {
+     let cells = filled_cells(table);
+     let uncovered_rules: Vec<usize> = (0..table.rules.len()).filter(|&rule| coverage.production_count(rule) == 0).collect();
+     let uncovered_cells: Vec<&(String, String)> =
+          cells.iter().filter(|(nonterminal, terminal)| coverage.cell_count(nonterminal, terminal) == 0).collect();
+
+     let mut output = String::new();
+     output.push_str(&format!("Productions covered: {}\n", ratio(table.rules.len() - uncovered_rules.len(), table.rules.len())));
+     output.push_str(&format!("Table cells covered: {}\n", ratio(cells.len() - uncovered_cells.len(), cells.len())));
+     output.push_str("Uncovered productions:\n");
+     for rule in uncovered_rules {
+          output.push_str(&format!("\t{}: {}\n", rule, table.rules[rule]));
+     }
+     output.push_str("Uncovered table cells:\n");
+     for (nonterminal, terminal) in uncovered_cells {
+          let rules: Vec<String> = table.find_cell(nonterminal, terminal).iter().map(|rule| rule.to_string()).collect();
+          output.push_str(&format!("\t{} on {} (production {})\n", nonterminal, terminal, rules.join(", ")));
+     }
+     output
+}
+
+/*
+     @Description: Lists the grammar with the use count of each production, "#####" for an unused one, and under each production
+                   the table cells that predict it with their counts
+     @Params: coverage - the counts, table - the parsing table of the grammar the corpus was parsed with
+     @Returns: The listing text
+*/
+pub fn annotated_listing(coverage: &Coverage, table: &Ll1Table) -> String {
+     let cells = filled_cells(table);
+     let count_text = |count: usize| if count == 0 { String::from("#####") } else { count.to_string() };
+
+     let mut output = String::new();
+     for (index, rule) in table.rules.iter().enumerate() {
+          output.push_str(&format!("{:>8} | {:>3} | {}\n", count_text(coverage.production_count(index)), index, rule));
+          let predicting: Vec<String> = cells
+               .iter()
+               .filter(|(nonterminal, terminal)| table.find_cell(nonterminal, terminal).contains(&index))
+               .map(|(nonterminal, terminal)| format!("{}: {}", terminal, count_text(coverage.cell_count(nonterminal, terminal))))
+               .collect();
+          if !predicting.is_empty() {
+               output.push_str(&format!("{:>8} |     |     on {}\n", "", predicting.join(", ")));
+          }
+     }
+     output
+}
@@ -0,0 +1,908 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::lexer::Token;
+use crate::pratt::OperatorTable;
+use crate::resilient::parse_classified;
+use crate::terminals::TerminalMap;
+use crate::tree::Span;
+
+/*
+     @Description: Token kinds for the source text the lexer does not turn into tokens; every other token's kind is its
+                   grammar terminal, e.g. "ID", "int" or ";"
+     @Params: None
+     @Returns: None
+*/
+pub const WHITESPACE: &str = "whitespace";
+pub const UNKNOWN: &str = "unknown";
+
+/*
+     @Description: Struct for a range of byte offsets into the source, end exclusive
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextRange {
+     pub start: usize,
+     pub end: usize,
+}
+
+impl TextRange {
+     /*
+          @Description: Tells whether an offset falls inside the range
+          @Params: offset - the byte offset
+          @Returns: True if start <= offset < end
+     */
+     pub fn contains(&self, offset: usize) -> bool {
+          self.start <= offset && offset < self.end
+     }
+}
+
+/*
+     @Description: String representation of a range, e.g. "12..40"
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for TextRange {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          write!(f, "{}..{}", self.start, self.end)
+     }
+}
+
+/*
+     @Description: Enum of the node kinds of the concrete syntax tree
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+     Program,
+     FnDef,
+     ParamList,
+     Param,
+     Decl,
+     StmtList,
+     AssignStmt,
+     IfStmt,
+     ElseClause,
+     WhileStmt,
+     PrintStmt,
+     ReturnStmt,
+     Name,
+     Literal,
+     ParenExpr,
+     PrefixExpr,
+     BinExpr,
+     CallExpr,
+     ArgList,
+     IndexExpr,
+     Error,
+}
+
+/*
+     @Description: String representation of a node kind
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for NodeKind {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          write!(f, "{:?}", self)
+     }
+}
+
+/*
+     @Description: Enum for the events a parser records as it goes, from which the tree is built: a node opens before its
+                   first token and closes after its last, and each Token event takes the next token that is not trivia
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+     Start(NodeKind),
+     Token,
+     Finish,
+}
+
+/*
+     @Description: Struct for an immutable green token: its kind and text, with no position, so equal tokens can be shared
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, PartialEq)]
+pub struct GreenToken {
+     pub kind: String,
+     pub text: String,
+}
+
+/*
+     @Description: Enum for a child of a green node; children are reference counted, so cloning a tree or building an
+                   edited copy shares every unchanged subtree
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum GreenElement {
+     Node(Rc<GreenNode>),
+     Token(Rc<GreenToken>),
+}
+
+impl GreenElement {
+     /*
+          @Description: Gets the length of the element's text
+          @Params: None
+          @Returns: The length in bytes
+     */
+     pub fn width(&self) -> usize {
+          match self {
+               GreenElement::Node(node) => node.width,
+               GreenElement::Token(token) => token.text.len(),
+          }
+     }
+}
+
+/*
+     @Description: Struct for an immutable green node: its kind, children and text length, with no position or parent
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, PartialEq)]
+pub struct GreenNode {
+     pub kind: NodeKind,
+     pub children: Vec<GreenElement>,
+     pub width: usize,
+}
+
+impl GreenNode {
+     /*
+          @Description: Creates a node, its width being the sum of its children's
+          @Params: kind - the node kind, children - the child nodes and tokens
+          @Returns: The node
+     */
+     pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> GreenNode {
+          let width = children.iter().map(|child| child.width()).sum();
+          GreenNode { kind, children, width }
+     }
+
+     /*
+          @Description: Builds a copy of the node with one child replaced; the other children are shared, not copied
+          @Params: index - the child to replace, child - the new child
+          @Returns: The new node
+     */
+     pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+          let mut children = self.children.clone();
+          children[index] = child;
+          GreenNode::new(self.kind, children)
+     }
+
+     /*
+          @Description: Appends the node's text
+          @Params: output - the text so far
+          @Returns: None
+     */
+     fn write_text(&self, output: &mut String) {
+          for child in &self.children {
+               match child {
+                    GreenElement::Node(node) => node.write_text(output),
+                    GreenElement::Token(token) => output.push_str(&token.text),
+               }
+          }
+     }
+}
+
+/*
+     @Description: Struct for the shared data of a red node: the green node, its parent, its index in the parent and its
+                   offset in the source, all computed lazily as the tree is walked
+     @Params: None
+     @Returns: None
+*/
+struct NodeData {
+     green: Rc<GreenNode>,
+     parent: Option<SyntaxNode>,
+     index: usize,
+     offset: usize,
+}
+
+/*
+     @Description: Struct for a red node, a green node seen at a position in one tree; cloning is a reference count bump
+     @Params: None
+     @Returns: None
+*/
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+/*
+     @Description: Struct for a red token, a green token seen at a position in one tree
+     @Params: None
+     @Returns: None
+*/
+#[derive(Clone)]
+pub struct SyntaxToken {
+     green: Rc<GreenToken>,
+     parent: SyntaxNode,
+     index: usize,
+     offset: usize,
+}
+
+/*
+     @Description: Enum for a child of a red node
+     @Params: None
+     @Returns: None
+*/
+#[derive(Clone)]
+pub enum SyntaxElement {
+     Node(SyntaxNode),
+     Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+     /*
+          @Description: Creates the root of a tree
+          @Params: green - the green root
+          @Returns: The red root, at offset 0
+     */
+     pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+          SyntaxNode(Rc::new(NodeData { green, parent: None, index: 0, offset: 0 }))
+     }
+
+     /*
+          @Description: Gets the kind of the node
+          @Params: None
+          @Returns: The node kind
+     */
+     pub fn kind(&self) -> NodeKind {
+          self.0.green.kind
+     }
+
+     /*
+          @Description: Gets the green node this node wraps, which other trees may share
+          @Params: None
+          @Returns: The green node
+     */
+     pub fn green(&self) -> Rc<GreenNode> {
+          self.0.green.clone()
+     }
+
+     /*
+          @Description: Gets the node containing this one
+          @Params: None
+          @Returns: The parent, or None at the root
+     */
+     pub fn parent(&self) -> Option<SyntaxNode> {
+          self.0.parent.clone()
+     }
+
+     /*
+          @Description: Gets the range of source text the node covers
+          @Params: None
+          @Returns: The range
+     */
+     pub fn text_range(&self) -> TextRange {
+          TextRange { start: self.0.offset, end: self.0.offset + self.0.green.width }
+     }
+
+     /*
+          @Description: Gets the node's source text, trivia included
+          @Params: None
+          @Returns: The text
+     */
+     pub fn text(&self) -> String {
+          let mut output = String::new();
+          self.0.green.write_text(&mut output);
+          output
+     }
+
+     /*
+          @Description: Lists the node's ancestors, nearest first
+          @Params: None
+          @Returns: The ancestors
+     */
+     pub fn ancestors(&self) -> Vec<SyntaxNode> {
+          let mut ancestors = Vec::new();
+          let mut current = self.parent();
+          while let Some(node) = current {
+               current = node.parent();
+               ancestors.push(node);
+          }
+          ancestors
+     }
+
+     /*
+          @Description: Lists the node's children, tokens included
+          @Params: None
+          @Returns: The children in source order
+     */
+     pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+          let mut offset = self.0.offset;
+          let mut children = Vec::new();
+          for (index, child) in self.0.green.children.iter().enumerate() {
+               match child {
+                    GreenElement::Node(node) => children.push(SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                         green: node.clone(),
+                         parent: Some(self.clone()),
+                         index,
+                         offset,
+                    })))),
+                    GreenElement::Token(token) => children.push(SyntaxElement::Token(SyntaxToken {
+                         green: token.clone(),
+                         parent: self.clone(),
+                         index,
+                         offset,
+                    })),
+               }
+               offset += child.width();
+          }
+          children
+     }
+
+     /*
+          @Description: Lists the node's child nodes
+          @Params: None
+          @Returns: The child nodes in source order
+     */
+     pub fn children(&self) -> Vec<SyntaxNode> {
+          self.children_with_tokens()
+               .into_iter()
+               .filter_map(|child| match child {
+                    SyntaxElement::Node(node) => Some(node),
+                    SyntaxElement::Token(_) => None,
+               })
+               .collect()
+     }
+
+     /*
+          @Description: Lists the node's child tokens, trivia included
+          @Params: None
+          @Returns: The child tokens in source order
+     */
+     pub fn tokens(&self) -> Vec<SyntaxToken> {
+          self.children_with_tokens()
+               .into_iter()
+               .filter_map(|child| match child {
+                    SyntaxElement::Token(token) => Some(token),
+                    SyntaxElement::Node(_) => None,
+               })
+               .collect()
+     }
+
+     /*
+          @Description: Gets the first child token of a kind
+          @Params: kind - the token kind
+          @Returns: The token, if any
+     */
+     pub fn token(&self, kind: &str) -> Option<SyntaxToken> {
+          self.tokens().into_iter().find(|token| token.kind() == kind)
+     }
+
+     /*
+          @Description: Gets the first child node of a kind
+          @Params: kind - the node kind
+          @Returns: The node, if any
+     */
+     pub fn child(&self, kind: NodeKind) -> Option<SyntaxNode> {
+          self.children().into_iter().find(|node| node.kind() == kind)
+     }
+
+     /*
+          @Description: Finds the deepest node whose range contains an offset
+          @Params: offset - the byte offset
+          @Returns: The node, or None if the offset is outside this node
+     */
+     pub fn node_at_offset(&self, offset: usize) -> Option<SyntaxNode> {
+          if !self.text_range().contains(offset) {
+               return None;
+          }
+          for child in self.children() {
+               if let Some(node) = child.node_at_offset(offset) {
+                    return Some(node);
+               }
+          }
+          Some(self.clone())
+     }
+
+     /*
+          @Description: Finds the token whose range contains an offset
+          @Params: offset - the byte offset
+          @Returns: The token, or None if the offset is outside this node
+     */
+     pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+          for child in self.children_with_tokens() {
+               match child {
+                    SyntaxElement::Node(node) if node.text_range().contains(offset) => return node.token_at_offset(offset),
+                    SyntaxElement::Token(token) if token.text_range().contains(offset) => return Some(token),
+                    _ => {}
+               }
+          }
+          None
+     }
+
+     /*
+          @Description: Builds a new tree with this node replaced; only the path from the node to the root is rebuilt
+                        and every other subtree is shared with the old tree, which stays unchanged
+          @Params: green - the replacement
+          @Returns: The root of the new tree
+     */
+     pub fn replace_with(&self, green: GreenNode) -> SyntaxNode {
+          let mut replacement = Rc::new(green);
+          let mut current = self.clone();
+          while let Some(parent) = current.parent() {
+               replacement = Rc::new(parent.0.green.replace_child(current.0.index, GreenElement::Node(replacement)));
+               current = parent;
+          }
+          SyntaxNode::new_root(replacement)
+     }
+}
+
+impl SyntaxToken {
+     /*
+          @Description: Gets the terminal of the token
+          @Params: None
+          @Returns: The terminal, e.g. "ID" or "whitespace"
+     */
+     pub fn kind(&self) -> &str {
+          &self.green.kind
+     }
+
+     /*
+          @Description: Gets the source text of the token
+          @Params: None
+          @Returns: The text
+     */
+     pub fn text(&self) -> &str {
+          &self.green.text
+     }
+
+     /*
+          @Description: Gets the node containing the token
+          @Params: None
+          @Returns: The parent node
+     */
+     pub fn parent(&self) -> SyntaxNode {
+          self.parent.clone()
+     }
+
+     /*
+          @Description: Gets the range of source text the token covers
+          @Params: None
+          @Returns: The range
+     */
+     pub fn text_range(&self) -> TextRange {
+          TextRange { start: self.offset, end: self.offset + self.green.text.len() }
+     }
+
+     /*
+          @Description: Tells whether the token is trivia, source text the grammar ignores
+          @Params: None
+          @Returns: True for whitespace
+     */
+     pub fn is_trivia(&self) -> bool {
+          self.green.kind == WHITESPACE
+     }
+
+     /*
+          @Description: Builds a new tree with this token's text replaced, keeping its kind
+          @Params: text - the new text
+          @Returns: The root of the new tree
+     */
+     pub fn replace_text(&self, text: &str) -> SyntaxNode {
+          let token = GreenToken { kind: self.green.kind.clone(), text: text.to_string() };
+          let parent = self.parent.0.green.replace_child(self.index, GreenElement::Token(Rc::new(token)));
+          self.parent.replace_with(parent)
+     }
+}
+
+/*
+     @Description: Picks one token at each position; the lexer can emit several at one position, e.g. `<=` and `<`, or an
+                   invalid `r` and the identifier `r5`, and a valid token wins over an invalid one, then the longest lexeme
+     @Params: tokens - the lexer's tokens, terminals - the token to terminal mapping
+     @Returns: The tokens kept, in order
+*/
+pub fn distinct_tokens<'t>(tokens: &'t [Token], terminals: &TerminalMap) -> Vec<&'t Token> {
+     let mut result: Vec<&Token> = Vec::new();
+     for token in tokens {
+          let rank = |token: &Token| (terminals.find_terminal(token).is_ok(), token.lexeme.len());
+          match result.last_mut() {
+               Some(last) if (last.line_number, last.column_number) == (token.line_number, token.column_number) => {
+                    if rank(token) > rank(last) {
+                         *last = token;
+                    }
+               }
+               _ => result.push(token),
+          }
+     }
+     result
+}
+
+/*
+     @Description: Lines a token stream up with the source it came from, so every byte belongs to a token: the text
+                   between tokens becomes whitespace or unknown tokens, invalid tokens become unknown tokens, and of the
+                   tokens at one position only the one distinct_tokens picks is kept
+     @Params: source - the source text, tokens - the lexer's tokens for it, terminals - the token to terminal mapping
+     @Returns: The green tokens, whose texts concatenate to the source, or an error naming a token that is not on its line
+*/
+pub fn lossless_tokens(source: &str, tokens: &[Token], terminals: &TerminalMap) -> Result<Vec<Rc<GreenToken>>, String> {
+     let mut result: Vec<Rc<GreenToken>> = Vec::new();
+     let mut cursor = 0;
+     let push_gap = |result: &mut Vec<Rc<GreenToken>>, gap: &str| {
+          let mut start = 0;
+          for (index, c) in gap.char_indices() {
+               let whitespace = c.is_whitespace();
+               let next_whitespace = gap[index + c.len_utf8()..].chars().next().map(char::is_whitespace);
+               if next_whitespace != Some(whitespace) {
+                    let kind = if whitespace { WHITESPACE } else { UNKNOWN };
+                    let text = gap[start..index + c.len_utf8()].to_string();
+                    result.push(Rc::new(GreenToken { kind: kind.to_string(), text }));
+                    start = index + c.len_utf8();
+               }
+          }
+     };
+
+     for token in distinct_tokens(tokens, terminals) {
+          if token.lexeme.is_empty() {
+               continue;
+          }
+          let rest = &source[cursor..];
+          let skipped = rest.len() - rest.trim_start().len();
+          let line_end = rest[skipped..].find('\n').map_or(rest.len(), |end| skipped + end);
+          let start = match rest[skipped..line_end].find(token.lexeme.as_str()) {
+               Some(found) => cursor + skipped + found,
+               None => {
+                    return Err(format!(
+                         "Error: Token `{}` at {}:{} is not in the source",
+                         token.lexeme, token.line_number, token.column_number
+                    ))
+               }
+          };
+          push_gap(&mut result, &source[cursor..start]);
+          let kind = terminals.find_terminal(token).unwrap_or_else(|_| UNKNOWN.to_string());
+          result.push(Rc::new(GreenToken { kind, text: token.lexeme.to_string() }));
+          cursor = start + token.lexeme.len();
+     }
+     push_gap(&mut result, &source[cursor..]);
+     Ok(result)
+}
+
+/*
+     @Description: Struct building the green tree from parser events; trivia, and tokens the parser never saw, go to the
+                   node that is open when the next token arrives
+     @Params: None
+     @Returns: None
+*/
+struct TreeSink {
+     tokens: Vec<Rc<GreenToken>>,
+     position: usize,
+     stack: Vec<(NodeKind, Vec<GreenElement>)>,
+}
+
+impl TreeSink {
+     /*
+          @Description: Moves the tokens before the next one the parser consumes into the open node
+          @Params: None
+          @Returns: None
+     */
+     fn flush_trivia(&mut self) {
+          while self.position < self.tokens.len() && is_skipped(&self.tokens[self.position]) {
+               let token = self.tokens[self.position].clone();
+               self.stack.last_mut().unwrap().1.push(GreenElement::Token(token));
+               self.position += 1;
+          }
+     }
+
+     /*
+          @Description: Applies one parser event; trivia before a token or a nested node joins the node open at that point
+          @Params: event - the event
+          @Returns: None
+     */
+     fn event(&mut self, event: Event) {
+          match event {
+               Event::Start(kind) => {
+                    if !self.stack.is_empty() {
+                         self.flush_trivia();
+                    }
+                    self.stack.push((kind, Vec::new()));
+               }
+               Event::Token => {
+                    self.flush_trivia();
+                    if let Some(token) = self.tokens.get(self.position).cloned() {
+                         self.stack.last_mut().unwrap().1.push(GreenElement::Token(token));
+                         self.position += 1;
+                    }
+               }
+               Event::Finish => {
+                    if self.stack.len() == 1 {
+                         let rest = self.tokens[self.position..].iter().map(|token| GreenElement::Token(token.clone()));
+                         self.stack[0].1.extend(rest);
+                         self.position = self.tokens.len();
+                         return;
+                    }
+                    let (kind, children) = self.stack.pop().unwrap();
+                    self.stack.last_mut().unwrap().1.push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+               }
+          }
+     }
+}
+
+/*
+     @Description: Tells whether a token is one the parser never sees: whitespace, or source text the lexer rejected
+     @Params: token - the token
+     @Returns: True if the token has no Token event
+*/
+fn is_skipped(token: &GreenToken) -> bool {
+     token.kind == WHITESPACE || token.kind == UNKNOWN
+}
+
+/*
+     @Description: Builds a green tree from parser events over a lossless token stream
+     @Params: tokens - the tokens, events - the events, starting with the root's Start and ending with its Finish
+     @Returns: The root node, holding every token
+*/
+pub fn build_tree(tokens: Vec<Rc<GreenToken>>, events: &[Event]) -> SyntaxNode {
+     let mut sink = TreeSink { tokens, position: 0, stack: Vec::new() };
+     for event in events {
+          sink.event(*event);
+     }
+     let (kind, children) = sink.stack.pop().unwrap_or((NodeKind::Program, Vec::new()));
+     SyntaxNode::new_root(Rc::new(GreenNode::new(kind, children)))
+}
+
+/*
+     @Description: Builds the lossless syntax tree of a source text from the resilient parser's events; the tree's text is
+                   always exactly the source, whatever errors the source has. The parser is given the same tokens the
+                   tree keeps, so each Token event lines up with a token of the tree
+     @Params: source - the source text, tokens - the lexer's tokens for it, terminals - the token to terminal mapping,
+              table - the operators for expressions
+     @Returns: The root node, or an error if the tokens do not line up with the source
+*/
+pub fn parse_cst(source: &str, tokens: &[Token], terminals: &TerminalMap, table: &OperatorTable) -> Result<SyntaxNode, String> {
+     let green = lossless_tokens(source, tokens, terminals)?;
+     let mut input = Vec::new();
+     for token in distinct_tokens(tokens, terminals) {
+          if let Ok(terminal) = terminals.find_terminal(token) {
+               if !token.lexeme.is_empty() {
+                    input.push((terminal, token.lexeme.to_string(), Span { line: token.line_number, column: token.column_number }));
+               }
+          }
+     }
+     let (_, events, _) = parse_classified(input, Vec::new(), table);
+     Ok(build_tree(green, &events))
+}
+
+/*
+     @Description: Prints a syntax tree one element per line with its range, e.g. `IfStmt@12..40` and `"then"@20..24`
+     @Params: node - the root of the dump
+     @Returns: The dump text
+*/
+pub fn dump(node: &SyntaxNode) -> String {
+     let mut output = String::new();
+     dump_node(node, 0, &mut output);
+     output
+}
+
+fn dump_node(node: &SyntaxNode, depth: usize, output: &mut String) {
+     output.push_str(&format!("{}{}@{}\n", "  ".repeat(depth), node.kind(), node.text_range()));
+     for child in node.children_with_tokens() {
+          match child {
+               SyntaxElement::Node(child) => dump_node(&child, depth + 1, output),
+               SyntaxElement::Token(token) => {
+                    output.push_str(&format!("{}{} {:?}@{}\n", "  ".repeat(depth + 1), token.kind(), token.text(), token.text_range()))
+               }
+          }
+     }
+}
+
+/*
+     @Description: Struct wrapping a Program node
+     @Params: None
+     @Returns: None
+*/
+pub struct Program(pub SyntaxNode);
+
+impl Program {
+     /*
+          @Description: Wraps a node if it is a Program
+          @Params: node - the node
+          @Returns: The typed node, or None for a node of another kind
+     */
+     pub fn cast(node: SyntaxNode) -> Option<Program> {
+          if node.kind() == NodeKind::Program { Some(Program(node)) } else { None }
+     }
+
+     /*
+          @Description: Gets the function definitions
+          @Params: None
+          @Returns: The functions, in source order
+     */
+     pub fn functions(&self) -> Vec<FnDef> {
+          self.0.children().into_iter().filter_map(FnDef::cast).collect()
+     }
+
+     /*
+          @Description: Gets the global declarations
+          @Params: None
+          @Returns: The Decl nodes, in source order
+     */
+     pub fn declarations(&self) -> Vec<SyntaxNode> {
+          self.0.children().into_iter().filter(|node| node.kind() == NodeKind::Decl).collect()
+     }
+
+     /*
+          @Description: Gets the main statements
+          @Params: None
+          @Returns: The statement list, or None when the source leaves it out
+     */
+     pub fn body(&self) -> Option<SyntaxNode> {
+          self.0.child(NodeKind::StmtList)
+     }
+}
+
+/*
+     @Description: Struct wrapping a FnDef node
+     @Params: None
+     @Returns: None
+*/
+pub struct FnDef(pub SyntaxNode);
+
+impl FnDef {
+     /*
+          @Description: Wraps a node if it is a FnDef
+          @Params: node - the node
+          @Returns: The typed node, or None for a node of another kind
+     */
+     pub fn cast(node: SyntaxNode) -> Option<FnDef> {
+          if node.kind() == NodeKind::FnDef { Some(FnDef(node)) } else { None }
+     }
+
+     /*
+          @Description: Gets the function's name
+          @Params: None
+          @Returns: The ID token, or None when the source leaves it out
+     */
+     pub fn name(&self) -> Option<SyntaxToken> {
+          self.0.token("ID")
+     }
+
+     /*
+          @Description: Gets the return type
+          @Params: None
+          @Returns: The int or double token, or None when the source leaves it out
+     */
+     pub fn return_type(&self) -> Option<SyntaxToken> {
+          self.0.tokens().into_iter().find(|token| token.kind() == "int" || token.kind() == "double")
+     }
+
+     /*
+          @Description: Gets the parameters
+          @Params: None
+          @Returns: The Param nodes, empty without a parameter list
+     */
+     pub fn params(&self) -> Vec<SyntaxNode> {
+          self.0.child(NodeKind::ParamList).map_or(Vec::new(), |list| list.children())
+     }
+
+     /*
+          @Description: Gets the function's statements
+          @Params: None
+          @Returns: The statement list, or None when the source leaves it out
+     */
+     pub fn body(&self) -> Option<SyntaxNode> {
+          self.0.child(NodeKind::StmtList)
+     }
+}
+
+/*
+     @Description: Struct wrapping an IfStmt node
+     @Params: None
+     @Returns: None
+*/
+pub struct IfStmt(pub SyntaxNode);
+
+impl IfStmt {
+     /*
+          @Description: Wraps a node if it is an IfStmt
+          @Params: node - the node
+          @Returns: The typed node, or None for a node of another kind
+     */
+     pub fn cast(node: SyntaxNode) -> Option<IfStmt> {
+          if node.kind() == NodeKind::IfStmt { Some(IfStmt(node)) } else { None }
+     }
+
+     /*
+          @Description: Gets the condition
+          @Params: None
+          @Returns: The expression node, or None when the source leaves it out
+     */
+     pub fn condition(&self) -> Option<SyntaxNode> {
+          self.0.children().into_iter().find(|node| node.kind() != NodeKind::StmtList && node.kind() != NodeKind::ElseClause)
+     }
+
+     /*
+          @Description: Gets the statements after then
+          @Params: None
+          @Returns: The statement list, or None when the source leaves it out
+     */
+     pub fn then_branch(&self) -> Option<SyntaxNode> {
+          self.0.child(NodeKind::StmtList)
+     }
+
+     /*
+          @Description: Gets the statements after else
+          @Params: None
+          @Returns: The statement list, or None without an else
+     */
+     pub fn else_branch(&self) -> Option<SyntaxNode> {
+          self.0.child(NodeKind::ElseClause).and_then(|clause| clause.child(NodeKind::StmtList))
+     }
+}
+
+/*
+     @Description: Struct wrapping a WhileStmt node
+     @Params: None
+     @Returns: None
+*/
+pub struct WhileStmt(pub SyntaxNode);
+
+impl WhileStmt {
+     /*
+          @Description: Wraps a node if it is a WhileStmt
+          @Params: node - the node
+          @Returns: The typed node, or None for a node of another kind
+     */
+     pub fn cast(node: SyntaxNode) -> Option<WhileStmt> {
+          if node.kind() == NodeKind::WhileStmt { Some(WhileStmt(node)) } else { None }
+     }
+
+     /*
+          @Description: Gets the condition
+          @Params: None
+          @Returns: The expression node, or None when the source leaves it out
+     */
+     pub fn condition(&self) -> Option<SyntaxNode> {
+          self.0.children().into_iter().find(|node| node.kind() != NodeKind::StmtList)
+     }
+
+     /*
+          @Description: Gets the statements after do
+          @Params: None
+          @Returns: The statement list, or None when the source leaves it out
+     */
+     pub fn body(&self) -> Option<SyntaxNode> {
+          self.0.child(NodeKind::StmtList)
+     }
+}
+
+/*
+     @Description: Struct wrapping an AssignStmt node
+     @Params: None
+     @Returns: None
+*/
+pub struct AssignStmt(pub SyntaxNode);
+
+impl AssignStmt {
+     /*
+          @Description: Wraps a node if it is an AssignStmt
+          @Params: node - the node
+          @Returns: The typed node, or None for a node of another kind
+     */
+     pub fn cast(node: SyntaxNode) -> Option<AssignStmt> {
+          if node.kind() == NodeKind::AssignStmt { Some(AssignStmt(node)) } else { None }
+     }
+
+     /*
+          @Description: Gets the assigned variable
+          @Params: None
+          @Returns: The Name or IndexExpr node before the "=", if any
+     */
+     pub fn target(&self) -> Option<SyntaxNode> {
+          let equals = self.0.token("=").map(|token| token.text_range().start);
+          self.0.children().into_iter().find(|node| equals.is_none_or(|start| node.text_range().end <= start))
+     }
+
+     /*
+          @Description: Gets the assigned value
+          @Params: None
+          @Returns: The expression node after the "=", if any
+     */
+     pub fn value(&self) -> Option<SyntaxNode> {
+          let equals = self.0.token("=")?.text_range().end;
+          self.0.children().into_iter().find(|node| node.text_range().start >= equals)
+     }
+}
@@ -0,0 +1,247 @@
+use std::fs;
+
+use crate::grammar::{nonterminals, split_rule};
+use crate::report::{write_file, xml_escape};
+use crate::tree::dot_escape;
+
+/*
+     @Description: Width of one character of box text in pixels; the text is monospace, so a box is sized without measuring it
+     @Params: None
+     @Returns: None
+*/
+const CHAR_WIDTH: usize = 8;
+
+/*
+     @Description: Height of a symbol box in pixels
+     @Params: None
+     @Returns: None
+*/
+const BOX_HEIGHT: usize = 24;
+
+/*
+     @Description: Space in pixels between a box's text and its left and right edges
+     @Params: None
+     @Returns: None
+*/
+const BOX_PADDING: usize = 10;
+
+/*
+     @Description: Length in pixels of the line between two boxes, and before the first and after the last box of an alternative
+     @Params: None
+     @Returns: None
+*/
+const BOX_GAP: usize = 16;
+
+/*
+     @Description: Vertical distance in pixels between the lines of two alternatives
+     @Params: None
+     @Returns: None
+*/
+const ROW_HEIGHT: usize = 40;
+
+/*
+     @Description: Length in pixels of the rails on either side, where the line enters and leaves the diagram and the
+                   alternatives below the first branch off and rejoin it
+     @Params: None
+     @Returns: None
+*/
+const RAIL: usize = 30;
+
+/*
+     @Description: Space in pixels around the diagram; the title sits in the top margin
+     @Params: None
+     @Returns: None
+*/
+const MARGIN: usize = 20;
+
+/*
+     @Description: Groups the right hand sides of a grammar by left hand side
+     @Params: grammar - the grammar rules
+     @Returns: Each nonterminal with its alternatives, in definition order
+*/
+pub fn alternatives(grammar: &[String]) -> Vec<(String, Vec<Vec<String>>)> {
+     let mut result: Vec<(String, Vec<Vec<String>>)> =
+          nonterminals(grammar).into_iter().map(|nonterminal| (nonterminal, Vec::new())).collect();
+     for rule in grammar {
+          let (lhs, rhs) = split_rule(rule);
+          if let Some(entry) = result.iter_mut().find(|(nonterminal, _)| *nonterminal == lhs) {
+               entry.1.push(rhs);
+          }
+     }
+     result
+}
+
+/*
+     @Description: Renders the nonterminal dependency graph as a Graphviz DOT digraph, with an edge from each
+                   nonterminal to every nonterminal on its right hand sides and the start symbol drawn in bold
+     @Params: grammar - the grammar rules
+     @Returns: The DOT source
+*/
+pub fn dependency_dot(grammar: &[String]) -> String {
+     let groups = alternatives(grammar);
+     let mut output = String::from("digraph grammar {\n     node [fontname=\"monospace\", shape=box];\n");
+     for (index, (nonterminal, _)) in groups.iter().enumerate() {
+          if index == 0 {
+               output.push_str(&format!("     \"{}\" [style=bold];\n", dot_escape(nonterminal)));
+          } else {
+               output.push_str(&format!("     \"{}\";\n", dot_escape(nonterminal)));
+          }
+     }
+     for (nonterminal, rhs_list) in &groups {
+          let mut targets: Vec<&String> = Vec::new();
+          for symbol in rhs_list.iter().flatten() {
+               if groups.iter().any(|(n, _)| n == symbol) && !targets.contains(&symbol) {
+                    targets.push(symbol);
+               }
+          }
+          for target in targets {
+               output.push_str(&format!("     \"{}\" -> \"{}\";\n", dot_escape(nonterminal), dot_escape(target)));
+          }
+     }
+     output.push_str("}\n");
+     output
+}
+
+/*
+     @Description: Gets the width of the box drawn for a symbol
+     @Params: symbol - the grammar symbol
+     @Returns: The width in pixels
+*/
+fn box_width(symbol: &str) -> usize {
+     symbol.chars().count() * CHAR_WIDTH + 2 * BOX_PADDING
+}
+
+/*
+     @Description: Gets the width of an alternative drawn as a row of boxes
+     @Params: symbols - the right hand side symbols
+     @Returns: The width in pixels, without the rails
+*/
+fn sequence_width(symbols: &[String]) -> usize {
+     symbols.iter().map(|symbol| box_width(symbol) + BOX_GAP).sum::<usize>() + BOX_GAP
+}
+
+/*
+     @Description: Renders the alternatives of a nonterminal as a standalone SVG railroad diagram; terminals are
+                   rounded boxes, nonterminals are square boxes linking to their own diagram, and an empty
+                   alternative is a bare line
+     @Params: nonterminal - the left hand side, rhs_list - its alternatives, nonterminal_names - every nonterminal of the grammar
+     @Returns: The SVG source
+*/
+pub fn railroad_svg(nonterminal: &str, rhs_list: &[Vec<String>], nonterminal_names: &[String]) -> String {
+     let inner_width = rhs_list.iter().map(|symbols| sequence_width(symbols)).max().unwrap_or(BOX_GAP);
+     let width = 2 * MARGIN + 2 * RAIL + inner_width;
+     let top = MARGIN + BOX_HEIGHT;
+     let height = top + ROW_HEIGHT * rhs_list.len().max(1) + MARGIN / 2;
+     let left = MARGIN + RAIL;
+     let right = left + inner_width;
+
+     let mut output = format!(
+          "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+           width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+          width, height, width, height
+     );
+     output.push_str(
+          "<style>\n\
+           path { fill: none; stroke: #333; stroke-width: 2; }\n\
+           rect { fill: #fff; stroke: #333; stroke-width: 2; }\n\
+           rect.terminal { fill: #e8f0ff; }\n\
+           text { font-family: monospace; font-size: 13px; fill: #000; }\n\
+           text.title { font-weight: bold; }\n\
+           </style>\n",
+     );
+     output.push_str(&format!(
+          "<text class=\"title\" x=\"{}\" y=\"{}\">{}</text>\n",
+          MARGIN,
+          MARGIN,
+          xml_escape(nonterminal)
+     ));
+
+     let main_line = top + ROW_HEIGHT / 2;
+     output.push_str(&format!("<path d=\"M{} {} h{}\"/>\n", MARGIN, main_line, RAIL));
+     output.push_str(&format!("<path d=\"M{} {} h{}\"/>\n", right, main_line, RAIL));
+     for (row, symbols) in rhs_list.iter().enumerate() {
+          let y = main_line + row * ROW_HEIGHT;
+          if row > 0 {
+               let curve = ROW_HEIGHT / 4;
+               output.push_str(&format!(
+                    "<path d=\"M{} {} q{} 0 {} {} V{} q0 {} {} {}\"/>\n",
+                    left - 2 * curve,
+                    main_line,
+                    curve,
+                    curve,
+                    curve,
+                    y - curve,
+                    curve,
+                    curve,
+                    curve
+               ));
+               output.push_str(&format!(
+                    "<path d=\"M{} {} q{} 0 {} -{} V{} q0 -{} {} -{}\"/>\n",
+                    right,
+                    y,
+                    curve,
+                    curve,
+                    curve,
+                    main_line + curve,
+                    curve,
+                    curve,
+                    curve
+               ));
+          }
+
+          let mut x = left;
+          for symbol in symbols {
+               let box_width = box_width(symbol);
+               output.push_str(&format!("<path d=\"M{} {} h{}\"/>\n", x, y, BOX_GAP));
+               x += BOX_GAP;
+               let box_top = y - BOX_HEIGHT / 2;
+               let text = format!(
+                    "<text x=\"{}\" y=\"{}\">{}</text>",
+                    x + BOX_PADDING,
+                    y + 4,
+                    xml_escape(symbol)
+               );
+               if nonterminal_names.contains(symbol) {
+                    output.push_str(&format!(
+                         "<a xlink:href=\"{}.svg\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>{}</a>\n",
+                         xml_escape(symbol),
+                         x,
+                         box_top,
+                         box_width,
+                         BOX_HEIGHT,
+                         text
+                    ));
+               } else {
+                    output.push_str(&format!(
+                         "<rect class=\"terminal\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/>{}\n",
+                         x,
+                         box_top,
+                         box_width,
+                         BOX_HEIGHT,
+                         BOX_HEIGHT / 2,
+                         text
+                    ));
+               }
+               x += box_width;
+          }
+          output.push_str(&format!("<path d=\"M{} {} H{}\"/>\n", x, y, right));
+     }
+     output.push_str("</svg>\n");
+     output
+}
+
+/*
+     @Description: Writes one railroad diagram per nonterminal to <directory>/<nonterminal>.svg, creating the directory
+     @Params: grammar - the grammar rules, directory - the output directory
+     @Returns: An error message if a diagram could not be written
+*/
+pub fn write_railroad_diagrams(grammar: &[String], directory: &str) -> Result<(), String> {
+     let directory = directory.trim_end_matches('/');
+     fs::create_dir_all(directory).map_err(|e| format!("Error: Unable to create {}: {}", directory, e))?;
+     let groups = alternatives(grammar);
+     let names: Vec<String> = groups.iter().map(|(nonterminal, _)| nonterminal.clone()).collect();
+     for (nonterminal, rhs_list) in &groups {
+          write_file(&format!("{}/{}.svg", directory, nonterminal), &railroad_svg(nonterminal, rhs_list, &names))?;
+     }
+     Ok(())
+}
@@ -0,0 +1,446 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::grammar::{first_sets, make_rule, split_rule, EPSILON};
+
+/*
+     @Description: Key of a forest node: the symbol and the input span [start, end) it derives
+     @Params: None
+     @Returns: None
+*/
+pub type NodeKey = (String, usize, usize);
+
+/*
+     @Description: Enum of the children of a packed forest family
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForestChild {
+     Terminal(usize),
+     Node(NodeKey),
+}
+
+/*
+     @Description: Enum for a single parse tree taken out of the forest, leaves hold input positions and nodes hold rule indexes
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Derivation {
+     Leaf(usize),
+     Node(usize, Vec<Derivation>),
+}
+
+/*
+     @Description: Struct for a shared packed parse forest, each node lists its alternative families as (rule, children)
+     @Params: None
+     @Returns: None
+*/
+pub struct Forest {
+     pub rules: Vec<(String, Vec<String>)>,
+     pub input: Vec<String>,
+     pub nodes: HashMap<NodeKey, Vec<(usize, Vec<ForestChild>)>>,
+     pub root: NodeKey,
+}
+
+/*
+     @Description: Struct for the rules used to pick one tree from an ambiguous forest
+     @Params: None
+     @Returns: None
+*/
+pub struct Disambiguation {
+     pub priorities: Vec<usize>,
+     pub longest_first: bool,
+}
+
+impl Disambiguation {
+     /*
+          @Description: Ranks a rule above every rule not added yet; unranked rules fall back to grammar order
+          @Params: rule - the rule index
+          @Returns: None
+     */
+     pub fn add_priority(&mut self, rule: usize) {
+          self.priorities.push(rule);
+     }
+
+     /*
+          @Description: Gets the sort key of a rule; ranked rules come first in the order they were added
+          @Params: rule - the rule index
+          @Returns: (0, rank) for a ranked rule, (1, rule) otherwise
+     */
+     fn rank(&self, rule: usize) -> (usize, usize) {
+          match self.priorities.iter().position(|r| *r == rule) {
+               Some(rank) => (0, rank),
+               None => (1, rule),
+          }
+     }
+}
+
+impl Default for Disambiguation {
+     /*
+          @Description: Creates the rules without priorities, preferring the longest match when rules tie
+          @Params: None
+          @Returns: The disambiguation rules
+     */
+     fn default() -> Disambiguation {
+          Disambiguation { priorities: Vec::new(), longest_first: true }
+     }
+}
+
+/*
+     @Description: Runs the Earley recogniser over a sequence of terminals and builds the parse forest
+     @Params: grammar - the grammar rules, the first rule's left hand side is the start symbol, input - the terminals
+     @Returns: The parse forest or an error naming the first position no item could continue from
+*/
+pub fn earley_parse(grammar: &[String], input: &[String]) -> Result<Forest, String> {
+     let rules: Vec<(String, Vec<String>)> = grammar.iter().map(|rule| split_rule(rule)).collect();
+     let first = first_sets(grammar);
+     let start = rules[0].0.clone();
+
+     let mut sets: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); input.len() + 1];
+     let mut seen: Vec<HashSet<(usize, usize, usize)>> = vec![HashSet::new(); input.len() + 1];
+
+     for (rule, (lhs, _)) in rules.iter().enumerate() {
+          if *lhs == start && seen[0].insert((rule, 0, 0)) {
+               sets[0].push((rule, 0, 0));
+          }
+     }
+
+     for position in 0..=input.len() {
+          let mut index = 0;
+          while index < sets[position].len() {
+               let (rule, dot, origin) = sets[position][index];
+               let mut added: Vec<(usize, (usize, usize, usize))> = Vec::new();
+
+               match rules[rule].1.get(dot) {
+                    Some(symbol) if first.contains_key(symbol) => {
+                         for (other, (lhs, _)) in rules.iter().enumerate() {
+                              if lhs == symbol {
+                                   added.push((position, (other, 0, position)));
+                              }
+                         }
+                         if first[symbol].contains(EPSILON) {
+                              added.push((position, (rule, dot + 1, origin)));
+                         }
+                    }
+                    Some(symbol) => {
+                         if position < input.len() && input[position] == *symbol {
+                              added.push((position + 1, (rule, dot + 1, origin)));
+                         }
+                    }
+                    None => {
+                         for &(waiting, waiting_dot, waiting_origin) in &sets[origin] {
+                              if rules[waiting].1.get(waiting_dot) == Some(&rules[rule].0) {
+                                   added.push((position, (waiting, waiting_dot + 1, waiting_origin)));
+                              }
+                         }
+                    }
+               }
+
+               for (target, item) in added {
+                    if seen[target].insert(item) {
+                         sets[target].push(item);
+                    }
+               }
+               index += 1;
+          }
+
+          if position < input.len() && sets[position + 1].is_empty() {
+               let mut expected: Vec<&String> = Vec::new();
+               for &(rule, dot, _) in &sets[position] {
+                    if let Some(symbol) = rules[rule].1.get(dot) {
+                         if !first.contains_key(symbol) && !expected.contains(&symbol) {
+                              expected.push(symbol);
+                         }
+                    }
+               }
+               expected.sort();
+               return Err(format!(
+                    "Error: Unexpected {} at token {}, expected one of {}",
+                    input[position],
+                    position + 1,
+                    expected.iter().map(|s| s.as_str()).collect::<Vec<&str>>().join(", ")
+               ));
+          }
+     }
+
+     let mut completed: HashSet<NodeKey> = HashSet::new();
+     for (end, set) in sets.iter().enumerate() {
+          for &(rule, dot, origin) in set {
+               if dot == rules[rule].1.len() {
+                    completed.insert((rules[rule].0.clone(), origin, end));
+               }
+          }
+     }
+
+     let root = (start, 0, input.len());
+     if !completed.contains(&root) {
+          return Err(String::from("Error: Unexpected end of input"));
+     }
+
+     let mut forest = Forest { rules, input: input.to_vec(), nodes: HashMap::new(), root: root.clone() };
+     build_node(&mut forest, &seen, &completed, root);
+     Ok(forest)
+}
+
+/*
+     @Description: Adds a node and every node below it to the forest, finding each way the node's rules split its span
+     @Params: forest - the forest being built, sets - the items of each Earley set, completed - the recognised (symbol, start, end) spans, key - the node to add
+     @Returns: None
+*/
+fn build_node(forest: &mut Forest, sets: &[HashSet<(usize, usize, usize)>], completed: &HashSet<NodeKey>, key: NodeKey) {
+     if forest.nodes.contains_key(&key) {
+          return;
+     }
+     forest.nodes.insert(key.clone(), Vec::new());
+
+     let (symbol, start, end) = key.clone();
+     let mut families = Vec::new();
+     for rule in 0..forest.rules.len() {
+          if forest.rules[rule].0 != symbol || !sets[end].contains(&(rule, forest.rules[rule].1.len(), start)) {
+               continue;
+          }
+          for children in splits(forest, sets, completed, rule, 0, start, start, end) {
+               families.push((rule, children));
+          }
+     }
+
+     for (_, children) in &families {
+          for child in children {
+               if let ForestChild::Node(child_key) = child {
+                    build_node(forest, sets, completed, child_key.clone());
+               }
+          }
+     }
+     forest.nodes.insert(key, families);
+}
+
+/*
+     @Description: Finds every way the symbols of a rule from a given index on derive the input between two positions
+     @Params: forest - the forest, sets - the items of each Earley set, completed - the recognised spans, rule - the rule, symbol - the index of the next symbol, origin - where the rule started, position - where the next symbol starts, end - where the rule ends
+     @Returns: The possible child lists
+*/
+#[allow(clippy::too_many_arguments)]
+fn splits(
+     forest: &Forest,
+     sets: &[HashSet<(usize, usize, usize)>],
+     completed: &HashSet<NodeKey>,
+     rule: usize,
+     symbol: usize,
+     origin: usize,
+     position: usize,
+     end: usize,
+) -> Vec<Vec<ForestChild>> {
+     let rhs = &forest.rules[rule].1;
+     if symbol == rhs.len() {
+          return if position == end { vec![Vec::new()] } else { Vec::new() };
+     }
+
+     let mut result = Vec::new();
+     let is_nonterminal = forest.rules.iter().any(|(lhs, _)| *lhs == rhs[symbol]);
+     for next in position..=end {
+          let child = if is_nonterminal {
+               if !completed.contains(&(rhs[symbol].clone(), position, next)) {
+                    continue;
+               }
+               ForestChild::Node((rhs[symbol].clone(), position, next))
+          } else {
+               if next != position + 1 || forest.input[position] != rhs[symbol] {
+                    continue;
+               }
+               ForestChild::Terminal(position)
+          };
+          if !sets[next].contains(&(rule, symbol + 1, origin)) {
+               continue;
+          }
+          for mut rest in splits(forest, sets, completed, rule, symbol + 1, origin, next, end) {
+               rest.insert(0, child.clone());
+               result.push(rest);
+          }
+     }
+     result
+}
+
+impl Forest {
+     /*
+          @Description: Counts the distinct parse trees in the forest, ignoring derivations that loop through a cycle
+          @Params: None
+          @Returns: The number of trees, saturating at u64::MAX
+     */
+     pub fn count(&self) -> u64 {
+          let mut memo: HashMap<NodeKey, u64> = HashMap::new();
+          self.count_node(&self.root, &mut memo, &mut HashSet::new())
+     }
+
+     fn count_node(&self, key: &NodeKey, memo: &mut HashMap<NodeKey, u64>, visiting: &mut HashSet<NodeKey>) -> u64 {
+          if let Some(count) = memo.get(key) {
+               return *count;
+          }
+          if !visiting.insert(key.clone()) {
+               return 0;
+          }
+          let mut total: u64 = 0;
+          for (_, children) in &self.nodes[key] {
+               let mut product: u64 = 1;
+               for child in children {
+                    if let ForestChild::Node(child_key) = child {
+                         product = product.saturating_mul(self.count_node(child_key, memo, visiting));
+                    }
+               }
+               total = total.saturating_add(product);
+          }
+          visiting.remove(key);
+          memo.insert(key.clone(), total);
+          total
+     }
+
+     /*
+          @Description: Lists the parse trees in the forest; each node keeps at most limit trees and is expanded once, so
+                        the work grows with the forest and the limit rather than with the number of trees
+          @Params: limit - the maximum number of trees to return
+          @Returns: Up to limit trees
+     */
+     pub fn trees(&self, limit: usize) -> Vec<Derivation> {
+          let mut memo: HashMap<NodeKey, Rc<Vec<Derivation>>> = HashMap::new();
+          self.node_trees(&self.root, limit, &mut memo, &mut HashSet::new()).to_vec()
+     }
+
+     fn node_trees(
+          &self,
+          key: &NodeKey,
+          limit: usize,
+          memo: &mut HashMap<NodeKey, Rc<Vec<Derivation>>>,
+          visiting: &mut HashSet<NodeKey>,
+     ) -> Rc<Vec<Derivation>> {
+          if let Some(trees) = memo.get(key) {
+               return Rc::clone(trees);
+          }
+          if limit == 0 || !visiting.insert(key.clone()) {
+               return Rc::new(Vec::new());
+          }
+          let mut result = Vec::new();
+          for (rule, children) in &self.nodes[key] {
+               if result.len() >= limit {
+                    break;
+               }
+               let mut partial: Vec<Vec<Derivation>> = vec![Vec::new()];
+               for child in children {
+                    let options = match child {
+                         ForestChild::Terminal(position) => Rc::new(vec![Derivation::Leaf(*position)]),
+                         ForestChild::Node(child_key) => self.node_trees(child_key, limit, memo, visiting),
+                    };
+                    let mut next = Vec::new();
+                    'prefixes: for prefix in &partial {
+                         for option in options.iter() {
+                              if next.len() >= limit - result.len() {
+                                   break 'prefixes;
+                              }
+                              let mut extended = prefix.clone();
+                              extended.push(option.clone());
+                              next.push(extended);
+                         }
+                    }
+                    partial = next;
+                    if partial.is_empty() {
+                         break;
+                    }
+               }
+               result.extend(partial.into_iter().map(|children| Derivation::Node(*rule, children)));
+          }
+          visiting.remove(key);
+          let result = Rc::new(result);
+          memo.insert(key.clone(), Rc::clone(&result));
+          result
+     }
+
+     /*
+          @Description: Picks one tree by preferring higher ranked rules, then the split with the longest (or shortest) first child
+          @Params: rules - the disambiguation rules
+          @Returns: The chosen tree, or None if every derivation loops
+     */
+     pub fn disambiguate(&self, rules: &Disambiguation) -> Option<Derivation> {
+          self.choose(&self.root, rules, &mut HashSet::new())
+     }
+
+     fn choose(&self, key: &NodeKey, rules: &Disambiguation, visiting: &mut HashSet<NodeKey>) -> Option<Derivation> {
+          if !visiting.insert(key.clone()) {
+               return None;
+          }
+          let mut families: Vec<&(usize, Vec<ForestChild>)> = self.nodes[key].iter().collect();
+          families.sort_by_key(|(rule, children)| {
+               let first_end = match children.first() {
+                    Some(ForestChild::Node((_, _, end))) => *end,
+                    Some(ForestChild::Terminal(position)) => position + 1,
+                    None => key.1,
+               };
+               let split = if rules.longest_first { usize::MAX - first_end } else { first_end };
+               (rules.rank(*rule), split)
+          });
+
+          let mut chosen = None;
+          'families: for (rule, children) in families {
+               let mut trees = Vec::new();
+               for child in children {
+                    match child {
+                         ForestChild::Terminal(position) => trees.push(Derivation::Leaf(*position)),
+                         ForestChild::Node(child_key) => match self.choose(child_key, rules, visiting) {
+                              Some(tree) => trees.push(tree),
+                              None => continue 'families,
+                         },
+                    }
+               }
+               chosen = Some(Derivation::Node(*rule, trees));
+               break;
+          }
+          visiting.remove(key);
+          chosen
+     }
+
+     /*
+          @Description: Formats a tree as a bracketed sketch such as expr(term(ID) + term(NUMBER))
+          @Params: tree - the tree
+          @Returns: The sketch
+     */
+     pub fn format_tree(&self, tree: &Derivation) -> String {
+          match tree {
+               Derivation::Leaf(position) => self.input[*position].to_string(),
+               Derivation::Node(rule, children) => {
+                    let inner: Vec<String> = children.iter().map(|child| self.format_tree(child)).collect();
+                    format!("{}({})", self.rules[*rule].0, inner.join(" "))
+               }
+          }
+     }
+
+     /*
+          @Description: Lists the rules used by a tree in leftmost derivation order
+          @Params: tree - the tree
+          @Returns: The rules
+     */
+     pub fn derivation_rules(&self, tree: &Derivation) -> Vec<String> {
+          let mut result = Vec::new();
+          if let Derivation::Node(rule, children) = tree {
+               result.push(make_rule(&self.rules[*rule].0, &self.rules[*rule].1));
+               for child in children {
+                    result.extend(self.derivation_rules(child));
+               }
+          }
+          result
+     }
+}
+
+/*
+     @Description: Prints the number of parses and up to limit of them as tree sketches
+     @Params: forest - the parse forest, limit - the maximum number of trees to print
+     @Returns: None
+*/
+pub fn print_parses(forest: &Forest, limit: usize) {
+     let count = forest.count();
+     println!("Parses: {}", count);
+     for (index, tree) in forest.trees(limit).iter().enumerate() {
+          println!("\t{}: {}", index + 1, forest.format_tree(tree));
+     }
+     if count > limit as u64 {
+          println!("\t... {} more", count - limit as u64);
+     }
+}
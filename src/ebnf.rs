@@ -0,0 +1,304 @@
+use crate::grammar::{make_rule, SourceRule};
+use crate::transform::fresh_name;
+
+/*
+     @Description: Enum of the tokens of an EBNF definition body
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+enum EbnfToken {
+     Nonterminal(String),
+     Terminal(String),
+     Open(char),
+     Close(char),
+     Bar,
+     Suffix(char),
+}
+
+/*
+     @Description: Enum for a parsed EBNF term; a group holds its alternatives and its repetition, one of
+                   ' ' for a plain group, '*' for zero or more, '+' for one or more and '?' for optional
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone)]
+enum Term {
+     Symbol(String, bool),
+     Group(Vec<Vec<Term>>, char),
+}
+
+/*
+     @Description: Splits one line of an EBNF body into tokens; a "*", "+" or "?" written directly after ">", ")", "]", "}"
+                   or a closing quote is a repetition, anywhere else it is a terminal like any other word
+     @Params: text - the line, line - its line number, tokens - the tokens so far
+     @Returns: An error message for an unterminated quote
+*/
+fn tokenize(text: &str, line: usize, tokens: &mut Vec<(EbnfToken, usize)>) -> Result<(), String> {
+     let chars: Vec<char> = text.chars().collect();
+     let mut index = 0;
+     let mut attached = false;
+     while index < chars.len() {
+          let c = chars[index];
+          if c.is_whitespace() {
+               attached = false;
+               index += 1;
+               continue;
+          }
+
+          let token = if "*+?".contains(c) && attached {
+               index += 1;
+               EbnfToken::Suffix(c)
+          } else if "([{".contains(c) {
+               index += 1;
+               EbnfToken::Open(c)
+          } else if ")]}".contains(c) {
+               index += 1;
+               EbnfToken::Close(c)
+          } else if c == '|' {
+               index += 1;
+               EbnfToken::Bar
+          } else if c == '"' || c == '\'' {
+               let end = match chars[index + 1..].iter().position(|&other| other == c) {
+                    Some(offset) => index + 1 + offset,
+                    None => return Err(format!("Error: Unterminated quote on line {}", line)),
+               };
+               let terminal: String = chars[index + 1..end].iter().collect();
+               index = end + 1;
+               EbnfToken::Terminal(terminal)
+          } else {
+               let mut end = index + 1;
+               if c == '<' {
+                    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                         end += 1;
+                    }
+                    if end > index + 1 && end < chars.len() && chars[end] == '>' && chars[index + 1].is_alphabetic() {
+                         let name: String = chars[index + 1..end].iter().collect();
+                         tokens.push((EbnfToken::Nonterminal(name), line));
+                         index = end + 1;
+                         attached = true;
+                         continue;
+                    }
+                    end = index + 1;
+               }
+               while end < chars.len() && !chars[end].is_whitespace() && !"()[]{}|\"'".contains(chars[end]) {
+                    end += 1;
+               }
+               let word: String = chars[index..end].iter().collect();
+               index = end;
+               tokens.push((EbnfToken::Terminal(word), line));
+               attached = false;
+               continue;
+          };
+          attached = matches!(token, EbnfToken::Close(_) | EbnfToken::Terminal(_));
+          tokens.push((token, line));
+     }
+     Ok(())
+}
+
+/*
+     @Description: Struct for a recursive descent parser over the tokens of one EBNF definition
+     @Params: None
+     @Returns: None
+*/
+struct EbnfParser {
+     tokens: Vec<(EbnfToken, usize)>,
+     position: usize,
+}
+
+impl EbnfParser {
+     /*
+          @Description: Parses alternatives separated by "|" up to a closing bracket or the end of the definition
+          @Params: close - the closing bracket expected, or None at the top level
+          @Returns: Each alternative with the line it starts on, or an error message
+     */
+     fn alternatives(&mut self, close: Option<char>) -> Result<Vec<(Vec<Term>, usize)>, String> {
+          let mut alternatives = Vec::new();
+          let mut line = self.tokens.get(self.position).map_or(0, |(_, line)| *line);
+          loop {
+               let sequence = self.sequence()?;
+               alternatives.push((sequence, line));
+               match self.tokens.get(self.position).cloned() {
+                    Some((EbnfToken::Bar, bar_line)) => {
+                         self.position += 1;
+                         line = self.tokens.get(self.position).map_or(bar_line, |(_, next_line)| *next_line);
+                    }
+                    Some((EbnfToken::Close(c), token_line)) => {
+                         if Some(c) != close {
+                              return Err(format!("Error: Unexpected {} on line {}", c, token_line));
+                         }
+                         return Ok(alternatives);
+                    }
+                    Some((token, token_line)) => return Err(format!("Error: Unexpected {:?} on line {}", token, token_line)),
+                    None => {
+                         if let Some(c) = close {
+                              return Err(format!("Error: Missing {} on line {}", c, line));
+                         }
+                         return Ok(alternatives);
+                    }
+               }
+          }
+     }
+
+     /*
+          @Description: Parses symbols and groups up to a "|" or a closing bracket
+          @Params: None
+          @Returns: The terms or an error message
+     */
+     fn sequence(&mut self) -> Result<Vec<Term>, String> {
+          let mut terms = Vec::new();
+          while let Some((token, line)) = self.tokens.get(self.position).cloned() {
+               let term = match token {
+                    EbnfToken::Nonterminal(name) => Term::Symbol(name, true),
+                    EbnfToken::Terminal(word) => Term::Symbol(word, false),
+                    EbnfToken::Open(open) => {
+                         let (close, repetition) = match open {
+                              '(' => (')', ' '),
+                              '[' => (']', '?'),
+                              _ => ('}', '*'),
+                         };
+                         self.position += 1;
+                         let alternatives = self.alternatives(Some(close))?;
+                         Term::Group(alternatives.into_iter().map(|(sequence, _)| sequence).collect(), repetition)
+                    }
+                    EbnfToken::Suffix(c) => return Err(format!("Error: Misplaced {} on line {}", c, line)),
+                    EbnfToken::Bar | EbnfToken::Close(_) => break,
+               };
+               self.position += 1;
+
+               let term = match self.tokens.get(self.position) {
+                    Some((EbnfToken::Suffix(c), _)) => {
+                         let c = *c;
+                         self.position += 1;
+                         match term {
+                              Term::Group(alternatives, ' ') => Term::Group(alternatives, c),
+                              Term::Group(alternatives, repetition) => {
+                                   Term::Group(vec![vec![Term::Group(alternatives, repetition)]], c)
+                              }
+                              symbol => Term::Group(vec![vec![symbol]], c),
+                         }
+                    }
+                    _ => term,
+               };
+               terms.push(term);
+          }
+          Ok(terms)
+     }
+}
+
+/*
+     @Description: Struct collecting the rules generated while desugaring one definition
+     @Params: None
+     @Returns: None
+*/
+struct Desugarer<'a> {
+     lhs: String,
+     taken: &'a mut Vec<String>,
+     generated: Vec<SourceRule>,
+}
+
+impl<'a> Desugarer<'a> {
+     /*
+          @Description: Adds a fresh nonterminal named after the definition, e.g. varlistRepeat, with the given alternatives
+          @Params: suffix - "Repeat", "Opt" or "Group", alternatives - the right hand sides, line - the line of the group
+          @Returns: The new nonterminal
+     */
+     fn add_nonterminal(&mut self, suffix: &str, alternatives: Vec<Vec<String>>, line: usize) -> String {
+          let name = fresh_name(&format!("{}{}", self.lhs, suffix), self.taken);
+          self.taken.push(name.clone());
+          for rhs in alternatives {
+               self.generated.push(SourceRule { rule: make_rule(&name, &rhs), line, bracketed: Vec::new() });
+          }
+          name
+     }
+
+     /*
+          @Description: Flattens a sequence of terms into BNF symbols, replacing groups with fresh nonterminals
+          @Params: terms - the terms, line - the line they are on, bracketed - collects the symbols written as <name>
+          @Returns: The symbols
+     */
+     fn symbols(&mut self, terms: &[Term], line: usize, bracketed: &mut Vec<String>) -> Vec<String> {
+          let mut symbols = Vec::new();
+          for term in terms {
+               match term {
+                    Term::Symbol(symbol, is_nonterminal) => {
+                         if *is_nonterminal && !bracketed.contains(symbol) {
+                              bracketed.push(symbol.clone());
+                         }
+                         symbols.push(symbol.clone());
+                    }
+                    Term::Group(alternatives, repetition) => {
+                         let bodies: Vec<Vec<String>> =
+                              alternatives.iter().map(|sequence| self.symbols(sequence, line, bracketed)).collect();
+                         symbols.extend(self.group(bodies, *repetition, line));
+                    }
+               }
+          }
+          symbols
+     }
+
+     /*
+          @Description: Desugars a group whose alternatives are already BNF symbols; a plain group with one alternative is inlined,
+                        X* becomes N ::= X N | 𝛜, X+ becomes X N, and X? becomes N ::= X | 𝛜
+          @Params: bodies - the alternatives, repetition - the group kind, line - the line of the group
+          @Returns: The symbols that replace the group
+     */
+     fn group(&mut self, bodies: Vec<Vec<String>>, repetition: char, line: usize) -> Vec<String> {
+          let body = if bodies.len() == 1 || repetition == '?' {
+               bodies
+          } else {
+               vec![vec![self.add_nonterminal("Group", bodies, line)]]
+          };
+          match repetition {
+               '*' | '+' => {
+                    let name = fresh_name(&format!("{}Repeat", self.lhs), self.taken);
+                    self.taken.push(name.clone());
+                    let mut recursive = body[0].clone();
+                    recursive.push(name.clone());
+                    self.generated.push(SourceRule { rule: make_rule(&name, &recursive), line, bracketed: Vec::new() });
+                    self.generated.push(SourceRule { rule: make_rule(&name, &[]), line, bracketed: Vec::new() });
+                    if repetition == '+' {
+                         let mut symbols = body[0].clone();
+                         symbols.push(name);
+                         symbols
+                    } else {
+                         vec![name]
+                    }
+               }
+               '?' => {
+                    let mut alternatives = body;
+                    alternatives.push(Vec::new());
+                    vec![self.add_nonterminal("Opt", alternatives, line)]
+               }
+               _ => body.into_iter().next().unwrap(),
+          }
+     }
+}
+
+/*
+     @Description: Desugars one EBNF definition into BNF rules; the rules of the definition come first, in order,
+                   followed by the rules of the nonterminals generated for its groups
+     @Params: lhs - the defined nonterminal, segments - the definition body, one (line number, text) per source line,
+              taken - the nonterminal names in use, extended with the generated names
+     @Returns: The rules or an error message
+*/
+pub fn desugar_definition(lhs: &str, segments: &[(usize, String)], taken: &mut Vec<String>) -> Result<Vec<SourceRule>, String> {
+     let mut tokens = Vec::new();
+     for (line, text) in segments {
+          tokenize(text, *line, &mut tokens)?;
+     }
+     let mut parser = EbnfParser { tokens, position: 0 };
+     let line = segments.first().map_or(0, |(line, _)| *line);
+     let alternatives = parser.alternatives(None)?;
+
+     let mut desugarer = Desugarer { lhs: lhs.to_string(), taken, generated: Vec::new() };
+     let mut rules = Vec::new();
+     for (sequence, alternative_line) in alternatives {
+          let alternative_line = if alternative_line == 0 { line } else { alternative_line };
+          let mut bracketed = Vec::new();
+          let rhs = desugarer.symbols(&sequence, alternative_line, &mut bracketed);
+          rules.push(SourceRule { rule: make_rule(lhs, &rhs), line: alternative_line, bracketed });
+     }
+     rules.extend(desugarer.generated);
+     Ok(rules)
+}
@@ -0,0 +1,365 @@
+// Generated from data/grammar/ez.bnf by `cargo run generate`; do not edit.
+// Symbols are numbered terminals first, so a symbol s >= TERMINALS.len() is the nonterminal
+// NONTERMINALS[s - TERMINALS.len()]. TERMINALS ends with "$", the end of input.
+
+#![allow(dead_code)]
+
+pub const TERMINALS: [&str; 38] = [
+     ".",
+     "def",
+     "(",
+     ")",
+     "fed",
+     ";",
+     ",",
+     "ID",
+     "int",
+     "double",
+     "=",
+     "if",
+     "then",
+     "while",
+     "do",
+     "od",
+     "print",
+     "return",
+     "fi",
+     "else",
+     "+",
+     "-",
+     "*",
+     "/",
+     "%",
+     "NUMBER",
+     "or",
+     "and",
+     "not",
+     "<",
+     ">",
+     "==",
+     "<=",
+     ">=",
+     "<>",
+     "[",
+     "]",
+     "$",
+];
+
+pub const NONTERMINALS: [&str; 34] = [
+     "program",
+     "fdecls",
+     "fdeclsRight",
+     "fdec",
+     "params",
+     "paramsRest",
+     "fname",
+     "declarations",
+     "declarationsRight",
+     "decl",
+     "type",
+     "varlist",
+     "varlistRest",
+     "statement_seq",
+     "statement_seqRest",
+     "statement",
+     "statementRest",
+     "expr",
+     "exprRight",
+     "term",
+     "termRight",
+     "factor",
+     "factorRest",
+     "exprseq",
+     "exprseqRest",
+     "bexpr",
+     "bexprRight",
+     "bterm",
+     "btermRight",
+     "bfactor",
+     "bfactorRest",
+     "comp",
+     "var",
+     "varRest",
+];
+
+pub const PRODUCTIONS: [&str; 70] = [
+     "program ::= fdecls declarations statement_seq .",
+     "fdecls ::= def type fname ( params ) declarations statement_seq fed ; fdeclsRight",
+     "fdecls ::= 𝛜",
+     "fdeclsRight ::= fdec ; fdeclsRight",
+     "fdeclsRight ::= 𝛜",
+     "fdec ::= def type fname ( params ) declarations statement_seq fed",
+     "params ::= type var paramsRest",
+     "params ::= 𝛜",
+     "paramsRest ::= 𝛜",
+     "paramsRest ::= , params",
+     "fname ::= ID",
+     "declarations ::= int varlist ; declarationsRight",
+     "declarations ::= double varlist ; declarationsRight",
+     "declarations ::= 𝛜",
+     "declarationsRight ::= decl ; declarationsRight",
+     "declarationsRight ::= 𝛜",
+     "decl ::= type varlist",
+     "type ::= int",
+     "type ::= double",
+     "varlist ::= var varlistRest",
+     "varlistRest ::= , varlist",
+     "varlistRest ::= 𝛜",
+     "statement_seq ::= statement statement_seqRest",
+     "statement_seqRest ::= 𝛜",
+     "statement_seqRest ::= ; statement_seq",
+     "statement ::= var = expr",
+     "statement ::= if bexpr then statement_seq statementRest",
+     "statement ::= while bexpr do statement_seq od",
+     "statement ::= print expr",
+     "statement ::= return expr",
+     "statement ::= 𝛜",
+     "statementRest ::= fi",
+     "statementRest ::= else statement_seq fi",
+     "expr ::= term exprRight",
+     "exprRight ::= + term exprRight",
+     "exprRight ::= - term exprRight",
+     "exprRight ::= 𝛜",
+     "term ::= factor termRight",
+     "termRight ::= * factor termRight",
+     "termRight ::= / factor termRight",
+     "termRight ::= % factor termRight",
+     "termRight ::= 𝛜",
+     "factor ::= ID factorRest",
+     "factor ::= NUMBER",
+     "factor ::= ( expr )",
+     "factorRest ::= varRest",
+     "factorRest ::= ( exprseq )",
+     "exprseq ::= expr exprseqRest",
+     "exprseq ::= 𝛜",
+     "exprseqRest ::= , exprseq",
+     "exprseqRest ::= 𝛜",
+     "bexpr ::= bterm bexprRight",
+     "bexprRight ::= or bterm bexprRight",
+     "bexprRight ::= 𝛜",
+     "bterm ::= bfactor btermRight",
+     "btermRight ::= and bfactor btermRight",
+     "btermRight ::= 𝛜",
+     "bfactor ::= ( bfactorRest",
+     "bfactor ::= not bfactor",
+     "bfactorRest ::= bexpr )",
+     "bfactorRest ::= expr comp expr )",
+     "comp ::= <",
+     "comp ::= >",
+     "comp ::= ==",
+     "comp ::= <=",
+     "comp ::= >=",
+     "comp ::= <>",
+     "var ::= ID varRest",
+     "varRest ::= 𝛜",
+     "varRest ::= [ expr ]",
+];
+
+pub const PRODUCTION_LHS: [u16; 70] = [0, 1, 1, 2, 2, 3, 4, 4, 5, 5, 6, 7, 7, 7, 8, 8, 9, 10, 10, 11, 12, 12, 13, 14, 14, 15, 15, 15, 15, 15, 15, 16, 16, 17, 18, 18, 18, 19, 20, 20, 20, 20, 21, 21, 21, 22, 22, 23, 23, 24, 24, 25, 26, 26, 27, 28, 28, 29, 29, 30, 30, 31, 31, 31, 31, 31, 31, 32, 33, 33];
+
+pub const PRODUCTION_RHS: [&[u16]; 70] = [
+     &[39, 45, 51, 0],
+     &[1, 48, 44, 2, 42, 3, 45, 51, 4, 5, 40],
+     &[],
+     &[41, 5, 40],
+     &[],
+     &[1, 48, 44, 2, 42, 3, 45, 51, 4],
+     &[48, 70, 43],
+     &[],
+     &[],
+     &[6, 42],
+     &[7],
+     &[8, 49, 5, 46],
+     &[9, 49, 5, 46],
+     &[],
+     &[47, 5, 46],
+     &[],
+     &[48, 49],
+     &[8],
+     &[9],
+     &[70, 50],
+     &[6, 49],
+     &[],
+     &[53, 52],
+     &[],
+     &[5, 51],
+     &[70, 10, 55],
+     &[11, 63, 12, 51, 54],
+     &[13, 63, 14, 51, 15],
+     &[16, 55],
+     &[17, 55],
+     &[],
+     &[18],
+     &[19, 51, 18],
+     &[57, 56],
+     &[20, 57, 56],
+     &[21, 57, 56],
+     &[],
+     &[59, 58],
+     &[22, 59, 58],
+     &[23, 59, 58],
+     &[24, 59, 58],
+     &[],
+     &[7, 60],
+     &[25],
+     &[2, 55, 3],
+     &[71],
+     &[2, 61, 3],
+     &[55, 62],
+     &[],
+     &[6, 61],
+     &[],
+     &[65, 64],
+     &[26, 65, 64],
+     &[],
+     &[67, 66],
+     &[27, 67, 66],
+     &[],
+     &[2, 68],
+     &[28, 67],
+     &[63, 3],
+     &[55, 69, 55, 3],
+     &[29],
+     &[30],
+     &[31],
+     &[32],
+     &[33],
+     &[34],
+     &[7, 71],
+     &[],
+     &[35, 55, 36],
+];
+
+// TABLE[nonterminal][terminal] is the rule to expand with, or -1 for a syntax error.
+pub const TABLE: [[i16; 38]; 34] = [
+     [0, 0, -1, -1, -1, 0, -1, 0, 0, 0, -1, 0, -1, 0, -1, -1, 0, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [2, 1, -1, -1, -1, 2, -1, 2, 2, 2, -1, 2, -1, 2, -1, -1, 2, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [4, 3, -1, -1, -1, 4, -1, 4, 4, 4, -1, 4, -1, 4, -1, -1, 4, 4, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, 5, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, 7, -1, -1, -1, -1, 6, 6, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, 8, -1, -1, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, 10, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [13, -1, -1, -1, 13, 13, -1, 13, 11, 12, -1, 13, -1, 13, -1, -1, 13, 13, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [15, -1, -1, -1, 15, 15, -1, 15, 14, 14, -1, 15, -1, 15, -1, -1, 15, 15, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, -1, 16, 16, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, -1, 17, 18, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, 19, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, 21, 20, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [22, -1, -1, -1, 22, 22, -1, 22, -1, -1, -1, 22, -1, 22, -1, 22, 22, 22, 22, 22, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [23, -1, -1, -1, 23, 24, -1, -1, -1, -1, -1, -1, -1, -1, -1, 23, -1, -1, 23, 23, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [30, -1, -1, -1, 30, 30, -1, 25, -1, -1, -1, 26, -1, 27, -1, 30, 28, 29, 30, 30, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 31, 32, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, 33, -1, -1, -1, -1, 33, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 33, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [36, -1, -1, 36, 36, 36, 36, -1, -1, -1, -1, -1, -1, -1, -1, 36, -1, -1, 36, 36, 34, 35, -1, -1, -1, -1, -1, -1, -1, 36, 36, 36, 36, 36, 36, -1, 36, -1],
+     [-1, -1, 37, -1, -1, -1, -1, 37, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 37, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [41, -1, -1, 41, 41, 41, 41, -1, -1, -1, -1, -1, -1, -1, -1, 41, -1, -1, 41, 41, 41, 41, 38, 39, 40, -1, -1, -1, -1, 41, 41, 41, 41, 41, 41, -1, 41, -1],
+     [-1, -1, 44, -1, -1, -1, -1, 42, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 43, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [45, -1, 46, 45, 45, 45, 45, -1, -1, -1, -1, -1, -1, -1, -1, 45, -1, -1, 45, 45, 45, 45, 45, 45, 45, -1, -1, -1, -1, 45, 45, 45, 45, 45, 45, 45, 45, -1],
+     [-1, -1, 47, 48, -1, -1, -1, 47, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 47, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, 50, -1, -1, 49, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, 51, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 51, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, 53, -1, -1, -1, -1, -1, -1, -1, -1, 53, -1, 53, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 52, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, 54, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 54, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, 56, -1, -1, -1, -1, -1, -1, -1, -1, 56, -1, 56, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 56, 55, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, 57, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 58, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, 59, -1, -1, -1, -1, 60, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 60, -1, -1, 59, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 61, 62, 63, 64, 65, 66, -1, -1, -1],
+     [-1, -1, -1, -1, -1, -1, -1, 67, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1],
+     [68, -1, -1, 68, 68, 68, 68, -1, -1, -1, 68, -1, -1, -1, -1, 68, -1, -1, 68, 68, 68, 68, 68, 68, 68, -1, -1, -1, -1, 68, 68, 68, 68, 68, 68, 69, 68, -1],
+];
+
+// CONFLICTS holds (nonterminal, terminal, rules) for each cell predicting more than one rule; TABLE keeps the first.
+pub const CONFLICTS: [(usize, usize, &[u16]); 1] = [
+     (30, 2, &[59, 60]),
+];
+
+/*
+     @Description: Finds the number of a terminal
+     @Params: terminal - the terminal name
+     @Returns: The symbol number, if the grammar uses the terminal
+*/
+pub fn terminal_index(terminal: &str) -> Option<usize> {
+     TERMINALS.iter().position(|t| *t == terminal)
+}
+
+/*
+     @Description: Adds the last conflicting cell the parser used to an error, since the input may have needed the rule
+                   the table dropped there
+     @Params: error - the error message, conflict - the nonterminal, terminal and location of the cell, if any
+     @Returns: The message
+*/
+fn with_conflict(error: String, conflict: &Option<(usize, usize, String)>) -> String {
+     match conflict {
+          Some((nonterminal, terminal, location)) => {
+               let rules = CONFLICTS
+                    .iter()
+                    .find(|(n, t, _)| n == nonterminal && t == terminal)
+                    .map_or(&[][..], |(_, _, rules)| *rules);
+               format!(
+                    "{} (conflict: {} on {} {} predicts rules {:?}, the table used rule {})",
+                    error, NONTERMINALS[*nonterminal], TERMINALS[*terminal], location, rules, rules[0]
+               )
+          }
+          None => error,
+     }
+}
+
+/*
+     @Description: Parses a sequence of terminals with the precomputed table; the end of input is implied
+     @Params: input - the terminal name, line and column of each token, e.g. [("ID", 1, 1), ("=", 1, 2)]
+     @Returns: The rules of the leftmost derivation or an error message naming the token's line:col
+*/
+pub fn parse<S: AsRef<str>>(input: &[(S, usize, usize)]) -> Result<Vec<usize>, String> {
+     let end = TERMINALS.len() - 1;
+     let mut lookaheads = Vec::with_capacity(input.len() + 1);
+     for (terminal, line, column) in input {
+          match terminal_index(terminal.as_ref()) {
+               Some(index) if index != end => lookaheads.push(index),
+               _ => return Err(format!("Error: Unknown terminal {} at {}:{}", terminal.as_ref(), line, column)),
+          }
+     }
+     lookaheads.push(end);
+     let location = |position: usize| match input.get(position) {
+          Some((_, line, column)) => format!("at {}:{}", line, column),
+          None => String::from("at the end of input"),
+     };
+
+     let mut derivation = Vec::new();
+     let mut conflict = None;
+     let mut stack: Vec<usize> = vec![end, TERMINALS.len()];
+     let mut position = 0;
+     while let Some(symbol) = stack.pop() {
+          let lookahead = lookaheads[position];
+          if symbol < TERMINALS.len() {
+               if symbol != lookahead {
+                    let error =
+                         format!("Error: Expected {} {}, found {}", TERMINALS[symbol], location(position), TERMINALS[lookahead]);
+                    return Err(with_conflict(error, &conflict));
+               }
+               position += 1;
+               continue;
+          }
+
+          let nonterminal = symbol - TERMINALS.len();
+          let row = &TABLE[nonterminal];
+          if row[lookahead] < 0 {
+               let expected: Vec<&str> =
+                    (0..TERMINALS.len()).filter(|&terminal| row[terminal] >= 0).map(|terminal| TERMINALS[terminal]).collect();
+               let error = format!(
+                    "Error: Expected one of {} in {} {}, found {}",
+                    expected.join(" "),
+                    NONTERMINALS[nonterminal],
+                    location(position),
+                    TERMINALS[lookahead]
+               );
+               return Err(with_conflict(error, &conflict));
+          }
+          if CONFLICTS.iter().any(|(n, t, _)| *n == nonterminal && *t == lookahead) {
+               conflict = Some((nonterminal, lookahead, location(position)));
+          }
+          let rule = row[lookahead] as usize;
+          derivation.push(rule);
+          stack.extend(PRODUCTION_RHS[rule].iter().rev().map(|&s| s as usize));
+     }
+     Ok(derivation)
+}
@@ -0,0 +1,379 @@
+use crate::ast::{Decl, Expr, FnDef, Param, Program, Stmt, Type};
+use crate::lexer::Lexer;
+use crate::pratt::OperatorTable;
+use crate::resilient::parse_program;
+use crate::terminals::TerminalMap;
+
+/*
+     @Description: Struct for the formatter settings: spaces per indentation level, or a tab per level, and the line
+                   width past which long expressions are broken at their operators and argument lists
+     @Params: None
+     @Returns: None
+*/
+pub struct FormatConfig {
+     pub indent: usize,
+     pub tabs: bool,
+     pub width: usize,
+}
+
+impl Default for FormatConfig {
+     /*
+          @Description: Creates the settings the format command uses without options: four spaces per level and 80 columns
+          @Params: None
+          @Returns: The settings
+     */
+     fn default() -> FormatConfig {
+          FormatConfig { indent: 4, tabs: false, width: 80 }
+     }
+}
+
+/*
+     @Description: Struct for the pretty printer; lines are collected so a separator can be added to the end of the line
+                   before it, and depth is the current indentation level
+     @Params: None
+     @Returns: None
+*/
+struct Printer<'a> {
+     config: &'a FormatConfig,
+     lines: Vec<String>,
+     depth: usize,
+}
+
+/*
+     @Description: Gets the source text of a type
+     @Params: ty - the type
+     @Returns: "int" or "double"
+*/
+fn type_text(ty: Type) -> &'static str {
+     match ty {
+          Type::Int => "int",
+          Type::Double => "double",
+          Type::Missing => "",
+     }
+}
+
+/*
+     @Description: Prints an expression on one line, with a space around binary operators and after word operators
+     @Params: expr - the expression
+     @Returns: The text
+*/
+fn expr_text(expr: &Expr) -> String {
+     match expr {
+          Expr::Number { value, .. } => value.clone(),
+          Expr::Ident { name, .. } => name.clone(),
+          Expr::Paren { inner, .. } => format!("({})", expr_text(inner)),
+          Expr::Unary { op, operand, .. } => {
+               if op.chars().all(char::is_alphabetic) {
+                    format!("{} {}", op, expr_text(operand))
+               } else {
+                    format!("{}{}", op, expr_text(operand))
+               }
+          }
+          Expr::Binary { op, left, right, .. } => format!("{} {} {}", expr_text(left), op, expr_text(right)),
+          Expr::Index { target, index, .. } => format!("{}[{}]", expr_text(target), expr_text(index)),
+          Expr::Call { callee, args, trailing_comma, .. } => {
+               let args: Vec<String> = args.iter().map(expr_text).collect();
+               format!("{}({}{})", expr_text(callee), args.join(", "), if *trailing_comma { "," } else { "" })
+          }
+          Expr::Missing { .. } => String::new(),
+          Expr::Error { tokens, .. } => tokens.iter().map(|(lexeme, _)| lexeme.as_str()).collect::<Vec<&str>>().join(" "),
+     }
+}
+
+/*
+     @Description: Splits an expression at the binary operators down its left side, e.g. a + b * c - d into a, (+ b * c), (- d)
+     @Params: expr - the expression
+     @Returns: The first operand and each following operator with its operand
+*/
+fn operator_chain(expr: &Expr) -> (&Expr, Vec<(&str, &Expr)>) {
+     let mut rest = Vec::new();
+     let mut first = expr;
+     while let Expr::Binary { op, left, right, .. } = first {
+          rest.push((op.as_str(), right.as_ref()));
+          first = left;
+     }
+     rest.reverse();
+     (first, rest)
+}
+
+impl<'a> Printer<'a> {
+     /*
+          @Description: Gets the indentation of a nesting level
+          @Params: depth - the level
+          @Returns: A tab per level, or the configured spaces per level
+     */
+     fn indentation(&self, depth: usize) -> String {
+          if self.config.tabs { "\t".repeat(depth) } else { " ".repeat(depth * self.config.indent) }
+     }
+
+     /*
+          @Description: Adds a line at the current indentation
+          @Params: text - the line without indentation
+          @Returns: None
+     */
+     fn push(&mut self, text: &str) {
+          let line = format!("{}{}", self.indentation(self.depth), text);
+          self.lines.push(line);
+     }
+
+     /*
+          @Description: Adds a separator to the end of the last line, e.g. the semicolon between two statements
+          @Params: separator - the separator
+          @Returns: None
+     */
+     fn append(&mut self, separator: &str) {
+          match self.lines.last_mut() {
+               Some(line) => line.push_str(separator),
+               None => self.lines.push(separator.to_string()),
+          }
+     }
+
+     /*
+          @Description: Gets the width of a line as displayed, counting a tab as one indentation level
+          @Params: line - the line
+          @Returns: The width in columns
+     */
+     fn width(&self, line: &str) -> usize {
+          line.chars().map(|c| if c == '\t' { self.config.indent } else { 1 }).sum()
+     }
+
+     /*
+          @Description: Prints an expression between a prefix and a suffix, broken over several lines if it is too wide
+          @Params: prefix - the text before the expression, expr - the expression, suffix - the text after it
+          @Returns: None
+     */
+     fn push_expr(&mut self, prefix: &str, expr: &Expr, suffix: &str) {
+          let lines = self.expr_lines(self.depth, prefix, expr, suffix);
+          self.lines.extend(lines);
+     }
+
+     /*
+          @Description: Lays out an expression between a prefix and a suffix; a line too wide is broken after the operators
+                        of the expression's left side, or inside its argument list, parentheses or index, each
+                        continuation indented one level deeper. An argument list puts every argument on its own line
+                        and the closing parenthesis back at the call's level
+          @Params: depth - the indentation level of the first line, prefix - the text before the expression,
+                   expr - the expression, suffix - the text after it
+          @Returns: The lines
+     */
+     fn expr_lines(&self, depth: usize, prefix: &str, expr: &Expr, suffix: &str) -> Vec<String> {
+          let line = format!("{}{}{}{}", self.indentation(depth), prefix, expr_text(expr), suffix);
+          if self.width(&line) <= self.config.width {
+               return vec![line];
+          }
+          match expr {
+               Expr::Binary { .. } => self.chain_lines(depth, prefix, expr, suffix),
+               Expr::Call { callee, args, trailing_comma, .. } if !args.is_empty() => {
+                    let mut lines = vec![format!("{}{}{}(", self.indentation(depth), prefix, expr_text(callee))];
+                    for (index, arg) in args.iter().enumerate() {
+                         let separator = if index + 1 < args.len() || *trailing_comma { "," } else { "" };
+                         lines.extend(self.expr_lines(depth + 1, "", arg, separator));
+                    }
+                    lines.push(format!("{}){}", self.indentation(depth), suffix));
+                    lines
+               }
+               Expr::Paren { inner, .. } => self.expr_lines(depth, &format!("{}(", prefix), inner, &format!("){}", suffix)),
+               Expr::Index { target, index, .. } => {
+                    self.expr_lines(depth, &format!("{}{}[", prefix, expr_text(target)), index, &format!("]{}", suffix))
+               }
+               Expr::Unary { op, operand, .. } => {
+                    let space = if op.chars().all(char::is_alphabetic) { " " } else { "" };
+                    self.expr_lines(depth, &format!("{}{}{}", prefix, op, space), operand, suffix)
+               }
+               _ => vec![line],
+          }
+     }
+
+     /*
+          @Description: Lays out a binary expression, filling each line with operators of its left side and breaking
+                        after the last operator that fits; an operand too wide for a line of its own is laid out in turn
+          @Params: depth - the indentation level of the first line, prefix - the text before the expression,
+                   expr - the expression, suffix - the text after it
+          @Returns: The lines
+     */
+     fn chain_lines(&self, depth: usize, prefix: &str, expr: &Expr, suffix: &str) -> Vec<String> {
+          let (first, rest) = operator_chain(expr);
+          let mut lines = self.expr_lines(depth, prefix, first, "");
+          let mut current = lines.pop().unwrap();
+          for (index, (op, operand)) in rest.iter().enumerate() {
+               let end = if index + 1 == rest.len() { suffix } else { "" };
+               let piece = format!(" {} {}{}", op, expr_text(operand), end);
+               if self.width(&current) + self.width(&piece) <= self.config.width {
+                    current.push_str(&piece);
+                    continue;
+               }
+               current.push_str(&format!(" {}", op));
+               lines.push(current);
+               let mut operand_lines = self.expr_lines(depth + 1, "", operand, end);
+               current = operand_lines.pop().unwrap();
+               lines.extend(operand_lines);
+          }
+          lines.push(current);
+          lines
+     }
+
+     /*
+          @Description: Prints a declaration on one line, e.g. "int a, b;"
+          @Params: decl - the declaration
+          @Returns: None
+     */
+     fn decl(&mut self, decl: &Decl) {
+          let vars: Vec<String> = decl.vars.iter().map(expr_text).collect();
+          self.push(&format!("{} {};", type_text(decl.ty), vars.join(", ")));
+     }
+
+     /*
+          @Description: Formats a parameter, e.g. "int a"
+          @Params: param - the parameter
+          @Returns: The parameter text
+     */
+     fn param_text(param: &Param) -> String {
+          format!("{} {}", type_text(param.ty), expr_text(&param.var))
+     }
+
+     /*
+          @Description: Prints a function header, its declarations and statements one level deeper, and fed
+          @Params: function - the function
+          @Returns: None
+     */
+     fn fn_def(&mut self, function: &FnDef) {
+          let params: Vec<String> = function.params.iter().map(Printer::param_text).collect();
+          let comma = if function.trailing_comma { "," } else { "" };
+          self.push(&format!("def {} {}({}{})", type_text(function.return_type), expr_text(&function.name), params.join(", "), comma));
+          self.depth += 1;
+          for decl in &function.declarations {
+               self.decl(decl);
+          }
+          self.statements(&function.body);
+          self.depth -= 1;
+          self.push("fed");
+     }
+
+     /*
+          @Description: Prints statements one per line with the semicolons between them; an empty statement prints nothing,
+                        so its semicolons end the line before it
+          @Params: stmts - the statements
+          @Returns: None
+     */
+     fn statements(&mut self, stmts: &[Stmt]) {
+          for (index, stmt) in stmts.iter().enumerate() {
+               if index > 0 {
+                    self.append(";");
+               }
+               self.statement(stmt);
+          }
+     }
+
+     /*
+          @Description: Prints the statements of a branch or loop body one level deeper
+          @Params: stmts - the statements
+          @Returns: None
+     */
+     fn block(&mut self, stmts: &[Stmt]) {
+          self.depth += 1;
+          self.statements(stmts);
+          self.depth -= 1;
+     }
+
+     /*
+          @Description: Prints a statement, breaking long expressions when they pass the line width
+          @Params: stmt - the statement
+          @Returns: None
+     */
+     fn statement(&mut self, stmt: &Stmt) {
+          match stmt {
+               Stmt::Assign { target, value, .. } => self.push_expr(&format!("{} = ", expr_text(target)), value, ""),
+               Stmt::If { cond, then_branch, else_branch, .. } => {
+                    self.push_expr("if ", cond, " then");
+                    self.block(then_branch);
+                    if let Some(else_branch) = else_branch {
+                         self.push("else");
+                         self.block(else_branch);
+                    }
+                    self.push("fi");
+               }
+               Stmt::While { cond, body, .. } => {
+                    self.push_expr("while ", cond, " do");
+                    self.block(body);
+                    self.push("od");
+               }
+               Stmt::Print { value, .. } => self.push_expr("print ", value, ""),
+               Stmt::Return { value, .. } => self.push_expr("return ", value, ""),
+               Stmt::Empty { .. } => {}
+               Stmt::Error { tokens, .. } => {
+                    let text: Vec<&str> = tokens.iter().map(|(lexeme, _)| lexeme.as_str()).collect();
+                    self.push(&text.join(" "));
+               }
+          }
+     }
+
+     /*
+          @Description: Prints a program: each function followed by a blank line, the global declarations, a blank line
+                        and the main statements ending in the period, spaced from a final number so it is not lexed
+                        as the number's decimal point
+          @Params: program - the program
+          @Returns: None
+     */
+     fn program(&mut self, program: &Program) {
+          for function in &program.functions {
+               self.fn_def(function);
+               self.append(";");
+               self.lines.push(String::new());
+          }
+          for decl in &program.declarations {
+               self.decl(decl);
+          }
+          if !program.declarations.is_empty() {
+               self.lines.push(String::new());
+          }
+          self.statements(&program.body);
+          let after_number = self.lines.last().is_some_and(|line| line.ends_with(|c: char| c.is_ascii_digit()));
+          self.append(if after_number { " ." } else { "." });
+     }
+}
+
+/*
+     @Description: Pretty-prints a program in the canonical layout
+     @Params: program - the program, config - the settings
+     @Returns: The source text, ending in a newline
+*/
+pub fn format_program(program: &Program, config: &FormatConfig) -> String {
+     let mut printer = Printer { config, lines: Vec::new(), depth: 0 };
+     printer.program(program);
+     let lines: Vec<&str> = printer.lines.iter().map(|line| line.trim_end()).collect();
+     format!("{}\n", lines.join("\n"))
+}
+
+/*
+     @Description: Lexes a source text into its terminals
+     @Params: source - the source text
+     @Returns: The terminal of each token, or the first lexical error
+*/
+fn token_kinds(source: &str) -> Result<Vec<String>, String> {
+     let tokens = Lexer::get_next_token(source)?;
+     let (symbols, errors) = TerminalMap::default().classify_spanned(&tokens);
+     if let Some(error) = errors.first() {
+          return Err(format!("Error: {}", error));
+     }
+     Ok(symbols.into_iter().map(|(terminal, _, _)| terminal).collect())
+}
+
+/*
+     @Description: Formats a source text; a program with syntax errors is refused rather than guessed at, and the result
+                   is checked to lex to the same terminals as the input, so formatting only ever changes whitespace.
+                   EZ has no comments, so there is nothing besides whitespace to keep
+     @Params: source - the source text, config - the settings
+     @Returns: The formatted text, or an error naming the first syntax error
+*/
+pub fn format_source(source: &str, config: &FormatConfig) -> Result<String, String> {
+     let tokens = Lexer::get_next_token(source)?;
+     let (program, errors) = parse_program(&tokens, &TerminalMap::default(), &OperatorTable::ez());
+     if let Some(error) = errors.first() {
+          return Err(format!("Error: {}", error));
+     }
+     let formatted = format_program(&program, config);
+     if token_kinds(&formatted)? != token_kinds(source)? {
+          return Err(String::from("Error: Formatting would change the program's tokens"));
+     }
+     Ok(formatted)
+}
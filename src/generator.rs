@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::earley::earley_parse;
+use crate::grammar::{nonterminals, split_rule};
+
+/*
+     @Description: Struct for a small xorshift random number generator, so generated programs are reproducible from a seed
+     @Params: None
+     @Returns: None
+*/
+pub struct Rng {
+     state: u64,
+}
+
+impl Rng {
+     /*
+          @Description: Creates a generator from a seed; the seed is spread over the bits and made odd, since xorshift
+                        never leaves a zero state
+          @Params: seed - the seed
+          @Returns: The generator
+     */
+     pub fn new(seed: u64) -> Rng {
+          Rng { state: seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1 }
+     }
+
+     /*
+          @Description: Gets the next random number
+          @Params: None
+          @Returns: The number
+     */
+     pub fn next_u64(&mut self) -> u64 {
+          self.state ^= self.state << 13;
+          self.state ^= self.state >> 7;
+          self.state ^= self.state << 17;
+          self.state
+     }
+
+     /*
+          @Description: Gets a random number below a bound
+          @Params: bound - the bound, greater than 0
+          @Returns: The number
+     */
+     pub fn below(&mut self, bound: usize) -> usize {
+          (self.next_u64() % bound as u64) as usize
+     }
+}
+
+/*
+     @Description: Identifiers used for ID tokens; none of them is an EZ keyword
+     @Params: None
+     @Returns: None
+*/
+const IDENTIFIERS: [&str; 10] = ["a", "b", "c", "x", "y", "n", "i", "count", "total", "value"];
+
+/*
+     @Description: Struct for generating random sentences of a grammar; rules are picked by weight, and once the depth
+                   bound is close only rules that can still finish in time are picked
+     @Params: None
+     @Returns: None
+*/
+pub struct Generator {
+     pub rules: Vec<(String, Vec<String>)>,
+     pub weights: Vec<u32>,
+     pub max_depth: usize,
+     nonterminals: Vec<String>,
+     heights: HashMap<String, usize>,
+     rng: Rng,
+}
+
+impl Generator {
+     /*
+          @Description: Creates a generator with every rule weighted 1
+          @Params: grammar - the grammar rules, max_depth - the depth bound, seed - the random seed
+          @Returns: The generator
+     */
+     pub fn new(grammar: &[String], max_depth: usize, seed: u64) -> Generator {
+          let rules: Vec<(String, Vec<String>)> = grammar.iter().map(|rule| split_rule(rule)).collect();
+          let nonterminals = nonterminals(grammar);
+          let mut heights: HashMap<String, usize> = HashMap::new();
+          let mut changed = true;
+          while changed {
+               changed = false;
+               for (lhs, rhs) in &rules {
+                    if let Some(height) = rule_height(rhs, &nonterminals, &heights) {
+                         if heights.get(lhs).is_none_or(|current| height < *current) {
+                              heights.insert(lhs.clone(), height);
+                              changed = true;
+                         }
+                    }
+               }
+          }
+          let weights = vec![1; rules.len()];
+          Generator { rules, weights, max_depth, nonterminals, heights, rng: Rng::new(seed) }
+     }
+
+     /*
+          @Description: Sets the weight of a rule; a rule with weight 0 is only used when nothing else can finish
+          @Params: rule - the rule, e.g. "statement ::= print expr", weight - the weight
+          @Returns: An error message if the grammar has no such rule
+     */
+     pub fn set_weight(&mut self, rule: &str, weight: u32) -> Result<(), String> {
+          let target = split_rule(rule);
+          match self.rules.iter().position(|candidate| *candidate == target) {
+               Some(index) => {
+                    self.weights[index] = weight;
+                    Ok(())
+               }
+               None => Err(format!("Error: No rule {} in the grammar", rule)),
+          }
+     }
+
+     /*
+          @Description: Loads rule weights from a file with lines like `5: statement ::= var = expr`
+          @Params: path - the weights file
+          @Returns: An error message if the file cannot be read or names an unknown rule
+     */
+     pub fn load_weights(&mut self, path: &str) -> Result<(), String> {
+          let mut source = String::new();
+          let mut file = File::open(path).map_err(|e| format!("Error: Unable to open {}: {}", path, e))?;
+          file.read_to_string(&mut source).map_err(|e| format!("Error: Unable to read {}: {}", path, e))?;
+          for (index, line) in source.lines().enumerate() {
+               let trimmed = line.trim();
+               if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+               }
+               let mut iter = trimmed.splitn(2, ": ");
+               let weight = iter.next().unwrap().trim().parse::<u32>();
+               match (weight, iter.next()) {
+                    (Ok(weight), Some(rule)) => self.set_weight(rule.trim(), weight)?,
+                    _ => return Err(format!("Error: Line {} of {} is not `weight: rule`: {}", index + 1, path, trimmed)),
+               }
+          }
+          Ok(())
+     }
+
+     /*
+          @Description: Generates a random sentence from the start symbol
+          @Params: None
+          @Returns: The terminals of the sentence
+     */
+     pub fn sentence(&mut self) -> Vec<String> {
+          let start = self.rules[0].0.clone();
+          let mut output = Vec::new();
+          self.expand(&start, 0, &mut output);
+          output
+     }
+
+     fn expand(&mut self, symbol: &str, depth: usize, output: &mut Vec<String>) {
+          if !self.nonterminals.iter().any(|name| name == symbol) {
+               output.push(symbol.to_string());
+               return;
+          }
+
+          let candidates: Vec<usize> = (0..self.rules.len()).filter(|&index| self.rules[index].0 == symbol).collect();
+          let fitting: Vec<usize> = candidates
+               .iter()
+               .cloned()
+               .filter(|&index| {
+                    rule_height(&self.rules[index].1, &self.nonterminals, &self.heights).is_some_and(|height| depth + height <= self.max_depth)
+                         && self.weights[index] > 0
+               })
+               .collect();
+          let choices = if fitting.is_empty() {
+               let lowest = self.heights.get(symbol).cloned();
+               candidates.into_iter().filter(|&index| rule_height(&self.rules[index].1, &self.nonterminals, &self.heights) == lowest).collect()
+          } else {
+               fitting
+          };
+          if choices.is_empty() {
+               // An unproductive nonterminal derives nothing
+               return;
+          }
+
+          let total: u64 = choices.iter().map(|&index| self.weights[index].max(1) as u64).sum();
+          let mut pick = self.rng.next_u64() % total;
+          let mut chosen = choices[0];
+          for &index in &choices {
+               let weight = self.weights[index].max(1) as u64;
+               if pick < weight {
+                    chosen = index;
+                    break;
+               }
+               pick -= weight;
+          }
+
+          for child in self.rules[chosen].1.clone() {
+               self.expand(&child, depth + 1, output);
+          }
+     }
+
+     /*
+          @Description: Turns terminals into source text, with identifiers and numbers for ID and NUMBER and a new line after each ";"
+          @Params: sentence - the terminals
+          @Returns: The source text
+     */
+     pub fn render(&mut self, sentence: &[String]) -> String {
+          let mut output = String::new();
+          for terminal in sentence {
+               if !output.is_empty() && !output.ends_with('\n') {
+                    output.push(' ');
+               }
+               match terminal.as_str() {
+                    "ID" => output.push_str(IDENTIFIERS[self.rng.below(IDENTIFIERS.len())]),
+                    "NUMBER" => {
+                         let number = self.rng.below(1000);
+                         if self.rng.below(4) == 0 {
+                              output.push_str(&format!("{}.{}", number, self.rng.below(100)));
+                         } else {
+                              output.push_str(&number.to_string());
+                         }
+                    }
+                    _ => output.push_str(terminal),
+               }
+               if terminal == ";" {
+                    output.push('\n');
+               }
+          }
+          output.push('\n');
+          output
+     }
+
+     /*
+          @Description: Turns a sentence into a near miss that the grammar rejects by deleting, duplicating, swapping or
+                        replacing one token; candidates the grammar still accepts are thrown away
+          @Params: grammar - the grammar rules, sentence - the terminals
+          @Returns: The mutated terminals and a description of the mutation, or None if no attempt produced an invalid sentence
+     */
+     pub fn mutate(&mut self, grammar: &[String], sentence: &[String]) -> Option<(Vec<String>, String)> {
+          let mut terminals: Vec<String> = Vec::new();
+          for (_, rhs) in &self.rules {
+               for symbol in rhs {
+                    if !self.nonterminals.contains(symbol) && !terminals.contains(symbol) {
+                         terminals.push(symbol.clone());
+                    }
+               }
+          }
+
+          for _ in 0..20 {
+               if sentence.is_empty() {
+                    return None;
+               }
+               let mut mutated = sentence.to_vec();
+               let position = self.rng.below(sentence.len());
+               let description = match self.rng.below(4) {
+                    0 => {
+                         let removed = mutated.remove(position);
+                         format!("deleted `{}` at token {}", removed, position + 1)
+                    }
+                    1 => {
+                         mutated.insert(position, sentence[position].clone());
+                         format!("duplicated `{}` at token {}", sentence[position], position + 1)
+                    }
+                    2 if position + 1 < sentence.len() => {
+                         mutated.swap(position, position + 1);
+                         format!("swapped tokens {} and {}", position + 1, position + 2)
+                    }
+                    _ => {
+                         let replacement = terminals[self.rng.below(terminals.len())].clone();
+                         let description = format!("replaced `{}` with `{}` at token {}", sentence[position], replacement, position + 1);
+                         mutated[position] = replacement;
+                         description
+                    }
+               };
+               if mutated != sentence && earley_parse(grammar, &mutated).is_err() {
+                    return Some((mutated, description));
+               }
+          }
+          None
+     }
+}
+
+/*
+     @Description: Gets the height of the shortest derivation tree through a rule
+     @Params: rhs - the right hand side, nonterminals - the nonterminals of the grammar,
+              heights - the shortest heights of the nonterminals found so far
+     @Returns: The height, or None if a nonterminal has no finished derivation yet
+*/
+fn rule_height(rhs: &[String], nonterminals: &[String], heights: &HashMap<String, usize>) -> Option<usize> {
+     let mut height = 1;
+     for symbol in rhs {
+          if let Some(child) = heights.get(symbol) {
+               height = height.max(child + 1);
+          } else if nonterminals.contains(symbol) {
+               return None;
+          }
+     }
+     Some(height)
+}
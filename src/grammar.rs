@@ -0,0 +1,448 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::ebnf::desugar_definition;
+
+/*
+     @Description: Symbol used for the empty production, matching the parser's grammar rules
+     @Params: None
+     @Returns: None
+*/
+pub const EPSILON: &str = "𝛜";
+
+/*
+     @Description: Splits a rule of the form "lhs ::= a b c" into its left and right hand sides
+     @Params: rule - the grammar rule
+     @Returns: The left hand side and the right hand side symbols, empty for an epsilon rule
+*/
+pub fn split_rule(rule: &str) -> (String, Vec<String>) {
+     let mut iter = rule.split(" ::= ");
+     let lhs = iter.next().unwrap().trim().to_string();
+     let rhs = iter
+          .next()
+          .unwrap_or("")
+          .split(' ')
+          .filter(|s| !s.is_empty() && *s != EPSILON)
+          .map(|s| s.to_string())
+          .collect();
+     (lhs, rhs)
+}
+
+/*
+     @Description: Builds a rule string from a left hand side and its right hand side symbols
+     @Params: lhs - the nonterminal, rhs - the right hand side symbols
+     @Returns: The rule in "lhs ::= a b c" form, using epsilon for an empty right hand side
+*/
+pub fn make_rule(lhs: &str, rhs: &[String]) -> String {
+     if rhs.is_empty() {
+          format!("{} ::= {}", lhs, EPSILON)
+     } else {
+          format!("{} ::= {}", lhs, rhs.join(" "))
+     }
+}
+
+/*
+     @Description: Lists the nonterminals of a grammar in the order they are first defined
+     @Params: grammar - the grammar rules
+     @Returns: The nonterminals
+*/
+pub fn nonterminals(grammar: &[String]) -> Vec<String> {
+     let mut result: Vec<String> = Vec::new();
+     for rule in grammar {
+          let (lhs, _) = split_rule(rule);
+          if !result.contains(&lhs) {
+               result.push(lhs);
+          }
+     }
+     result
+}
+
+/*
+     @Description: Lists the terminals of a grammar in the order they first appear
+     @Params: grammar - the grammar rules
+     @Returns: The terminals
+*/
+pub fn terminals(grammar: &[String]) -> Vec<String> {
+     let nonterminals = nonterminals(grammar);
+     let mut result: Vec<String> = Vec::new();
+     for rule in grammar {
+          let (_, rhs) = split_rule(rule);
+          for symbol in rhs {
+               if !nonterminals.contains(&symbol) && !result.contains(&symbol) {
+                    result.push(symbol);
+               }
+          }
+     }
+     result
+}
+
+/*
+     @Description: Calculates the first set of a sequence of symbols
+     @Params: symbols - the symbols, first - the first sets of the nonterminals
+     @Returns: The first set, containing epsilon if the whole sequence can derive the empty string
+*/
+pub fn first_of_sequence(symbols: &[String], first: &HashMap<String, HashSet<String>>) -> HashSet<String> {
+     let mut result = HashSet::new();
+     for symbol in symbols {
+          match first.get(symbol) {
+               Some(first_s) => {
+                    result.extend(first_s.iter().filter(|s| *s != EPSILON).cloned());
+                    if !first_s.contains(EPSILON) {
+                         return result;
+                    }
+               }
+               None => {
+                    result.insert(symbol.to_string());
+                    return result;
+               }
+          }
+     }
+     result.insert(EPSILON.to_string());
+     result
+}
+
+/*
+     @Description: Calculates the first set of every nonterminal of a grammar
+     @Params: grammar - the grammar rules
+     @Returns: The first sets, containing epsilon for nullable nonterminals
+*/
+pub fn first_sets(grammar: &[String]) -> HashMap<String, HashSet<String>> {
+     let mut first: HashMap<String, HashSet<String>> = HashMap::new();
+     for nonterminal in nonterminals(grammar) {
+          first.insert(nonterminal, HashSet::new());
+     }
+
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               let first_rhs = first_of_sequence(&rhs, &first);
+               let first_s = first.get_mut(&lhs).unwrap();
+               for symbol in first_rhs {
+                    changed |= first_s.insert(symbol);
+               }
+          }
+     }
+     first
+}
+
+/*
+     @Description: Calculates the follow set of every nonterminal of a grammar
+     @Params: grammar - the grammar rules, the first rule's left hand side is the start symbol, first - the first sets
+     @Returns: The follow sets, with $ marking the end of input
+*/
+pub fn follow_sets(grammar: &[String], first: &HashMap<String, HashSet<String>>) -> HashMap<String, HashSet<String>> {
+     let mut follow: HashMap<String, HashSet<String>> = HashMap::new();
+     for nonterminal in nonterminals(grammar) {
+          follow.insert(nonterminal, HashSet::new());
+     }
+     follow.get_mut(&split_rule(&grammar[0]).0).unwrap().insert("$".to_string());
+
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               for (index, symbol) in rhs.iter().enumerate() {
+                    if !follow.contains_key(symbol) {
+                         continue;
+                    }
+                    let mut follow_s = first_of_sequence(&rhs[index + 1..], first);
+                    if follow_s.remove(EPSILON) {
+                         follow_s.extend(follow[&lhs].iter().cloned());
+                    }
+                    let target = follow.get_mut(symbol).unwrap();
+                    for terminal in follow_s {
+                         changed |= target.insert(terminal);
+                    }
+               }
+          }
+     }
+     follow
+}
+
+/*
+     @Description: Calculates the predict set of every rule, the lookaheads that select it in an LL(1) table
+     @Params: grammar - the grammar rules, first - the first sets, follow - the follow sets
+     @Returns: The predict set of each rule, in grammar order
+*/
+pub fn predict_sets(
+     grammar: &[String],
+     first: &HashMap<String, HashSet<String>>,
+     follow: &HashMap<String, HashSet<String>>,
+) -> Vec<HashSet<String>> {
+     grammar
+          .iter()
+          .map(|rule| {
+               let (lhs, rhs) = split_rule(rule);
+               let mut predict = first_of_sequence(&rhs, first);
+               if predict.remove(EPSILON) {
+                    predict.extend(follow[&lhs].iter().cloned());
+               }
+               predict
+          })
+          .collect()
+}
+
+/*
+     @Description: Concatenates two sets of lookahead sequences, cutting every result to k terminals; a sequence
+                   ending in $ has reached the end of input and is not extended
+     @Params: left - the first set, right - the second set, k - the lookahead length
+     @Returns: The concatenated set
+*/
+pub fn concat_k(left: &HashSet<Vec<String>>, right: &HashSet<Vec<String>>, k: usize) -> HashSet<Vec<String>> {
+     let mut result = HashSet::new();
+     for prefix in left {
+          if prefix.len() >= k || prefix.last().is_some_and(|symbol| symbol == "$") {
+               result.insert(prefix.clone());
+               continue;
+          }
+          for suffix in right {
+               let mut sequence = prefix.clone();
+               sequence.extend(suffix.iter().take(k - prefix.len()).cloned());
+               result.insert(sequence);
+          }
+     }
+     result
+}
+
+/*
+     @Description: Calculates FIRSTk of a sequence of symbols, the first k terminals of every string it derives,
+                   or all of a string shorter than k; the empty sequence stands for epsilon
+     @Params: symbols - the sequence, first - the FIRSTk sets of the nonterminals, k - the lookahead length
+     @Returns: The set of terminal sequences, empty if a nonterminal of the sequence derives nothing yet
+*/
+pub fn first_k_of_sequence(symbols: &[String], first: &HashMap<String, HashSet<Vec<String>>>, k: usize) -> HashSet<Vec<String>> {
+     let mut result: HashSet<Vec<String>> = HashSet::from([Vec::new()]);
+     for symbol in symbols {
+          if result.iter().all(|sequence| sequence.len() >= k) {
+               break;
+          }
+          let first_s = match first.get(symbol) {
+               Some(first_s) => first_s.clone(),
+               None => HashSet::from([vec![symbol.to_string()]]),
+          };
+          result = concat_k(&result, &first_s, k);
+     }
+     result
+}
+
+/*
+     @Description: Calculates FIRSTk of every nonterminal; FIRST1 holds the same terminals as first_sets, with [] for 𝛜
+     @Params: grammar - the grammar rules, k - the lookahead length
+     @Returns: A map from each nonterminal to its set of terminal sequences
+*/
+pub fn first_k_sets(grammar: &[String], k: usize) -> HashMap<String, HashSet<Vec<String>>> {
+     let mut first: HashMap<String, HashSet<Vec<String>>> = HashMap::new();
+     for nonterminal in nonterminals(grammar) {
+          first.insert(nonterminal, HashSet::new());
+     }
+
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               let first_rhs = first_k_of_sequence(&rhs, &first, k);
+               let first_s = first.get_mut(&lhs).unwrap();
+               for sequence in first_rhs {
+                    changed |= first_s.insert(sequence);
+               }
+          }
+     }
+     first
+}
+
+/*
+     @Description: Calculates FOLLOWk of every nonterminal, the k terminals that can come after it, ending in $ when the
+                   input can end sooner
+     @Params: grammar - the grammar rules, first - the FIRSTk sets, k - the lookahead length
+     @Returns: A map from each nonterminal to its set of terminal sequences
+*/
+pub fn follow_k_sets(grammar: &[String], first: &HashMap<String, HashSet<Vec<String>>>, k: usize) -> HashMap<String, HashSet<Vec<String>>> {
+     let mut follow: HashMap<String, HashSet<Vec<String>>> = HashMap::new();
+     for nonterminal in nonterminals(grammar) {
+          follow.insert(nonterminal, HashSet::new());
+     }
+     follow.get_mut(&split_rule(&grammar[0]).0).unwrap().insert(vec!["$".to_string()]);
+
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               for (index, symbol) in rhs.iter().enumerate() {
+                    if !follow.contains_key(symbol) {
+                         continue;
+                    }
+                    let follow_s = concat_k(&first_k_of_sequence(&rhs[index + 1..], first, k), &follow[&lhs], k);
+                    let target = follow.get_mut(symbol).unwrap();
+                    for sequence in follow_s {
+                         changed |= target.insert(sequence);
+                    }
+               }
+          }
+     }
+     follow
+}
+
+/*
+     @Description: Calculates the predict set of every rule for an LL(k) table, FIRSTk of the right hand side followed by FOLLOWk of the left
+     @Params: grammar - the grammar rules, first - the FIRSTk sets, follow - the FOLLOWk sets, k - the lookahead length
+     @Returns: The predict set of each rule, in grammar order
+*/
+pub fn predict_k_sets(
+     grammar: &[String],
+     first: &HashMap<String, HashSet<Vec<String>>>,
+     follow: &HashMap<String, HashSet<Vec<String>>>,
+     k: usize,
+) -> Vec<HashSet<Vec<String>>> {
+     grammar
+          .iter()
+          .map(|rule| {
+               let (lhs, rhs) = split_rule(rule);
+               concat_k(&first_k_of_sequence(&rhs, first, k), &follow[&lhs], k)
+          })
+          .collect()
+}
+
+/*
+     @Description: Splits one alternative of a BNF rule into symbols, unwrapping <name> into a nonterminal
+     @Params: text - the alternative
+     @Returns: The symbols of the alternative, each flagged when it was written as <name>
+*/
+fn bnf_symbols(text: &str) -> Vec<(String, bool)> {
+     let mut symbols = Vec::new();
+     for chunk in text.split_whitespace() {
+          let chars: Vec<char> = chunk.chars().collect();
+          let mut terminal = String::new();
+          let mut index = 0;
+          while index < chars.len() {
+               if chars[index] == '<' {
+                    let mut end = index + 1;
+                    while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                         end += 1;
+                    }
+                    if end > index + 1 && end < chars.len() && chars[end] == '>' && chars[index + 1].is_alphabetic() {
+                         if !terminal.is_empty() {
+                              symbols.push((terminal.clone(), false));
+                              terminal.clear();
+                         }
+                         symbols.push((chars[index + 1..end].iter().collect(), true));
+                         index = end + 1;
+                         continue;
+                    }
+               }
+               terminal.push(chars[index]);
+               index += 1;
+          }
+          if !terminal.is_empty() {
+               symbols.push((terminal, false));
+          }
+     }
+     symbols
+}
+
+/*
+     @Description: Struct for a rule read from a grammar file, with the line its alternative starts on and the symbols written as <name>
+     @Params: None
+     @Returns: None
+*/
+pub struct SourceRule {
+     pub rule: String,
+     pub line: usize,
+     pub bracketed: Vec<String>,
+}
+
+/*
+     @Description: Parses a BNF grammar written in the README style, keeping where each rule came from
+     @Params: source - the grammar text, with one "<lhs> ::= alt | alt" definition per line and indented continuation lines; "#" comments and "%left"-style declarations are skipped,
+              and a "%ebnf" line enables { }, [ ], ( ) groups with *, + and ? repetition, with bracket terminals then written quoted, e.g. "("
+     @Returns: The rules, one per alternative, or an error message
+*/
+pub fn parse_grammar_source(source: &str) -> Result<Vec<SourceRule>, String> {
+     let mut definitions: Vec<(String, Vec<(usize, String)>)> = Vec::new();
+     let mut ebnf = false;
+
+     for (index, line) in source.lines().enumerate() {
+          let trimmed = line.trim();
+          if trimmed == "%ebnf" {
+               ebnf = true;
+          }
+          if trimmed.is_empty() || trimmed.starts_with('#') || (trimmed.starts_with('%') && trimmed[1..].starts_with(char::is_alphabetic)) {
+               continue;
+          }
+
+          let separator = if trimmed.contains("::=") { "::=" } else { ":=" };
+          let mut iter = trimmed.splitn(2, separator);
+          let head = iter.next().unwrap().trim();
+
+          if let (Some(body), true) = (iter.next(), head.starts_with('<') && head.ends_with('>')) {
+               definitions.push((head[1..head.len() - 1].to_string(), vec![(index + 1, body.to_string())]));
+          } else if let Some(definition) = definitions.last_mut() {
+               definition.1.push((index + 1, trimmed.to_string()));
+          } else {
+               return Err(format!("Error: Line {} does not start a rule: {}", index + 1, trimmed));
+          }
+     }
+
+     if definitions.is_empty() {
+          return Err(String::from("Error: Grammar has no rules"));
+     }
+
+     let mut rules = Vec::new();
+     if ebnf {
+          let mut taken: Vec<String> = definitions.iter().map(|(lhs, _)| lhs.clone()).collect();
+          for (lhs, segments) in &definitions {
+               rules.extend(desugar_definition(lhs, segments, &mut taken)?);
+          }
+          return Ok(rules);
+     }
+     for (lhs, segments) in &definitions {
+          let mut alternatives: Vec<(usize, String)> = Vec::new();
+          for (segment_index, (line, text)) in segments.iter().enumerate() {
+               for (piece_index, piece) in text.split('|').enumerate() {
+                    if segment_index > 0 && piece_index == 0 {
+                         let last = alternatives.last_mut().unwrap();
+                         if last.1.trim().is_empty() {
+                              last.0 = *line;
+                         }
+                         last.1.push(' ');
+                         last.1.push_str(piece);
+                    } else {
+                         alternatives.push((*line, piece.to_string()));
+                    }
+               }
+          }
+          for (line, alternative) in alternatives {
+               let symbols = bnf_symbols(&alternative);
+               let rhs: Vec<String> = symbols.iter().map(|(symbol, _)| symbol.clone()).collect();
+               let bracketed = symbols.into_iter().filter(|(_, bracketed)| *bracketed).map(|(symbol, _)| symbol).collect();
+               rules.push(SourceRule { rule: make_rule(lhs, &rhs), line, bracketed });
+          }
+     }
+     Ok(rules)
+}
+
+/*
+     @Description: Parses a BNF grammar written in the README style into grammar rules
+     @Params: source - the grammar text
+     @Returns: The grammar rules, one per alternative, or an error message
+*/
+pub fn parse_grammar(source: &str) -> Result<Vec<String>, String> {
+     Ok(parse_grammar_source(source)?.into_iter().map(|source_rule| source_rule.rule).collect())
+}
+
+/*
+     @Description: Loads a BNF grammar file
+     @Params: path - the path to the grammar file
+     @Returns: The grammar rules or an error message
+*/
+pub fn load_grammar(path: &str) -> Result<Vec<String>, String> {
+     let mut source = String::new();
+     let mut file = File::open(path).map_err(|e| format!("Error: Unable to open {}: {}", path, e))?;
+     file.read_to_string(&mut source).map_err(|e| format!("Error: Unable to read {}: {}", path, e))?;
+     parse_grammar(&source)
+}
@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::earley::earley_parse;
+use crate::generator::Generator;
+use crate::grammar::{first_sets, follow_sets, nonterminals, EPSILON};
+use crate::table::Ll1Table;
+use crate::transform::transform;
+
+/*
+     @Description: Struct for the change of one nonterminal's FIRST or FOLLOW set between two grammars
+     @Params: None
+     @Returns: None
+*/
+pub struct SetChange {
+     pub nonterminal: String,
+     pub added: Vec<String>,
+     pub removed: Vec<String>,
+}
+
+/*
+     @Description: Struct for the semantic differences between an old and a new version of a grammar
+     @Params: None
+     @Returns: None
+*/
+pub struct GrammarDiff {
+     pub added_rules: Vec<String>,
+     pub removed_rules: Vec<String>,
+     pub first_changes: Vec<SetChange>,
+     pub follow_changes: Vec<SetChange>,
+     pub new_conflicts: Vec<(String, String)>,
+     pub resolved_conflicts: Vec<(String, String)>,
+     pub only_old: Vec<String>,
+     pub only_new: Vec<String>,
+}
+
+/*
+     @Description: Compares the sets of every nonterminal of either grammar
+     @Params: old - the sets of the old grammar, new - the sets of the new grammar, order - the nonterminals to compare
+     @Returns: The changes, one per nonterminal whose set changed; 𝛜 is listed as EPSILON
+*/
+fn set_changes(old: &HashMap<String, HashSet<String>>, new: &HashMap<String, HashSet<String>>, order: &[String]) -> Vec<SetChange> {
+     let empty = HashSet::new();
+     let mut changes = Vec::new();
+     for nonterminal in order {
+          let old_set = old.get(nonterminal).unwrap_or(&empty);
+          let new_set = new.get(nonterminal).unwrap_or(&empty);
+          let name = |symbol: &String| if symbol == EPSILON { String::from("EPSILON") } else { symbol.clone() };
+          let mut added: Vec<String> = new_set.difference(old_set).map(name).collect();
+          let mut removed: Vec<String> = old_set.difference(new_set).map(name).collect();
+          if added.is_empty() && removed.is_empty() {
+               continue;
+          }
+          added.sort();
+          removed.sort();
+          changes.push(SetChange { nonterminal: nonterminal.clone(), added, removed });
+     }
+     changes
+}
+
+/*
+     @Description: Generates sentences from one grammar and keeps those the other grammar rejects; depth bounds cycle
+                   from 6 to 10 so that short examples turn up
+     @Params: from - the grammar generating sentences, other - the grammar checking them, samples - the number of sentences tried,
+              seed - the random seed
+     @Returns: The distinct rejected sentences as space separated terminals, shortest first
+*/
+fn rejected_sentences(from: &[String], other: &[String], samples: usize, seed: u64) -> Vec<String> {
+     let mut generator = Generator::new(from, 10, seed);
+     let mut rejected: Vec<String> = Vec::new();
+     for index in 0..samples {
+          generator.max_depth = 6 + index % 5;
+          let sentence = generator.sentence();
+          let text = sentence.join(" ");
+          if !rejected.contains(&text) && earley_parse(other, &sentence).is_err() {
+               rejected.push(text);
+          }
+     }
+     rejected.sort_by_key(|text| (text.len(), text.clone()));
+     rejected
+}
+
+/*
+     @Description: Compares two versions of a grammar; FIRST and FOLLOW sets are those of the grammars as written,
+                   conflicts are those of the LL(1) tables of the transformed grammars the parser uses, and language changes
+                   are approximated by random sentences of each grammar that the other one rejects
+     @Params: old - the old grammar rules, new - the new grammar rules, samples - the sentences generated from each grammar,
+              seed - the random seed
+     @Returns: The differences
+*/
+pub fn diff_grammars(old: &[String], new: &[String], samples: usize, seed: u64) -> GrammarDiff {
+     let added_rules: Vec<String> = new.iter().filter(|rule| !old.contains(rule)).cloned().collect();
+     let removed_rules: Vec<String> = old.iter().filter(|rule| !new.contains(rule)).cloned().collect();
+
+     let mut order = nonterminals(old);
+     for nonterminal in nonterminals(new) {
+          if !order.contains(&nonterminal) {
+               order.push(nonterminal);
+          }
+     }
+     let (old_first, new_first) = (first_sets(old), first_sets(new));
+     let first_changes = set_changes(&old_first, &new_first, &order);
+     let follow_changes = set_changes(&follow_sets(old, &old_first), &follow_sets(new, &new_first), &order);
+
+     let old_conflicts = Ll1Table::new(&transform(old).rules).conflicts();
+     let new_conflicts = Ll1Table::new(&transform(new).rules).conflicts();
+
+     GrammarDiff {
+          added_rules,
+          removed_rules,
+          first_changes,
+          follow_changes,
+          new_conflicts: new_conflicts.iter().filter(|conflict| !old_conflicts.contains(conflict)).cloned().collect(),
+          resolved_conflicts: old_conflicts.iter().filter(|conflict| !new_conflicts.contains(conflict)).cloned().collect(),
+          only_old: rejected_sentences(old, new, samples, seed),
+          only_new: rejected_sentences(new, old, samples, seed),
+     }
+}
+
+/*
+     @Description: Formats the changes of FIRST or FOLLOW sets, e.g. "  factor: +( -NUMBER"
+     @Params: title - the section title, changes - the changes
+     @Returns: The section text
+*/
+fn format_set_changes(title: &str, changes: &[SetChange]) -> String {
+     let mut output = format!("{}:\n", title);
+     if changes.is_empty() {
+          output.push_str("  none\n");
+     }
+     for change in changes {
+          let mut symbols: Vec<String> = change.added.iter().map(|symbol| format!("+{}", symbol)).collect();
+          symbols.extend(change.removed.iter().map(|symbol| format!("-{}", symbol)));
+          output.push_str(&format!("  {}: {}\n", change.nonterminal, symbols.join(" ")));
+     }
+     output
+}
+
+/*
+     @Description: Formats a list under a title, with "none" for an empty list and at most limit entries
+     @Params: title - the section title, lines - the entries, limit - the most entries shown
+     @Returns: The section text
+*/
+fn format_list(title: &str, lines: &[String], limit: usize) -> String {
+     let mut output = format!("{}:\n", title);
+     if lines.is_empty() {
+          output.push_str("  none\n");
+     }
+     for line in lines.iter().take(limit) {
+          output.push_str(&format!("  {}\n", line));
+     }
+     if lines.len() > limit {
+          output.push_str(&format!("  ... and {} more\n", lines.len() - limit));
+     }
+     output
+}
+
+/*
+     @Description: Formats a grammar diff as a report
+     @Params: diff - the differences
+     @Returns: The report text
+*/
+pub fn format_diff(diff: &GrammarDiff) -> String {
+     let conflict = |(nonterminal, terminal): &(String, String)| format!("{} on {}", nonterminal, terminal);
+     let mut output = String::new();
+     output.push_str(&format_list("Added productions", &diff.added_rules, usize::MAX));
+     output.push_str(&format_list("Removed productions", &diff.removed_rules, usize::MAX));
+     output.push_str(&format_set_changes("FIRST set changes", &diff.first_changes));
+     output.push_str(&format_set_changes("FOLLOW set changes", &diff.follow_changes));
+     output.push_str(&format_list("New LL(1) conflicts", &diff.new_conflicts.iter().map(conflict).collect::<Vec<String>>(), usize::MAX));
+     output.push_str(&format_list("Resolved LL(1) conflicts", &diff.resolved_conflicts.iter().map(conflict).collect::<Vec<String>>(), usize::MAX));
+     output.push_str(&format_list("Sentences only the old grammar accepts", &diff.only_old, 10));
+     output.push_str(&format_list("Sentences only the new grammar accepts", &diff.only_new, 10));
+     output
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::grammar::{nonterminals, split_rule, SourceRule};
+
+/*
+     @Description: Enum of the problems the grammar checks find
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FindingKind {
+     Undefined,
+     Unreachable,
+     Unproductive,
+     Duplicate,
+     EpsilonCycle,
+}
+
+/*
+     @Description: Struct for one problem with a grammar and the rules it was found in, as indexes into the grammar
+     @Params: None
+     @Returns: None
+*/
+pub struct Finding {
+     pub kind: FindingKind,
+     pub symbol: String,
+     pub rules: Vec<usize>,
+     pub message: String,
+}
+
+impl FindingKind {
+     /*
+          @Description: Tells whether the finding makes the grammar wrong, rather than only untidy
+          @Params: None
+          @Returns: True for undefined and unproductive nonterminals
+     */
+     pub fn is_error(&self) -> bool {
+          matches!(self, FindingKind::Undefined | FindingKind::Unproductive)
+     }
+}
+
+/*
+     @Description: String representation of a finding kind
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for FindingKind {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          match self {
+               FindingKind::Undefined => write!(f, "undefined nonterminal"),
+               FindingKind::Unreachable => write!(f, "unreachable nonterminal"),
+               FindingKind::Unproductive => write!(f, "unproductive nonterminal"),
+               FindingKind::Duplicate => write!(f, "duplicate production"),
+               FindingKind::EpsilonCycle => write!(f, "useless epsilon cycle"),
+          }
+     }
+}
+
+/*
+     @Description: Lists the indexes of the rules defining a nonterminal
+     @Params: grammar - the grammar rules, nonterminal - the nonterminal
+     @Returns: The rule indexes
+*/
+fn defining_rules(grammar: &[String], nonterminal: &str) -> Vec<usize> {
+     (0..grammar.len()).filter(|&index| split_rule(&grammar[index]).0 == nonterminal).collect()
+}
+
+/*
+     @Description: Finds the symbols written as <name> that no rule defines, e.g. a misspelt <statment>
+     @Params: rules - the rules read from the grammar file
+     @Returns: The findings, one per undefined symbol, referencing every rule that uses it
+*/
+fn undefined_nonterminals(rules: &[SourceRule]) -> Vec<Finding> {
+     let grammar: Vec<String> = rules.iter().map(|source_rule| source_rule.rule.clone()).collect();
+     let defined = nonterminals(&grammar);
+     let mut findings: Vec<Finding> = Vec::new();
+     for (index, source_rule) in rules.iter().enumerate() {
+          for symbol in &source_rule.bracketed {
+               if defined.contains(symbol) {
+                    continue;
+               }
+               match findings.iter_mut().find(|finding| finding.symbol == *symbol) {
+                    Some(finding) => {
+                         if !finding.rules.contains(&index) {
+                              finding.rules.push(index);
+                         }
+                    }
+                    None => findings.push(Finding {
+                         kind: FindingKind::Undefined,
+                         symbol: symbol.clone(),
+                         rules: vec![index],
+                         message: format!("<{}> is used but never defined, so it would be read as a terminal", symbol),
+                    }),
+               }
+          }
+     }
+     findings
+}
+
+/*
+     @Description: Finds the nonterminals no derivation from the start symbol reaches
+     @Params: grammar - the grammar rules
+     @Returns: The findings, referencing the rules of each unreachable nonterminal
+*/
+fn unreachable_nonterminals(grammar: &[String]) -> Vec<Finding> {
+     let order = nonterminals(grammar);
+     let mut reached: HashSet<String> = HashSet::new();
+     let mut pending = vec![order[0].clone()];
+     while let Some(nonterminal) = pending.pop() {
+          if !reached.insert(nonterminal.clone()) {
+               continue;
+          }
+          for index in defining_rules(grammar, &nonterminal) {
+               for symbol in split_rule(&grammar[index]).1 {
+                    if order.contains(&symbol) && !reached.contains(&symbol) {
+                         pending.push(symbol);
+                    }
+               }
+          }
+     }
+
+     order
+          .iter()
+          .filter(|nonterminal| !reached.contains(*nonterminal))
+          .map(|nonterminal| Finding {
+               kind: FindingKind::Unreachable,
+               symbol: nonterminal.clone(),
+               rules: defining_rules(grammar, nonterminal),
+               message: format!("{} cannot be reached from the start symbol {}", nonterminal, order[0]),
+          })
+          .collect()
+}
+
+/*
+     @Description: Finds the nonterminals that derive no string of terminals; undefined symbols derive nothing
+     @Params: grammar - the grammar rules, undefined - the undefined symbols
+     @Returns: The findings, referencing the rules of each unproductive nonterminal
+*/
+fn unproductive_nonterminals(grammar: &[String], undefined: &[String]) -> Vec<Finding> {
+     let order = nonterminals(grammar);
+     let mut productive: HashSet<String> = HashSet::new();
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               if productive.contains(&lhs) {
+                    continue;
+               }
+               let derives = rhs.iter().all(|symbol| {
+                    productive.contains(symbol) || (!order.contains(symbol) && !undefined.contains(symbol))
+               });
+               if derives {
+                    productive.insert(lhs);
+                    changed = true;
+               }
+          }
+     }
+
+     order
+          .iter()
+          .filter(|nonterminal| !productive.contains(*nonterminal))
+          .map(|nonterminal| Finding {
+               kind: FindingKind::Unproductive,
+               symbol: nonterminal.clone(),
+               rules: defining_rules(grammar, nonterminal),
+               message: format!("{} derives no string of terminals; every rule for it uses an unproductive nonterminal", nonterminal),
+          })
+          .collect()
+}
+
+/*
+     @Description: Finds productions written more than once
+     @Params: grammar - the grammar rules
+     @Returns: The findings, referencing every copy of each duplicated production
+*/
+fn duplicate_productions(grammar: &[String]) -> Vec<Finding> {
+     let mut findings: Vec<Finding> = Vec::new();
+     for (index, rule) in grammar.iter().enumerate() {
+          if findings.iter().any(|finding| grammar[finding.rules[0]] == *rule) {
+               continue;
+          }
+          let copies: Vec<usize> = (index..grammar.len()).filter(|&other| grammar[other] == *rule).collect();
+          if copies.len() > 1 {
+               findings.push(Finding {
+                    kind: FindingKind::Duplicate,
+                    symbol: split_rule(rule).0,
+                    rules: copies,
+                    message: format!("{} is written more than once", rule),
+               });
+          }
+     }
+     findings
+}
+
+/*
+     @Description: Finds cycles A => ... => A where every step uses a rule whose other symbols all derive epsilon;
+                   such a cycle adds infinitely many derivations of the same string and nothing else
+     @Params: grammar - the grammar rules
+     @Returns: The findings, one per cycle, referencing the rules along it
+*/
+fn epsilon_cycles(grammar: &[String]) -> Vec<Finding> {
+     let order = nonterminals(grammar);
+     let mut nullable: HashSet<String> = HashSet::new();
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for rule in grammar {
+               let (lhs, rhs) = split_rule(rule);
+               if !nullable.contains(&lhs) && rhs.iter().all(|symbol| nullable.contains(symbol)) {
+                    nullable.insert(lhs);
+                    changed = true;
+               }
+          }
+     }
+
+     // An edge A -> B for each rule A ::= x B y with x and y nullable
+     let mut edges: Vec<(String, String, usize)> = Vec::new();
+     for (index, rule) in grammar.iter().enumerate() {
+          let (lhs, rhs) = split_rule(rule);
+          for (position, symbol) in rhs.iter().enumerate() {
+               let others_nullable = rhs.iter().enumerate().all(|(other, s)| other == position || nullable.contains(s));
+               if order.contains(symbol) && others_nullable {
+                    edges.push((lhs.clone(), symbol.clone(), index));
+               }
+          }
+     }
+
+     let mut findings: Vec<Finding> = Vec::new();
+     let mut seen: Vec<Vec<String>> = Vec::new();
+     for start in &order {
+          // Breadth first search for the shortest path back to the start, remembering the edge used to reach each nonterminal
+          let mut previous: Vec<(String, String, usize)> = Vec::new();
+          let mut frontier = vec![start.clone()];
+          let mut visited: HashSet<String> = HashSet::new();
+          let mut closing = None;
+          while closing.is_none() && !frontier.is_empty() {
+               let mut next = Vec::new();
+               for nonterminal in &frontier {
+                    for edge in edges.iter().filter(|edge| edge.0 == *nonterminal) {
+                         if edge.1 == *start {
+                              closing = Some(edge.clone());
+                              break;
+                         }
+                         if visited.insert(edge.1.clone()) {
+                              previous.push(edge.clone());
+                              next.push(edge.1.clone());
+                         }
+                    }
+                    if closing.is_some() {
+                         break;
+                    }
+               }
+               frontier = next;
+          }
+
+          let mut edge = match closing {
+               Some(edge) => edge,
+               None => continue,
+          };
+          let mut path = vec![edge.clone()];
+          while edge.0 != *start {
+               edge = previous.iter().find(|candidate| candidate.1 == edge.0).unwrap().clone();
+               path.push(edge.clone());
+          }
+          path.reverse();
+
+          let mut members: Vec<String> = path.iter().map(|edge| edge.0.clone()).collect();
+          members.sort();
+          if seen.contains(&members) {
+               continue;
+          }
+          seen.push(members);
+
+          let mut cycle: Vec<String> = path.iter().map(|edge| edge.0.clone()).collect();
+          cycle.push(start.clone());
+          findings.push(Finding {
+               kind: FindingKind::EpsilonCycle,
+               symbol: start.clone(),
+               rules: path.iter().map(|edge| edge.2).collect(),
+               message: format!("{} derives itself through {}", start, cycle.join(" => ")),
+          });
+     }
+     findings
+}
+
+/*
+     @Description: Runs every grammar check
+     @Params: rules - the rules read from the grammar file
+     @Returns: The findings, grouped by kind
+*/
+pub fn check_grammar(rules: &[SourceRule]) -> Vec<Finding> {
+     let grammar: Vec<String> = rules.iter().map(|source_rule| source_rule.rule.clone()).collect();
+     let mut findings = undefined_nonterminals(rules);
+     let undefined: Vec<String> = findings.iter().map(|finding| finding.symbol.clone()).collect();
+     findings.extend(unreachable_nonterminals(&grammar));
+     findings.extend(unproductive_nonterminals(&grammar, &undefined));
+     findings.extend(duplicate_productions(&grammar));
+     findings.extend(epsilon_cycles(&grammar));
+     findings
+}
+
+/*
+     @Description: Formats findings with a reference to each rule involved, e.g. "rule 14 (line 12): statement ::= ..."
+     @Params: findings - the findings, rules - the rules read from the grammar file
+     @Returns: The report text
+*/
+pub fn format_findings(findings: &[Finding], rules: &[SourceRule]) -> String {
+     let mut output = String::new();
+     for finding in findings {
+          let severity = if finding.kind.is_error() { "Error" } else { "Warning" };
+          output.push_str(&format!("{}: {}: {}\n", severity, finding.kind, finding.message));
+          for &index in &finding.rules {
+               output.push_str(&format!("\trule {} (line {}): {}\n", index, rules[index].line, rules[index].rule));
+          }
+     }
+     output
+}
@@ -257,7 +257,7 @@ impl Lexer {
                               if c.is_alphabetic()  {
                                    tokens.push(Token {
                                         token_type: TokenTypes::Error,
-                                        lexeme: String::from("".to_owned() + &c.to_string()),
+                                        lexeme: c.to_string(),
                                         line_number,
                                         column_number: column_number + 1,
                                    });
@@ -430,9 +430,7 @@ impl Lexer {
                                    line_number,
                                    column_number,
                               });
-                         } 
-     
-                         if let Some(&'>') = chars.peek() {
+                         } else if let Some(&'>') = chars.peek() {
                               chars.next();
                               tokens.push(Token {
                                    token_type: TokenTypes::NotEqual,
@@ -528,7 +526,7 @@ impl Lexer {
                     _ => {
                          tokens.push(Token {
                              token_type: TokenTypes::Error,
-                             lexeme: String::from("".to_owned() + &c.to_string()),
+                             lexeme: c.to_string(),
                              line_number,
                              column_number,
                         });
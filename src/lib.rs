@@ -0,0 +1,29 @@
+pub mod lexer;
+pub mod parser;
+pub mod grammar;
+pub mod ebnf;
+pub mod transform;
+pub mod lr;
+pub mod earley;
+pub mod tree;
+pub mod terminals;
+pub mod syntax_error;
+pub mod report;
+pub mod table;
+pub mod codegen;
+pub mod ez_table;
+pub mod diagram;
+pub mod ast;
+pub mod pratt;
+pub mod resilient;
+pub mod hygiene;
+pub mod witness;
+pub mod generator;
+pub mod llk;
+pub mod grammar_diff;
+pub mod coverage;
+pub mod cst;
+pub mod visit;
+pub mod constant_fold;
+pub mod formatter;
+pub mod serialize;
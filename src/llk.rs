@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use crate::grammar::{first_k_sets, follow_k_sets, nonterminals, predict_k_sets, split_rule};
+use crate::lexer::Token;
+use crate::syntax_error::SyntaxError;
+use crate::terminals::TerminalMap;
+use crate::tree::{ParseTree, Span, TreeBuilder};
+
+/*
+     @Description: Struct for an LL(k) parse table; each cell is keyed on a nonterminal and the next k terminals, or fewer
+                   when they end in $, and a cell with more than one rule is a conflict
+     @Params: None
+     @Returns: None
+*/
+pub struct LlkTable {
+     pub k: usize,
+     pub rules: Vec<String>,
+     pub nonterminals: Vec<String>,
+     pub cells: HashMap<(String, Vec<String>), Vec<usize>>,
+}
+
+impl LlkTable {
+     /*
+          @Description: Builds the table from the FIRSTk and FOLLOWk sets, keeping every rule that lands in a cell
+          @Params: grammar - the grammar rules, k - the lookahead length, at least 1
+          @Returns: The table
+     */
+     pub fn new(grammar: &[String], k: usize) -> LlkTable {
+          let first = first_k_sets(grammar, k);
+          let follow = follow_k_sets(grammar, &first, k);
+          let predict = predict_k_sets(grammar, &first, &follow, k);
+
+          let mut cells: HashMap<(String, Vec<String>), Vec<usize>> = HashMap::new();
+          for (index, (rule, set)) in grammar.iter().zip(&predict).enumerate() {
+               let (lhs, _) = split_rule(rule);
+               for sequence in set {
+                    cells.entry((lhs.clone(), sequence.clone())).or_default().push(index);
+               }
+          }
+          for indexes in cells.values_mut() {
+               indexes.sort();
+          }
+          LlkTable { k, rules: grammar.to_vec(), nonterminals: nonterminals(grammar), cells }
+     }
+
+     /*
+          @Description: Gets the rules in a cell
+          @Params: nonterminal - the row, lookahead - the next k terminals
+          @Returns: The rule indexes, empty for an error entry
+     */
+     pub fn find_cell(&self, nonterminal: &str, lookahead: &[String]) -> &[usize] {
+          match self.cells.get(&(nonterminal.to_string(), lookahead.to_vec())) {
+               Some(indexes) => indexes,
+               None => &[],
+          }
+     }
+
+     /*
+          @Description: Lists the lookahead sequences of a nonterminal's row, sorted
+          @Params: nonterminal - the row
+          @Returns: The sequences
+     */
+     pub fn lookaheads(&self, nonterminal: &str) -> Vec<&Vec<String>> {
+          let mut sequences: Vec<&Vec<String>> =
+               self.cells.keys().filter(|(lhs, _)| lhs == nonterminal).map(|(_, sequence)| sequence).collect();
+          sequences.sort();
+          sequences
+     }
+
+     /*
+          @Description: Lists the cells holding more than one rule
+          @Params: None
+          @Returns: The (nonterminal, lookahead) pairs, rows in definition order and lookaheads sorted
+     */
+     pub fn conflicts(&self) -> Vec<(String, Vec<String>)> {
+          let mut result = Vec::new();
+          for nonterminal in &self.nonterminals {
+               for sequence in self.lookaheads(nonterminal) {
+                    if self.find_cell(nonterminal, sequence).len() > 1 {
+                         result.push((nonterminal.clone(), sequence.clone()));
+                    }
+               }
+          }
+          result
+     }
+}
+
+/*
+     @Description: Finds the smallest k for which each nonterminal's row of the LL(k) table has no conflicts
+     @Params: grammar - the grammar rules, max_k - the largest k tried
+     @Returns: Each nonterminal in definition order with its k, or None if it still conflicts at max_k
+*/
+pub fn minimal_k(grammar: &[String], max_k: usize) -> Vec<(String, Option<usize>)> {
+     let mut result: Vec<(String, Option<usize>)> = nonterminals(grammar).into_iter().map(|nonterminal| (nonterminal, None)).collect();
+     for k in 1..=max_k {
+          if result.iter().all(|(_, found)| found.is_some()) {
+               break;
+          }
+          let table = LlkTable::new(grammar, k);
+          let conflicts = table.conflicts();
+          for (nonterminal, found) in result.iter_mut() {
+               if found.is_none() && !conflicts.iter().any(|(lhs, _)| lhs == nonterminal) {
+                    *found = Some(k);
+               }
+          }
+     }
+     result
+}
+
+/*
+     @Description: Formats the minimal k of each nonterminal, one per line
+     @Params: minimal - the result of minimal_k, max_k - the largest k tried
+     @Returns: The report text
+*/
+pub fn format_minimal_k(minimal: &[(String, Option<usize>)], max_k: usize) -> String {
+     let mut output = String::new();
+     for (nonterminal, k) in minimal {
+          match k {
+               Some(k) => output.push_str(&format!("{}: LL({})\n", nonterminal, k)),
+               None => output.push_str(&format!("{}: not LL(k) for any k up to {}\n", nonterminal, max_k)),
+          }
+     }
+     output
+}
+
+/*
+     @Description: Runs the table-driven predictive parser, peeking k tokens ahead to pick each production; a conflicting cell uses its earliest rule
+     @Params: table - the parse table, tokens - the tokens to parse, terminals - the token to terminal mapping
+     @Returns: The concrete parse tree or the first lexical or syntax error
+*/
+pub fn llk_parse(table: &LlkTable, tokens: &[Token], terminals: &TerminalMap) -> Result<ParseTree, SyntaxError> {
+     let (mut input, errors) = terminals.classify_spanned(tokens);
+     if let Some(error) = errors.into_iter().next() {
+          return Err(error);
+     }
+     let end = input.last().map_or(Span { line: 1, column: 1 }, |(_, _, span)| Span { line: span.line, column: span.column + 1 });
+     input.push(("$".to_string(), "$".to_string(), end));
+
+     let start = split_rule(&table.rules[0]).0;
+     let mut builder = TreeBuilder::default();
+     let root = builder.add_node(&start);
+     let mut stack = vec![("$".to_string(), root, start.clone()), (start.clone(), root, start.clone())];
+     let mut position = 0;
+
+     while let Some((stack_symbol, node, parent)) = stack.pop() {
+          let (input_symbol, lexeme, span) = input[position].clone();
+          if stack_symbol == input_symbol {
+               if stack_symbol == "$" {
+                    return Ok(builder.finish(root));
+               }
+               builder.set_token(node, &lexeme, span);
+               position += 1;
+               continue;
+          }
+          if !table.nonterminals.contains(&stack_symbol) {
+               return Err(SyntaxError { span, found: lexeme, expected: vec![stack_symbol], nonterminal: Some(parent), repair: None });
+          }
+
+          let window: Vec<String> = input[position..(position + table.k).min(input.len())].iter().map(|(symbol, _, _)| symbol.clone()).collect();
+          let rule = match table.find_cell(&stack_symbol, &window).first() {
+               Some(&rule) => rule,
+               None => return Err(lookahead_error(table, &stack_symbol, &window, &input[position..])),
+          };
+          let (_, rhs_symbols) = split_rule(&table.rules[rule]);
+          let children = builder.expand(node, &table.rules[rule], &rhs_symbols);
+          for (symbol, child) in rhs_symbols.into_iter().zip(children).rev() {
+               stack.push((symbol, child, stack_symbol.to_string()));
+          }
+     }
+     Ok(builder.finish(root))
+}
+
+/*
+     @Description: Creates the error for a lookahead with no table entry, at the first token where it leaves every entry of the row
+     @Params: table - the parse table, nonterminal - the nonterminal being expanded, window - the lookahead,
+              input - the remaining input
+     @Returns: The error, expecting the terminals the row allows at that token
+*/
+fn lookahead_error(table: &LlkTable, nonterminal: &str, window: &[String], input: &[(String, String, Span)]) -> SyntaxError {
+     let row = table.lookaheads(nonterminal);
+     let mut matched = 0;
+     while matched < window.len() && row.iter().any(|sequence| sequence.len() > matched && sequence[..=matched] == window[..=matched]) {
+          matched += 1;
+     }
+     let mut expected: Vec<String> = row
+          .iter()
+          .filter(|sequence| sequence.len() > matched && sequence[..matched] == window[..matched])
+          .map(|sequence| sequence[matched].clone())
+          .collect();
+     expected.sort();
+     expected.dedup();
+     let (_, found, span) = input[matched.min(input.len() - 1)].clone();
+     SyntaxError { span, found, expected, nonterminal: Some(nonterminal.to_string()), repair: None }
+}
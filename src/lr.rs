@@ -0,0 +1,573 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use crate::grammar::{first_of_sequence, first_sets, make_rule, split_rule, EPSILON};
+use crate::lexer::Token;
+use crate::terminals::TerminalMap;
+
+/*
+     @Description: Enum of operator associativities for precedence declarations
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Assoc {
+     Left,
+     Right,
+     NonAssoc,
+}
+
+/*
+     @Description: Struct for yacc style precedence declarations, later levels bind tighter
+     @Params: None
+     @Returns: None
+*/
+#[derive(Default)]
+pub struct Precedence {
+     pub levels: HashMap<String, (usize, Assoc)>,
+     pub count: usize,
+}
+
+impl Precedence {
+     /*
+          @Description: Adds a precedence level binding tighter than every level added before it
+          @Params: assoc - the associativity of the level, terminals - the terminals on the level
+          @Returns: None
+     */
+     pub fn add_level(&mut self, assoc: Assoc, terminals: &[&str]) {
+          self.count += 1;
+          for terminal in terminals {
+               self.levels.insert(terminal.to_string(), (self.count, assoc));
+          }
+     }
+
+     /*
+          @Description: Reads "%left", "%right" and "%nonassoc" lines from a grammar file
+          @Params: source - the grammar text
+          @Returns: The precedence declarations
+     */
+     pub fn parse(source: &str) -> Precedence {
+          let mut precedence = Precedence::default();
+          for line in source.lines() {
+               let mut words = line.split_whitespace();
+               let assoc = match words.next() {
+                    Some("%left") => Assoc::Left,
+                    Some("%right") => Assoc::Right,
+                    Some("%nonassoc") => Assoc::NonAssoc,
+                    _ => continue,
+               };
+               let terminals: Vec<&str> = words.collect();
+               precedence.add_level(assoc, &terminals);
+          }
+          precedence
+     }
+}
+
+/*
+     @Description: Enum of parser actions stored in the action table
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+     Shift(usize),
+     Reduce(usize),
+     Accept,
+     Error,
+}
+
+/*
+     @Description: String representation of the actions
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for Action {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          match *self {
+               Action::Shift(state) => write!(f, "s{}", state),
+               Action::Reduce(rule) => write!(f, "r{}", rule),
+               Action::Accept => write!(f, "acc"),
+               Action::Error => write!(f, "err"),
+          }
+     }
+}
+
+/*
+     @Description: Enum of the table construction methods
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LrMode {
+     Lalr,
+     Canonical,
+}
+
+/*
+     @Description: Struct for a conflict found while filling the action table
+     @Params: None
+     @Returns: None
+*/
+pub struct Conflict {
+     pub state: usize,
+     pub terminal: String,
+     pub actions: Vec<Action>,
+     pub resolution: Action,
+     pub by_precedence: bool,
+}
+
+impl Conflict {
+     pub fn kind(&self) -> &str {
+          if self.actions.iter().any(|action| matches!(action, Action::Shift(_))) {
+               "shift/reduce"
+          } else {
+               "reduce/reduce"
+          }
+     }
+}
+
+/*
+     @Description: Struct for an LR parse table; rule 0 is the augmented start rule and rule N is grammar rule N - 1
+     @Params: None
+     @Returns: None
+*/
+pub struct LrTable {
+     pub rules: Vec<(String, Vec<String>)>,
+     pub states: Vec<Vec<(usize, usize, String)>>,
+     pub action: HashMap<(usize, String), Action>,
+     pub goto: HashMap<(usize, String), usize>,
+     pub conflicts: Vec<Conflict>,
+}
+
+/*
+     @Description: LR(0) item (rule, dot) and LR(1) item (rule, dot, lookahead)
+     @Params: None
+     @Returns: None
+*/
+type Item = (usize, usize);
+type Item1 = (usize, usize, String);
+
+/*
+     @Description: Closed item sets with lookaheads and the transitions between them
+     @Params: None
+     @Returns: None
+*/
+type Automaton = (Vec<BTreeSet<Item1>>, HashMap<(usize, String), usize>);
+
+/*
+     @Description: Struct holding the augmented grammar while the automaton is built
+     @Params: None
+     @Returns: None
+*/
+struct Builder {
+     rules: Vec<(String, Vec<String>)>,
+     first: HashMap<String, HashSet<String>>,
+}
+
+impl Builder {
+     fn new(grammar: &[String]) -> Builder {
+          let start = split_rule(&grammar[0]).0;
+          let mut rules = vec![(format!("{}'", start), vec![start])];
+          rules.extend(grammar.iter().map(|rule| split_rule(rule)));
+          Builder { rules, first: first_sets(grammar) }
+     }
+
+     fn is_nonterminal(&self, symbol: &str) -> bool {
+          self.first.contains_key(symbol)
+     }
+
+     fn next_symbol(&self, rule: usize, dot: usize) -> Option<&String> {
+          self.rules[rule].1.get(dot)
+     }
+
+     /*
+          @Description: Closes a kernel of LR(0) items, adding an item with the dot at the start of every rule of a
+                        nonterminal that some item has after its dot
+          @Params: kernel - the kernel items
+          @Returns: The closed items, kernel first
+     */
+     fn closure0(&self, kernel: &[Item]) -> Vec<Item> {
+          let mut items = kernel.to_vec();
+          let mut index = 0;
+          while index < items.len() {
+               let (rule, dot) = items[index];
+               if let Some(symbol) = self.next_symbol(rule, dot) {
+                    for (other, (lhs, _)) in self.rules.iter().enumerate() {
+                         if lhs == symbol && !items.contains(&(other, 0)) {
+                              items.push((other, 0));
+                         }
+                    }
+               }
+               index += 1;
+          }
+          items
+     }
+
+     /*
+          @Description: Closes a kernel of LR(1) items; an added item's lookaheads are the first set of what follows the
+                        nonterminal in the item that added it, plus that item's lookahead when the rest is nullable
+          @Params: kernel - the kernel items
+          @Returns: The closed items
+     */
+     fn closure1(&self, kernel: Vec<Item1>) -> BTreeSet<Item1> {
+          let mut items: BTreeSet<Item1> = BTreeSet::new();
+          let mut pending = kernel;
+          while let Some(item) = pending.pop() {
+               if !items.insert(item.clone()) {
+                    continue;
+               }
+               let (rule, dot, lookahead) = item;
+               let symbol = match self.next_symbol(rule, dot) {
+                    Some(symbol) if self.is_nonterminal(symbol) => symbol,
+                    _ => continue,
+               };
+               let mut first_rest = first_of_sequence(&self.rules[rule].1[dot + 1..], &self.first);
+               if first_rest.remove(EPSILON) {
+                    first_rest.insert(lookahead.clone());
+               }
+               for (other, (lhs, _)) in self.rules.iter().enumerate() {
+                    if lhs == symbol {
+                         for terminal in &first_rest {
+                              pending.push((other, 0, terminal.clone()));
+                         }
+                    }
+               }
+          }
+          items
+     }
+
+     /*
+          @Description: Builds the LR(0) automaton and computes LALR(1) lookaheads by spontaneous generation and propagation
+          @Params: None
+          @Returns: The closed item sets with lookaheads and the transitions
+     */
+     fn lalr_states(&self) -> Automaton {
+          let mut kernels: Vec<Vec<Item>> = vec![vec![(0, 0)]];
+          let mut transitions: HashMap<(usize, String), usize> = HashMap::new();
+
+          let mut state = 0;
+          while state < kernels.len() {
+               let mut symbols: Vec<String> = Vec::new();
+               let closure = self.closure0(&kernels[state]);
+               for &(rule, dot) in &closure {
+                    if let Some(symbol) = self.next_symbol(rule, dot) {
+                         if !symbols.contains(symbol) {
+                              symbols.push(symbol.clone());
+                         }
+                    }
+               }
+               for symbol in symbols {
+                    let mut kernel: Vec<Item> = closure
+                         .iter()
+                         .filter(|&&(rule, dot)| self.next_symbol(rule, dot) == Some(&symbol))
+                         .map(|&(rule, dot)| (rule, dot + 1))
+                         .collect();
+                    kernel.sort();
+                    let target = match kernels.iter().position(|k| *k == kernel) {
+                         Some(target) => target,
+                         None => {
+                              kernels.push(kernel);
+                              kernels.len() - 1
+                         }
+                    };
+                    transitions.insert((state, symbol), target);
+               }
+               state += 1;
+          }
+
+          let mut lookaheads: Vec<HashMap<Item, BTreeSet<String>>> =
+               kernels.iter().map(|kernel| kernel.iter().map(|&item| (item, BTreeSet::new())).collect()).collect();
+          lookaheads[0].get_mut(&(0, 0)).unwrap().insert("$".to_string());
+
+          let mut propagation: Vec<((usize, Item), (usize, Item))> = Vec::new();
+          for (state, kernel) in kernels.iter().enumerate() {
+               for &(kernel_rule, kernel_dot) in kernel {
+                    for (rule, dot, lookahead) in self.closure1(vec![(kernel_rule, kernel_dot, "#".to_string())]) {
+                         let symbol = match self.next_symbol(rule, dot) {
+                              Some(symbol) => symbol.clone(),
+                              None => continue,
+                         };
+                         let target = transitions[&(state, symbol)];
+                         if lookahead == "#" {
+                              propagation.push(((state, (kernel_rule, kernel_dot)), (target, (rule, dot + 1))));
+                         } else {
+                              lookaheads[target].get_mut(&(rule, dot + 1)).unwrap().insert(lookahead);
+                         }
+                    }
+               }
+          }
+
+          let mut changed = true;
+          while changed {
+               changed = false;
+               for ((from_state, from_item), (to_state, to_item)) in &propagation {
+                    let incoming: Vec<String> = lookaheads[*from_state][from_item].iter().cloned().collect();
+                    let target = lookaheads[*to_state].get_mut(to_item).unwrap();
+                    for lookahead in incoming {
+                         changed |= target.insert(lookahead);
+                    }
+               }
+          }
+
+          let states = kernels
+               .iter()
+               .enumerate()
+               .map(|(state, kernel)| {
+                    let mut items = Vec::new();
+                    for item in kernel {
+                         for lookahead in &lookaheads[state][item] {
+                              items.push((item.0, item.1, lookahead.clone()));
+                         }
+                    }
+                    self.closure1(items)
+               })
+               .collect();
+          (states, transitions)
+     }
+
+     /*
+          @Description: Builds the canonical LR(1) collection of item sets
+          @Params: None
+          @Returns: The closed item sets and the transitions
+     */
+     fn canonical_states(&self) -> Automaton {
+          let mut kernels: Vec<BTreeSet<Item1>> = vec![[(0, 0, "$".to_string())].into_iter().collect()];
+          let mut states: Vec<BTreeSet<Item1>> = Vec::new();
+          let mut transitions: HashMap<(usize, String), usize> = HashMap::new();
+
+          let mut state = 0;
+          while state < kernels.len() {
+               let closure = self.closure1(kernels[state].iter().cloned().collect());
+               let mut symbols: Vec<String> = Vec::new();
+               for (rule, dot, _) in &closure {
+                    if let Some(symbol) = self.next_symbol(*rule, *dot) {
+                         if !symbols.contains(symbol) {
+                              symbols.push(symbol.clone());
+                         }
+                    }
+               }
+               for symbol in symbols {
+                    let kernel: BTreeSet<Item1> = closure
+                         .iter()
+                         .filter(|(rule, dot, _)| self.next_symbol(*rule, *dot) == Some(&symbol))
+                         .map(|(rule, dot, lookahead)| (*rule, dot + 1, lookahead.clone()))
+                         .collect();
+                    let target = match kernels.iter().position(|k| *k == kernel) {
+                         Some(target) => target,
+                         None => {
+                              kernels.push(kernel);
+                              kernels.len() - 1
+                         }
+                    };
+                    transitions.insert((state, symbol), target);
+               }
+               states.push(closure);
+               state += 1;
+          }
+          (states, transitions)
+     }
+
+     /*
+          @Description: Finds the precedence of a rule from the last terminal on its right hand side that has one
+          @Params: rule - the rule index, precedence - the precedence declarations
+          @Returns: The precedence level and associativity, if any
+     */
+     fn rule_precedence(&self, rule: usize, precedence: &Precedence) -> Option<(usize, Assoc)> {
+          self.rules[rule].1.iter().rev().find_map(|symbol| precedence.levels.get(symbol).cloned())
+     }
+}
+
+/*
+     @Description: Adds an action to the table, resolving conflicts with the precedence declarations and recording them
+     @Params: table - the table being filled, builder - the grammar, precedence - the precedence declarations, key - the state and terminal, action - the new action
+     @Returns: None
+*/
+fn add_action(table: &mut LrTable, builder: &Builder, precedence: &Precedence, key: (usize, String), action: Action) {
+     let existing = match table.action.get(&key) {
+          None => {
+               table.action.insert(key, action);
+               return;
+          }
+          Some(existing) if *existing == action => return,
+          Some(existing) => *existing,
+     };
+
+     let mut by_precedence = false;
+     let resolution = match (existing, action) {
+          (Action::Shift(_), Action::Reduce(rule)) | (Action::Reduce(rule), Action::Shift(_)) => {
+               let shift = if let Action::Shift(_) = existing { existing } else { action };
+               match (builder.rule_precedence(rule, precedence), precedence.levels.get(&key.1)) {
+                    (Some((rule_level, _)), Some(&(token_level, assoc))) => {
+                         by_precedence = true;
+                         if rule_level > token_level {
+                              Action::Reduce(rule)
+                         } else if rule_level < token_level {
+                              shift
+                         } else {
+                              match assoc {
+                                   Assoc::Left => Action::Reduce(rule),
+                                   Assoc::Right => shift,
+                                   Assoc::NonAssoc => Action::Error,
+                              }
+                         }
+                    }
+                    _ => shift,
+               }
+          }
+          (Action::Reduce(first), Action::Reduce(second)) => Action::Reduce(first.min(second)),
+          (Action::Accept, _) | (_, Action::Accept) => Action::Accept,
+          (Action::Error, _) | (_, Action::Error) => Action::Error,
+          _ => existing,
+     };
+
+     table.conflicts.push(Conflict {
+          state: key.0,
+          terminal: key.1.clone(),
+          actions: vec![existing, action],
+          resolution,
+          by_precedence,
+     });
+     table.action.insert(key, resolution);
+}
+
+/*
+     @Description: Builds an LALR(1) or canonical LR(1) parse table for a grammar
+     @Params: grammar - the grammar rules, the first rule's left hand side is the start symbol, precedence - the precedence declarations, mode - the construction method
+     @Returns: The parse table with every conflict found
+*/
+pub fn build_lr_table(grammar: &[String], precedence: &Precedence, mode: LrMode) -> LrTable {
+     let builder = Builder::new(grammar);
+     let (states, transitions) = match mode {
+          LrMode::Lalr => builder.lalr_states(),
+          LrMode::Canonical => builder.canonical_states(),
+     };
+
+     let mut table = LrTable {
+          rules: builder.rules.clone(),
+          states: Vec::new(),
+          action: HashMap::new(),
+          goto: HashMap::new(),
+          conflicts: Vec::new(),
+     };
+
+     let mut keys: Vec<&(usize, String)> = transitions.keys().collect();
+     keys.sort();
+     for key in keys {
+          let target = transitions[key];
+          if builder.is_nonterminal(&key.1) {
+               table.goto.insert(key.clone(), target);
+          } else {
+               add_action(&mut table, &builder, precedence, key.clone(), Action::Shift(target));
+          }
+     }
+
+     for (state, items) in states.iter().enumerate() {
+          for (rule, dot, lookahead) in items {
+               if *dot < builder.rules[*rule].1.len() {
+                    continue;
+               }
+               let action = if *rule == 0 { Action::Accept } else { Action::Reduce(*rule) };
+               add_action(&mut table, &builder, precedence, (state, lookahead.clone()), action);
+          }
+          table.states.push(items.iter().filter(|(rule, dot, _)| *dot > 0 || *rule == 0).cloned().collect());
+     }
+
+     table
+}
+
+/*
+     @Description: Runs the shift-reduce driver over the lexer's tokens
+     @Params: table - the parse table, tokens - the tokens to parse, terminals - the token to terminal mapping
+     @Returns: The rules reduced, in order (a reversed rightmost derivation), or an error message
+*/
+pub fn lr_parse(table: &LrTable, tokens: &[Token], terminals: &TerminalMap) -> Result<Vec<String>, String> {
+     let mut input = Vec::new();
+     for token in tokens {
+          input.push((terminals.find_terminal(token)?, token.lexeme.as_str(), token.line_number, token.column_number));
+     }
+     let (line, column) = tokens.last().map_or((1, 0), |t| (t.line_number, t.column_number + 1));
+     input.push((String::from("$"), "end of input", line, column));
+
+     let mut stack: Vec<usize> = vec![0];
+     let mut position = 0;
+     let mut reductions = Vec::new();
+
+     loop {
+          let state = *stack.last().unwrap();
+          let (terminal, lexeme, line, column) = &input[position];
+          match table.action.get(&(state, terminal.to_string())) {
+               Some(Action::Shift(target)) => {
+                    stack.push(*target);
+                    position += 1;
+               }
+               Some(Action::Reduce(rule)) => {
+                    let (lhs, rhs) = &table.rules[*rule];
+                    stack.truncate(stack.len() - rhs.len());
+                    let state = *stack.last().unwrap();
+                    stack.push(table.goto[&(state, lhs.to_string())]);
+                    reductions.push(make_rule(lhs, rhs));
+               }
+               Some(Action::Accept) => return Ok(reductions),
+               _ => {
+                    let mut expected: Vec<&String> = table
+                         .action
+                         .iter()
+                         .filter(|((s, _), action)| *s == state && **action != Action::Error)
+                         .map(|((_, t), _)| t)
+                         .collect();
+                    expected.sort();
+                    return Err(format!(
+                         "Error: Unexpected {} at {}:{}, expected one of {}",
+                         lexeme,
+                         line,
+                         column,
+                         expected.iter().map(|t| t.as_str()).collect::<Vec<&str>>().join(", ")
+                    ));
+               }
+          }
+     }
+}
+
+/*
+     @Description: Prints every conflict of an LR table
+     @Params: table - the parse table
+     @Returns: None
+*/
+pub fn print_conflicts(table: &LrTable) {
+     println!("Conflicts:");
+     for conflict in &table.conflicts {
+          let actions: Vec<String> = conflict.actions.iter().map(|a| a.to_string()).collect();
+          println!(
+               "\tstate {} on {}: {} conflict between {}, {} {}",
+               conflict.state,
+               conflict.terminal,
+               conflict.kind(),
+               actions.join(" and "),
+               if conflict.by_precedence { "resolved by precedence as" } else { "defaulted to" },
+               conflict.resolution
+          );
+          for action in &conflict.actions {
+               if let Action::Reduce(rule) = action {
+                    println!("\t\tr{}: {}", rule, make_rule(&table.rules[*rule].0, &table.rules[*rule].1));
+               }
+          }
+     }
+}
+
+/*
+     @Description: Prints the kernel items of every LR state
+     @Params: table - the parse table
+     @Returns: None
+*/
+pub fn print_states(table: &LrTable) {
+     println!("States:");
+     for (state, items) in table.states.iter().enumerate() {
+          println!("\t{}:", state);
+          for (rule, dot, lookahead) in items {
+               let (lhs, rhs) = &table.rules[*rule];
+               let mut symbols: Vec<&str> = rhs.iter().map(|s| s.as_str()).collect();
+               symbols.insert(*dot, "•");
+               println!("\t\t{} ::= {}, {}", lhs, symbols.join(" "), lookahead);
+          }
+     }
+}
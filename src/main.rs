@@ -3,10 +3,9 @@ use std::process;
 use std::fs::*;
 use std::io::prelude::*;
 
-mod lexer;
-use lexer::Lexer;
-mod parser;
-use parser::Parser;
+use compiler::{codegen, constant_fold, coverage, cst, diagram, earley, ez_table, formatter, generator, grammar, grammar_diff, hygiene, llk, lr, parser, pratt, report, resilient, serialize, table, transform, tree, visit, witness};
+use compiler::lexer::Lexer;
+use compiler::terminals::TerminalMap;
 
 /*
     @Description: Main function
@@ -14,9 +13,523 @@ use parser::Parser;
     @Returns: None
 */
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <filename>", args[0]);
+    let mut args: Vec<String> = env::args().collect();
+    let terminals = match args.iter().position(|arg| arg.starts_with("--terminals=")) {
+        Some(index) => TerminalMap::load(&args.remove(index)["--terminals=".len()..]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        }),
+        None => TerminalMap::default(),
+    };
+    if args.len() == 3 && args[1] == "transform" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let derived = transform::transform(&rules);
+        transform::print_mapping(&rules, &derived);
+        for (nonterminal, terminal) in table::Ll1Table::new(&derived.rules).conflicts() {
+            println!("Remaining conflict: {} on {}", nonterminal, terminal);
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "check" {
+        let rules = grammar::parse_grammar_source(&read_file(&args[2])).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let findings = hygiene::check_grammar(&rules);
+        print!("{}", hygiene::format_findings(&findings, &rules));
+        if findings.iter().any(|finding| finding.kind.is_error()) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "sets" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let directory = if args.len() == 4 { &args[3] } else { "data/output" };
+        if let Err(error) = report::write_set_reports(&transform::transform(&rules).rules, &report::ReportPaths::new(directory)) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "table" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let directory = if args.len() == 4 { &args[3] } else { "data/output" };
+        let ll1_table = table::Ll1Table::new(&transform::transform(&rules).rules);
+        println!("{}", table::to_comfy_table(&ll1_table));
+        for (nonterminal, terminal) in ll1_table.conflicts() {
+            println!("Conflict: {} on {}", nonterminal, terminal);
+        }
+        if let Err(error) = table::write_table_reports(&ll1_table, directory) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if (args.len() == 4 || args.len() == 5) && args[1] == "diff" {
+        let old = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let new = grammar::load_grammar(&args[3]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let samples = if args.len() == 5 {
+            args[4].parse::<usize>().unwrap_or_else(|_| {
+                eprintln!("Error: {} is not a sentence count", args[4]);
+                process::exit(1);
+            })
+        } else {
+            200
+        };
+        print!("{}", grammar_diff::format_diff(&grammar_diff::diff_grammars(&old, &new, samples, 1)));
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "graph" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let path = if args.len() == 4 { &args[3] } else { "data/output/grammar.dot" };
+        if let Err(error) = report::write_file(path, &diagram::dependency_dot(&rules)) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "railroad" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let directory = if args.len() == 4 { &args[3] } else { "data/output/railroad" };
+        if let Err(error) = diagram::write_railroad_diagrams(&rules, directory) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.len() == 4 && args[1] == "generate" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let rules = transform::transform(&rules).rules;
+        for (nonterminal, terminal) in table::Ll1Table::new(&rules).conflicts() {
+            println!("Conflict: {} on {}, keeping the first rule", nonterminal, terminal);
+        }
+        if let Err(error) = report::write_file(&args[3], &codegen::generate_parser_module(&rules, &args[2])) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.len() >= 4 && args[1] == "coverage" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let rules = transform::transform(&rules).rules;
+        let parser = parser::Parser::new(&rules);
+
+        let mut files = Vec::new();
+        for path in &args[3..] {
+            match read_dir(path) {
+                Ok(entries) => {
+                    let mut found: Vec<String> = entries
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.path().to_string_lossy().to_string())
+                        .filter(|file| file.ends_with(".ez"))
+                        .collect();
+                    found.sort();
+                    files.extend(found);
+                }
+                Err(_) => files.push(path.to_string()),
+            }
+        }
+
+        let mut counts = coverage::Coverage::new();
+        for file in &files {
+            let tokens = Lexer::get_next_token(&read_file(file)).unwrap();
+            if let Err(error) = parser.parse_tokens_covered(&tokens, &terminals, &mut counts) {
+                println!("{}: Error: {}", file, error);
+            }
+        }
+        print!("{}", coverage::format_uncovered(&counts, &parser.table));
+        if let Err(error) = report::write_file("data/output/coverage.log", &coverage::annotated_listing(&counts, &parser.table)) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "static" {
+        let tokens = Lexer::get_next_token(&read_file(&args[2])).unwrap();
+        let (symbols, errors) = terminals.classify_spanned(&tokens);
+        if let Some(error) = errors.first() {
+            eprintln!("Error: {}", error);
+            process::exit(1);
+        }
+        for (nonterminal, terminal, rules) in ez_table::CONFLICTS {
+            eprintln!(
+                "Warning: {} on {} predicts rules {:?}, the table uses rule {}",
+                ez_table::NONTERMINALS[nonterminal],
+                ez_table::TERMINALS[terminal],
+                rules,
+                rules[0]
+            );
+        }
+        let input: Vec<(String, usize, usize)> =
+            symbols.into_iter().map(|(terminal, _, span)| (terminal, span.line, span.column)).collect();
+        match ez_table::parse(&input) {
+            Ok(derivation) => {
+                for rule in derivation {
+                    println!("\t{}", ez_table::PRODUCTIONS[rule]);
+                }
+            }
+            Err(error) => println!("{}", error),
+        }
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "expr" {
+        let table = if args.len() == 4 {
+            pratt::OperatorTable::parse(&read_file(&args[3])).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                process::exit(1);
+            })
+        } else {
+            pratt::OperatorTable::ez()
+        };
+        let tokens = Lexer::get_next_token(&args[2]).unwrap();
+        match pratt::parse_expression(&tokens, &terminals, &table) {
+            Ok(expr) => println!("{}", expr),
+            Err(error) => println!("Error: {}", error),
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "recover" {
+        let tokens = Lexer::get_next_token(&read_file(&args[2])).unwrap();
+        let (program, errors) = resilient::parse_program(&tokens, &terminals, &pratt::OperatorTable::ez());
+        println!("{:#?}", program);
+        for error in &errors {
+            println!("Error: {}", error);
+        }
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "ast" {
+        let tokens = Lexer::get_next_token(&read_file(&args[2])).unwrap();
+        let (program, errors) = resilient::parse_program(&tokens, &terminals, &pratt::OperatorTable::ez());
+        match args.get(3).map(String::as_str) {
+            None | Some("--json") => print!("{}", serialize::to_json(&program)),
+            Some("--sexp") => print!("{}", serialize::to_sexp(&program)),
+            Some(option) => {
+                eprintln!("Error: Unknown option {}", option);
+                process::exit(1);
+            }
+        }
+        for error in &errors {
+            eprintln!("Error: {}", error);
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "import" {
+        let program = serialize::decode(&read_file(&args[2])).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        println!("{:#?}", program);
+        return;
+    }
+
+    if args.len() >= 3 && args[1] == "fmt" {
+        let mut config = formatter::FormatConfig::default();
+        let mut check = false;
+        let mut write = false;
+        let mut files = Vec::new();
+        for option in &args[2..] {
+            match option.split_once('=') {
+                Some(("--indent", value)) if value.parse::<usize>().is_ok() => config.indent = value.parse().unwrap(),
+                Some(("--width", value)) if value.parse::<usize>().is_ok() => config.width = value.parse().unwrap(),
+                None if option == "--tabs" => config.tabs = true,
+                None if option == "--check" => check = true,
+                None if option == "--write" => write = true,
+                None if !option.starts_with("--") => files.push(option.clone()),
+                _ => {
+                    eprintln!("Error: Unknown option {}", option);
+                    process::exit(1);
+                }
+            }
+        }
+
+        let mut failed = false;
+        for file in &files {
+            let source = read_file(file);
+            let formatted = match formatter::format_source(&source, &config) {
+                Ok(formatted) => formatted,
+                Err(error) => {
+                    eprintln!("{}: {}", file, error);
+                    failed = true;
+                    continue;
+                }
+            };
+            if check {
+                if formatted != source {
+                    println!("Would reformat {}", file);
+                    failed = true;
+                }
+            } else if write {
+                if formatted != source {
+                    if let Err(error) = report::write_file(file, &formatted) {
+                        eprintln!("{}", error);
+                        failed = true;
+                    }
+                }
+            } else {
+                print!("{}", formatted);
+            }
+        }
+        if failed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "fold" {
+        let tokens = Lexer::get_next_token(&read_file(&args[2])).unwrap();
+        let (program, errors) = resilient::parse_program(&tokens, &terminals, &pratt::OperatorTable::ez());
+        let mut folder = constant_fold::ConstantFolder::new();
+        let program = visit::Fold::fold_program(&mut folder, program);
+        println!("{:#?}", program);
+        println!("Folded {} constant operations", folder.folded);
+        for error in &errors {
+            println!("Error: {}", error);
+        }
+        return;
+    }
+
+    if args.len() == 3 && args[1] == "cst" {
+        let source = read_file(&args[2]);
+        let tokens = Lexer::get_next_token(&source).unwrap();
+        let root = cst::parse_cst(&source, &tokens, &terminals, &pratt::OperatorTable::ez()).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        print!("{}", cst::dump(&root));
+        return;
+    }
+
+    if (args.len() == 3 || args.len() == 4) && args[1] == "witness" {
+        let source = read_file(&args[2]);
+        let rules = grammar::parse_grammar(&source).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        if args.len() == 4 && args[3] != "--lr" {
+            eprintln!("Error: Unknown option {}", args[3]);
+            process::exit(1);
+        }
+        if args.len() == 4 {
+            let table = lr::build_lr_table(&rules, &lr::Precedence::parse(&source), lr::LrMode::Lalr);
+            print!("{}", witness::format_witnesses(&witness::lr_witnesses(&rules, &table)));
+        } else {
+            let rules = transform::transform(&rules).rules;
+            let table = table::Ll1Table::new(&rules);
+            print!("{}", witness::format_witnesses(&witness::ll1_witnesses(&rules, &table)));
+        }
+        return;
+    }
+
+    if (args.len() == 4 || args.len() == 5) && args[1] == "llk" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let k = match args[3].parse::<usize>() {
+            Ok(k) if k > 0 => k,
+            _ => {
+                eprintln!("Error: {} is not a lookahead length", args[3]);
+                process::exit(1);
+            }
+        };
+        let rules = transform::transform(&rules).rules;
+        print!("{}", llk::format_minimal_k(&llk::minimal_k(&rules, k), k));
+        let table = llk::LlkTable::new(&rules, k);
+        for (nonterminal, lookahead) in table.conflicts() {
+            println!("Conflict: {} on {}, keeping the first rule", nonterminal, lookahead.join(" "));
+        }
+
+        if args.len() == 5 {
+            let tokens = Lexer::get_next_token(&read_file(&args[4])).unwrap();
+            match llk::llk_parse(&table, &tokens, &terminals) {
+                Ok(tree) => println!("Parse tree:\n{}", tree::dump(&tree)),
+                Err(error) => println!("Error: {}", error),
+            }
+        }
+        return;
+    }
+
+    if (4..=6).contains(&args.len()) && args[1] == "lr" {
+        let source = read_file(&args[2]);
+        let rules = grammar::parse_grammar(&source).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let mut mode = lr::LrMode::Lalr;
+        let mut states = false;
+        for option in &args[4..] {
+            match option.as_str() {
+                "--canonical" => mode = lr::LrMode::Canonical,
+                "--states" => states = true,
+                _ => {
+                    eprintln!("Error: Unknown option {}", option);
+                    process::exit(1);
+                }
+            }
+        }
+        let table = lr::build_lr_table(&rules, &lr::Precedence::parse(&source), mode);
+        if states {
+            lr::print_states(&table);
+        }
+        lr::print_conflicts(&table);
+
+        let tokens = Lexer::get_next_token(&read_file(&args[3])).unwrap();
+        match lr::lr_parse(&table, &tokens, &terminals) {
+            Ok(reductions) => {
+                for rule in reductions {
+                    println!("\t{}", rule);
+                }
+            }
+            Err(error) => println!("{}", error),
+        }
+        return;
+    }
+
+    if args.len() >= 5 && args[1] == "fuzz" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let count = args[4].parse::<usize>().unwrap_or_else(|_| {
+            eprintln!("Error: {} is not a program count", args[4]);
+            process::exit(1);
+        });
+        let mut seed = 1;
+        let mut depth = 12;
+        let mut mutate = false;
+        let mut weights = None;
+        for option in &args[5..] {
+            match option.split_once('=') {
+                Some(("--seed", value)) if value.parse::<u64>().is_ok() => seed = value.parse().unwrap(),
+                Some(("--depth", value)) if value.parse::<usize>().is_ok() => depth = value.parse().unwrap(),
+                Some(("--weights", path)) => weights = Some(path.to_string()),
+                None if option == "--mutate" => mutate = true,
+                _ => {
+                    eprintln!("Error: Unknown option {}", option);
+                    process::exit(1);
+                }
+            }
+        }
+
+        let mut generator = generator::Generator::new(&rules, depth, seed);
+        if let Some(path) = weights {
+            if let Err(error) = generator.load_weights(&path) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        if let Err(error) = create_dir_all(&args[3]) {
+            eprintln!("Error: Unable to create {}: {}", args[3], error);
+            process::exit(1);
+        }
+        for index in 1..=count {
+            let mut sentence = generator.sentence();
+            let path = format!("{}/program-{}.ez", args[3], index);
+            if mutate {
+                match generator.mutate(&rules, &sentence) {
+                    Some((mutated, description)) => {
+                        println!("{}: {}", path, description);
+                        sentence = mutated;
+                    }
+                    None => println!("{}: no invalid mutation found, kept valid", path),
+                }
+            }
+            let program = generator.render(&sentence);
+            if let Err(error) = report::write_file(&path, &program) {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.len() >= 4 && args[1] == "earley" {
+        let rules = grammar::load_grammar(&args[2]).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+        let mut disambiguation = earley::Disambiguation::default();
+        for option in &args[4..] {
+            match option.split_once('=') {
+                Some(("--prefer", list)) => {
+                    for rule in list.split(',') {
+                        match rule.parse::<usize>() {
+                            Ok(rule) if rule < rules.len() => disambiguation.add_priority(rule),
+                            _ => {
+                                eprintln!("Error: {} is not a rule number", rule);
+                                process::exit(1);
+                            }
+                        }
+                    }
+                }
+                None if option == "--shortest" => disambiguation.longest_first = false,
+                _ => {
+                    eprintln!("Error: Unknown option {}", option);
+                    process::exit(1);
+                }
+            }
+        }
+        let tokens = Lexer::get_next_token(&read_file(&args[3])).unwrap();
+        let input = terminals.classify(&tokens).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+
+        match earley::earley_parse(&rules, &input) {
+            Ok(forest) => {
+                earley::print_parses(&forest, 10);
+                if let Some(tree) = forest.disambiguate(&disambiguation) {
+                    println!("Chosen: {}", forest.format_tree(&tree));
+                }
+            }
+            Err(error) => println!("{}", error),
+        }
+        return;
+    }
+
+    let trace = args.len() == 3 && args[2] == "--trace";
+    if args.len() != 2 && !trace {
+        eprintln!("Usage: {} <filename> [--trace] [--terminals=<file>]", args[0]);
         process::exit(1);
     }
 
@@ -29,12 +542,35 @@ fn main() {
     file.read_to_string(&mut buffer2).expect("Unable to read file");
     buffer1.push_str(&buffer2);
 
-    let tokens = Lexer::get_next_token(&mut buffer1);
-    let parser = Parser::new();
-    match parser.parse(tokens) {
-        Ok(tree) => println!("Parse tree: {:?}", tree),
-        Err(error) => println!("Error: {}", error),
+    let rules = grammar::load_grammar("data/grammar/ez.bnf").expect("Unable to load grammar");
+    let parser = parser::Parser::new(&transform::transform(&rules).rules);
+
+    let tokens = Lexer::get_next_token(&buffer1).unwrap();
+    if trace {
+        let (_, steps) = parser.parse_tokens_traced(&tokens, &terminals);
+        parser::write_trace(&steps, "data/output/syntax-trace.log");
+    }
+    let (tree, errors) = parser.parse_tokens_recovering(&tokens, &terminals);
+    if errors.is_empty() {
+        println!("Parse tree:\n{}", tree::dump(&tree));
+        let mut dot_file = File::create("data/output/parse-tree.dot").expect("Unable to create file");
+        write!(dot_file, "{}", tree::to_dot(&tree)).expect("Unable to write to file");
+    }
+    for error in &errors {
+        println!("Error: {}", error);
     }
 
     println!("{:#?}", tokens);
-}
\ No newline at end of file
+}
+
+/*
+    @Description: Reads a whole file into a string
+    @Params: path - the file to read
+    @Returns: The file contents
+*/
+fn read_file(path: &str) -> String {
+    let mut buffer = String::new();
+    let mut file = File::open(path).expect("Unable to open file");
+    file.read_to_string(&mut buffer).expect("Unable to read file");
+    buffer
+}
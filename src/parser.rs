@@ -1,349 +1,315 @@
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::prelude::*;
 
-/*
-     @Description: Static variables for the first set, follow set, terminals, nonterminals, grammar, first and follow, and lookahead
-     @Params: None
-     @Returns: None
-*/
-static mut FIRST_SET_DICT: HashMap<String, HashSet<String>> = HashMap::new();
-static mut FOLLOW_SET_DICT: HashMap<String, HashSet<String>> = HashMap::new();
-static mut TERMINALS: HashSet<String> = HashSet::new();
-static mut NONTERMINALS: HashSet<String> = HashSet::new();
-static mut GRAMMAR: Vec<String> = Vec::new();
-static mut FIRST_AND_FOLLOW: Vec<(String, String, String)> = Vec::new();
-static mut LOOKAHEAD: HashMap<String, HashMap<String, String>> = HashMap::new();
-static mut START_SYMBOL: String = String::new();
+use comfy_table::Table;
+
+use crate::coverage::Coverage;
+use crate::grammar::{first_sets, follow_sets, split_rule};
+use crate::lexer::Token;
+use crate::syntax_error::SyntaxError;
+use crate::table::Ll1Table;
+use crate::terminals::TerminalMap;
+use crate::tree::{ParseTree, Span, TreeBuilder};
 
 /*
-     @Description: Calculates the first set, follow set, and lookahead for each nonterminal
+     @Description: Struct for one step of the predictive parser: the stack with its top last, the remaining input and the action taken
      @Params: None
      @Returns: None
 */
-fn sets_and_table() {
-     unsafe {
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               NONTERMINALS.insert(lhs.to_string());
-          }
-
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
-               for symbol in rhs_symbols {
-                    if !NONTERMINALS.contains(symbol) {
-                         TERMINALS.insert(symbol.to_string());
-                    }
-               }
-          }
-
-          first_set();
-          follow_set();
-
-          for rule in &FIRST_AND_FOLLOW {
-               let first_symbols = rule.1.split(" | ");
-               let follow_symbols = rule.2.split(" | ");
-               FIRST_SET_DICT.insert(rule.0.to_string(), HashSet::new());
-               FOLLOW_SET_DICT.insert(rule.0.to_string(), HashSet::new());
-               for symbol in first_symbols {
-                    FIRST_SET_DICT.get_mut(&rule.0).unwrap().insert(symbol.to_string());
-               }
-               for symbol in follow_symbols {
-                    FOLLOW_SET_DICT.get_mut(&rule.0).unwrap().insert(symbol.to_string());
-               }
-          }
-
-          lookahead_one();
-     }
+pub struct TraceStep {
+     pub stack: String,
+     pub input: String,
+     pub action: String,
 }
 
 /*
-     @Description: Calculates the first set for each nonterminal
-     @Params: None
+     @Description: Records a step when the parser is tracing
+     @Params: trace - the steps so far, if tracing, snapshot - the stack and input before the step, action - the action taken
      @Returns: None
 */
-fn first_set() {
-     unsafe {
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
-
-               NONTERMINALS.insert(lhs.to_string());
-
-               for symbol in rhs_symbols {
-                    let first_s = FIRST_SET_DICT.entry(symbol.to_string()).or_insert(HashSet::new());
-
-                    if symbol == "𝛜" {
-                         first_s.insert(symbol.to_string());
-                         break;
-                    } else {
-                         let first_rhs = FIRST_SET_DICT.get(symbol).unwrap();
-                         if !first_rhs.contains("𝛜") {
-                              first_s.extend(first_rhs.iter().cloned());
-                         break;
-                         } else {
-                              first_s.extend(first_rhs.iter().cloned().filter(|s| *s != "𝛜"));
-                              first_s.extend(FIRST_SET_DICT.get(lhs).unwrap().iter().cloned());
-                         }
-                    }
-               }
-
-               if !FIRST_SET_DICT.contains_key(lhs) {
-                    FIRST_SET_DICT.insert(lhs.to_string(), HashSet::new());
-               }
-               FIRST_SET_DICT.get_mut(lhs).unwrap().insert("𝛜".to_string());
-          }
+fn record(trace: &mut Option<&mut Vec<TraceStep>>, snapshot: &Option<(String, String)>, action: String) {
+     if let (Some(steps), Some((stack, input))) = (trace, snapshot) {
+          steps.push(TraceStep { stack: stack.to_string(), input: input.to_string(), action });
      }
 }
 
 /*
-     @Description: Calculates the follow set for each nonterminal
+     @Description: Struct for the table-driven LL(1) parser of a grammar: its parse table, the follow sets it
+                   synchronises on after an error and its start symbol
      @Params: None
      @Returns: None
 */
-fn follow_set() {
-     unsafe {
-          START_SYMBOL = GRAMMAR[0].split(" ::= ").next().unwrap().to_string();
-          FOLLOW_SET_DICT.insert(START_SYMBOL.to_string(), HashSet::new());
-          FOLLOW_SET_DICT.get_mut(&START_SYMBOL).unwrap().insert("$".to_string());
+pub struct Parser {
+     pub table: Ll1Table,
+     follow: HashMap<String, HashSet<String>>,
+     start: String,
+}
 
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
+impl Parser {
+     /*
+          @Description: Builds the parser of a grammar; a conflicting table cell uses its earliest rule
+          @Params: rules - the grammar rules, the first rule's left hand side is the start symbol
+          @Returns: The parser
+     */
+     pub fn new(rules: &[String]) -> Parser {
+          let follow = follow_sets(rules, &first_sets(rules));
+          Parser { table: Ll1Table::new(rules), follow, start: split_rule(&rules[0]).0 }
+     }
 
-               for (index, symbol) in rhs_symbols.enumerate() {
-                    if NONTERMINALS.contains(symbol) {
-                         let follow_s = FOLLOW_SET_DICT.entry(symbol.to_string()).or_insert(HashSet::new());
-                         let mut follow_rhs = FOLLOW_SET_DICT.get(lhs).unwrap().iter().cloned();
-                         if index < rhs_symbols.clone().count() - 1 {
-                              let mut first_rhs = FIRST_SET_DICT.get(rhs_symbols.clone().nth(index + 1).unwrap()).unwrap().iter().cloned();
-                              if first_rhs.contains("𝛜") {
-                                   first_rhs = first_rhs.filter(|s| *s != "𝛜");
-                                   follow_rhs.extend(first_rhs);
-                                   follow_rhs.extend(FOLLOW_SET_DICT.get(lhs).unwrap().iter().cloned());
-                              } else {
-                                   follow_rhs.extend(first_rhs);
-                              }
-                         } else {
-                              follow_rhs.extend(FOLLOW_SET_DICT.get(lhs).unwrap().iter().cloned());
-                         }
-                         follow_s.extend(follow_rhs);
-                    }
-               }
+     /*
+          @Description: Parses the input string
+          @Params: input - the space separated terminals to parse
+          @Returns: The concrete parse tree or the first syntax error
+     */
+     pub fn parse(&self, input: &str) -> Result<ParseTree, SyntaxError> {
+          let symbols = input
+               .split(' ')
+               .filter(|s| !s.is_empty())
+               .enumerate()
+               .map(|(index, symbol)| (symbol.to_string(), symbol.to_string(), Span { line: 1, column: index + 1 }))
+               .collect();
+          let (tree, errors) = self.predictive_parse(symbols, false, None, None);
+          match errors.into_iter().next() {
+               Some(error) => Err(error),
+               None => Ok(tree),
           }
      }
-}
-
-/*
-     @Description: Calculates the lookahead for each nonterminal
-     @Params: None
-     @Returns: None
-*/
-fn lookahead_one() {
-     unsafe {
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
 
-               for symbol in rhs_symbols {
-                    if NONTERMINALS.contains(symbol) {
-                         LOOKAHEAD.insert(symbol.to_string(), HashMap::new());
-                    }
-               }
+     /*
+          @Description: Parses the lexer's tokens, classifying each one as a grammar terminal
+          @Params: tokens - the tokens to parse, terminals - the token to terminal mapping
+          @Returns: The concrete parse tree or the first lexical or syntax error
+     */
+     pub fn parse_tokens(&self, tokens: &[Token], terminals: &TerminalMap) -> Result<ParseTree, SyntaxError> {
+          let (symbols, errors) = terminals.classify_spanned(tokens);
+          if let Some(error) = errors.into_iter().next() {
+               return Err(error);
           }
-
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
-
-               for (index, symbol) in rhs_symbols.enumerate() {
-                    if NONTERMINALS.contains(symbol) {
-                         let mut lookahead_s = LOOKAHEAD.get_mut(symbol).unwrap();
-                         let mut first_rhs = FIRST_SET_DICT.get(rhs_symbols.clone().nth(index + 1).unwrap()).unwrap().iter().cloned();
-                         if first_rhs.contains("𝛜") {
-                              first_rhs = first_rhs.filter(|s| *s != "𝛜");
-                              lookahead_s.extend(first_rhs);
-                              lookahead_s.extend(FOLLOW_SET_DICT.get(lhs).unwrap().iter().cloned());
-                         } else {
-                              lookahead_s.extend(first_rhs);
-                         }
-                    }
-               }
+          let (tree, errors) = self.predictive_parse(symbols, false, None, None);
+          match errors.into_iter().next() {
+               Some(error) => Err(error),
+               None => Ok(tree),
           }
      }
-}
 
-/*
-     @Description: Calculates the parsing table
-     @Params: None
-     @Returns: None
-*/
-fn parse_table() {
-     unsafe {
-          for rule in &GRAMMAR {
-               let mut iter = rule.split(" ::= ");
-               let lhs = iter.next().unwrap();
-               let rhs_symbols = iter.next().unwrap().split(" ");
-
-               for symbol in rhs_symbols {
-                    if NONTERMINALS.contains(symbol) {
-                         for lookahead in LOOKAHEAD.get(symbol).unwrap().keys() {
-                              TABLE.insert((lhs.to_string(), lookahead.to_string()), rule.to_string());
-                         }
-                    } else {
-                         TABLE.insert((lhs.to_string(), symbol.to_string()), rule.to_string());
-                    }
-               }
+     /*
+          @Description: Parses the lexer's tokens like parse_tokens, recording every step of the parser
+          @Params: tokens - the tokens to parse, terminals - the token to terminal mapping
+          @Returns: The concrete parse tree or the first lexical or syntax error, and the steps taken
+     */
+     pub fn parse_tokens_traced(&self, tokens: &[Token], terminals: &TerminalMap) -> (Result<ParseTree, SyntaxError>, Vec<TraceStep>) {
+          let (symbols, errors) = terminals.classify_spanned(tokens);
+          if let Some(error) = errors.into_iter().next() {
+               return (Err(error), Vec::new());
+          }
+          let mut trace = Vec::new();
+          let (tree, errors) = self.predictive_parse(symbols, false, Some(&mut trace), None);
+          match errors.into_iter().next() {
+               Some(error) => (Err(error), trace),
+               None => (Ok(tree), trace),
           }
      }
-}
 
-/*
-     @Description: Parses the input string
-     @Params: input - the input string to parse
-     @Returns: None
-*/
-pub fn parse(input: &str) -> Result<(), String> {
-     unsafe {
-          let mut stack = vec!["$".to_string(), START_SYMBOL.to_string()];
-          let mut input_iter = input.split(" ");
-          let mut input_symbol = input_iter.next().unwrap().to_string();
-          let mut stack_symbol = stack.pop().unwrap();
-
-          while stack_symbol != "$" {
-               if stack_symbol == input_symbol {
-                    input_symbol = input_iter.next().unwrap().to_string();
-                    stack_symbol = stack.pop().unwrap();
-               } else if TERMINALS.contains(&stack_symbol) {
-                    return Err(format!("Error: Expected {}, found {}", stack_symbol, input_symbol));
-               } else if TABLE.contains_key(&(stack_symbol.to_string(), input_symbol.to_string())) {
-                    let rule = TABLE.get(&(stack_symbol.to_string(), input_symbol.to_string())).unwrap();
-                    let mut iter = rule.split(" ::= ");
-                    let lhs = iter.next().unwrap();
-                    let rhs_symbols = iter.next().unwrap().split(" ").rev();
-                    for symbol in rhs_symbols {
-                         stack.push(symbol.to_string());
-                    }
-               } else {
-                    return Err(format!("Error: No rule for {} and {}", stack_symbol, input_symbol));
-               }
-               stack_symbol = stack.pop().unwrap();
+     /*
+          @Description: Parses the lexer's tokens like parse_tokens, counting the productions and parsing table cells used
+          @Params: tokens - the tokens to parse, terminals - the token to terminal mapping, coverage - the counts to add to
+          @Returns: The concrete parse tree or the first lexical or syntax error
+     */
+     pub fn parse_tokens_covered(&self, tokens: &[Token], terminals: &TerminalMap, coverage: &mut Coverage) -> Result<ParseTree, SyntaxError> {
+          let (symbols, errors) = terminals.classify_spanned(tokens);
+          if let Some(error) = errors.into_iter().next() {
+               return Err(error);
+          }
+          let (tree, errors) = self.predictive_parse(symbols, false, None, Some(coverage));
+          match errors.into_iter().next() {
+               Some(error) => Err(error),
+               None => Ok(tree),
           }
-          Ok(())
      }
-}
 
-/*
-     @Description: Prints the grammar
-     @Params: None
-     @Returns: None
-*/
-pub fn print_grammar() {
-     unsafe {
-          println!("Grammar:");
-          for rule in &GRAMMAR {
-               println!("\t{}", rule);
-          }
+     /*
+          @Description: Parses the lexer's tokens, recovering from every syntax error instead of stopping at the first one
+          @Params: tokens - the tokens to parse, terminals - the token to terminal mapping
+          @Returns: The parse tree with missing pieces left unmatched, and every lexical and syntax error found
+     */
+     pub fn parse_tokens_recovering(&self, tokens: &[Token], terminals: &TerminalMap) -> (ParseTree, Vec<SyntaxError>) {
+          let (symbols, mut errors) = terminals.classify_spanned(tokens);
+          let (tree, syntax_errors) = self.predictive_parse(symbols, true, None, None);
+          errors.extend(syntax_errors);
+          (tree, errors)
      }
-}
 
-/*
-     @Description: Prints the terminals
-     @Params: None
-     @Returns: None
-*/
-pub fn print_terminals() {
-     unsafe {
-          println!("Terminals:");
-          for terminal in &TERMINALS {
-               println!("\t{}", terminal);
-          }
+     /*
+          @Description: Gets the rule the table picks for a nonterminal and lookahead
+          @Params: nonterminal - the row, terminal - the column
+          @Returns: The index of the earliest rule in the cell, or None for an error entry
+     */
+     fn rule_for(&self, nonterminal: &str, terminal: &str) -> Option<usize> {
+          self.table.find_cell(nonterminal, terminal).first().cloned()
      }
-}
 
-/*
-     @Description: Prints the nonterminals
-     @Params: None
-     @Returns: None
-*/
-pub fn print_nonterminals() {
-     unsafe {
-          println!("Nonterminals:");
-          for nonterminal in &NONTERMINALS {
-               println!("\t{}", nonterminal);
-          }
+     /*
+          @Description: Lists the terminals with a parsing table entry for a nonterminal, in table column order
+          @Params: nonterminal - the nonterminal
+          @Returns: The terminals the nonterminal can start with, or be followed by when it derives 𝛜
+     */
+     fn expected_terminals(&self, nonterminal: &str) -> Vec<String> {
+          self.table.terminals.iter().filter(|terminal| self.rule_for(nonterminal, terminal).is_some()).cloned().collect()
      }
-}
 
-/*
-     @Description: Prints the first set
-     @Params: None
-     @Returns: None
-*/
-pub fn print_first_set() {
-     unsafe {
-          println!("First Set:");
-          for (symbol, first_set) in &FIRST_SET_DICT {
-               print!("\t{}: ", symbol);
-               for first in first_set {
-                    print!("{} ", first);
+     /*
+          @Description: Runs the table-driven predictive parser, building the parse tree as productions are expanded
+          @Params: input - the terminal, lexeme and span of each input token, recover - whether to repair errors and continue,
+                   trace - where to record each step, if tracing, coverage - where to count the productions and cells used, if measuring
+          @Returns: The parse tree and the errors found; without recovery parsing stops at the first error
+     */
+     fn predictive_parse(
+          &self,
+          mut input: Vec<(String, String, Span)>,
+          recover: bool,
+          mut trace: Option<&mut Vec<TraceStep>>,
+          mut coverage: Option<&mut Coverage>,
+     ) -> (ParseTree, Vec<SyntaxError>) {
+          let end = input.last().map_or(Span { line: 1, column: 1 }, |(_, _, span)| Span { line: span.line, column: span.column + 1 });
+          input.push(("$".to_string(), "$".to_string(), end));
+
+          let mut builder = TreeBuilder::default();
+          let root = builder.add_node(&self.start);
+          let mut stack = vec![
+               ("$".to_string(), root, self.start.to_string()),
+               (self.start.to_string(), root, self.start.to_string()),
+          ];
+          let mut errors = Vec::new();
+          let mut position = 0;
+          let (mut stack_symbol, mut node, mut parent) = stack.pop().unwrap();
+
+          while stack_symbol != "$" {
+               let (input_symbol, lexeme, span) = input[position].clone();
+               let next_symbol = input.get(position + 1).map(|(symbol, _, _)| symbol.to_string());
+               let snapshot = trace.as_ref().map(|_| {
+                    let stack: Vec<&str> = stack.iter().map(|(symbol, _, _)| symbol.as_str()).chain([stack_symbol.as_str()]).collect();
+                    let remaining: Vec<&str> = input[position..].iter().map(|(symbol, _, _)| symbol.as_str()).collect();
+                    (stack.join(" "), remaining.join(" "))
+               });
+
+               if stack_symbol == input_symbol {
+                    record(&mut trace, &snapshot, format!("match `{}`", lexeme));
+                    builder.set_token(node, &lexeme, span);
+                    position += 1;
+               } else if !self.table.nonterminals.contains(&stack_symbol) {
+                    let mut error = SyntaxError {
+                         span,
+                         found: lexeme.to_string(),
+                         expected: vec![stack_symbol.to_string()],
+                         nonterminal: Some(parent.to_string()),
+                         repair: None,
+                    };
+                    if !recover {
+                         record(&mut trace, &snapshot, format!("error: {}", error));
+                         errors.push(error);
+                         break;
+                    }
+                    if next_symbol.as_ref() == Some(&stack_symbol) {
+                         error.repair = Some(format!("deleted `{}`", lexeme));
+                         errors.push(error);
+                         position += 1;
+                         continue;
+                    }
+                    error.repair = Some(format!("inserted `{}`", stack_symbol));
+                    errors.push(error);
+               } else if let Some(number) = self.rule_for(&stack_symbol, &input_symbol) {
+                    let rule = &self.table.rules[number];
+                    let (_, rhs_symbols) = split_rule(rule);
+                    record(&mut trace, &snapshot, format!("expand with production {}: {}", number, rule));
+                    if let Some(coverage) = coverage.as_mut() {
+                         coverage.record(number, &stack_symbol, &input_symbol);
+                    }
+                    let children = builder.expand(node, rule, &rhs_symbols);
+                    for (symbol, child) in rhs_symbols.into_iter().zip(children).rev() {
+                         stack.push((symbol, child, stack_symbol.to_string()));
+                    }
+               } else {
+                    let mut error = SyntaxError {
+                         span,
+                         found: lexeme.to_string(),
+                         expected: self.expected_terminals(&stack_symbol),
+                         nonterminal: Some(stack_symbol.to_string()),
+                         repair: None,
+                    };
+                    if !recover {
+                         record(&mut trace, &snapshot, format!("error: {}", error));
+                         errors.push(error);
+                         break;
+                    }
+                    if input_symbol != "$" && next_symbol.is_some_and(|next| self.rule_for(&stack_symbol, &next).is_some()) {
+                         error.repair = Some(format!("deleted `{}`", lexeme));
+                         errors.push(error);
+                         position += 1;
+                         continue;
+                    }
+
+                    let follow = self.follow.get(&stack_symbol).cloned().unwrap_or_default();
+                    let skipped = position;
+                    while input[position].0 != "$"
+                         && !follow.contains(&input[position].0)
+                         && self.rule_for(&stack_symbol, &input[position].0).is_none()
+                    {
+                         position += 1;
+                    }
+                    let sync = input[position].1.to_string();
+                    if self.rule_for(&stack_symbol, &input[position].0).is_some() {
+                         error.repair = Some(format!("skipped {} tokens to `{}`", position - skipped, sync));
+                         errors.push(error);
+                         continue;
+                    }
+                    error.repair = Some(format!("skipped {} tokens and resumed at `{}`", position - skipped, sync));
+                    errors.push(error);
                }
-               println!("");
+               (stack_symbol, node, parent) = stack.pop().unwrap();
           }
-     }
-}
 
-/*
-     @Description: Prints the follow set
-     @Params: None
-     @Returns: None
-*/
-pub fn print_follow_set() {
-     unsafe {
-          println!("Follow Set:");
-          for (symbol, follow_set) in &FOLLOW_SET_DICT {
-               print!("\t{}: ", symbol);
-               for follow in follow_set {
-                    print!("{} ", follow);
-               }
-               println!("");
+          let snapshot = trace.as_ref().map(|_| {
+               let remaining: Vec<&str> = input[position..].iter().map(|(symbol, _, _)| symbol.as_str()).collect();
+               (String::from("$"), remaining.join(" "))
+          });
+          if input[position].0 != "$" && (recover || errors.is_empty()) {
+               let error = SyntaxError {
+                    span: input[position].2,
+                    found: input[position].1.to_string(),
+                    expected: vec!["$".to_string()],
+                    nonterminal: Some(self.start.to_string()),
+                    repair: None,
+               };
+               record(&mut trace, &snapshot, format!("error: {}", error));
+               errors.push(error);
+          } else if errors.is_empty() {
+               record(&mut trace, &snapshot, String::from("accept"));
           }
+          (builder.finish(root), errors)
      }
 }
 
 /*
-     @Description: Prints the lookahead
-     @Params: None
-     @Returns: None
+     @Description: Renders parser steps as a table like the lexer logs
+     @Params: steps - the steps
+     @Returns: The table
 */
-pub fn print_lookahead() {
-     unsafe {
-          println!("Lookahead:");
-          for (symbol, lookahead) in &LOOKAHEAD {
-               print!("\t{}: ", symbol);
-               for lookahead in lookahead {
-                    print!("{} ", lookahead.0);
-               }
-               println!("");
-          }
+pub fn trace_table(steps: &[TraceStep]) -> Table {
+     let mut table = Table::new();
+     table.set_header(vec!["Step", "Stack", "Input", "Action"]);
+     for (index, step) in steps.iter().enumerate() {
+          table.add_row(vec![(index + 1).to_string(), step.stack.to_string(), step.input.to_string(), step.action.to_string()]);
      }
+     table
 }
 
 /*
-     @Description: Prints the parsing table
-     @Params: None
+     @Description: Writes parser steps to a log file
+     @Params: steps - the steps, path - the log file, e.g. "data/output/syntax-trace.log"
      @Returns: None
 */
-pub fn print_table() {
-     unsafe {
-          println!("Parsing Table:");
-          for ((lhs, lookahead), rule) in &TABLE {
-               println!("\t{} {} {}", lhs, lookahead, rule);
-          }
-     }
+pub fn write_trace(steps: &[TraceStep], path: &str) {
+     let mut trace_file = File::create(path).expect("Unable to create file");
+     writeln!(trace_file, "{}", trace_table(steps)).expect("Unable to write to file");
 }
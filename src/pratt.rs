@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use crate::ast::Expr;
+use crate::cst::{Event, NodeKind};
+use crate::lexer::Token;
+use crate::lr::Assoc;
+use crate::syntax_error::SyntaxError;
+use crate::terminals::TerminalMap;
+use crate::tree::Span;
+
+/*
+     @Description: Enum for the postfix operators with a closing terminal; a call takes comma separated arguments, an index takes one expression
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostfixKind {
+     Call,
+     Index,
+}
+
+/*
+     @Description: Struct for the operator table of the Pratt parser; every operator sits on a level, and a higher level binds tighter
+     @Params: None
+     @Returns: None
+*/
+#[derive(Default)]
+pub struct OperatorTable {
+     pub prefix: HashMap<String, usize>,
+     pub infix: HashMap<String, (usize, Assoc)>,
+     pub postfix: HashMap<String, (usize, PostfixKind, String)>,
+     pub count: usize,
+}
+
+impl OperatorTable {
+     /*
+          @Description: Creates the table for EZ, matching the grouping of the expr/term/factor and bexpr/bterm/bfactor rules;
+                        not sits between and and the comparisons, so not a < b negates the whole comparison
+          @Params: None
+          @Returns: The operator table
+     */
+     pub fn ez() -> OperatorTable {
+          let mut table = OperatorTable::default();
+          table.add_infix(Assoc::Left, &["or"]);
+          table.add_infix(Assoc::Left, &["and"]);
+          table.add_prefix(&["not"]);
+          table.add_infix(Assoc::NonAssoc, &["<", ">", "==", "<=", ">=", "<>"]);
+          table.add_infix(Assoc::Left, &["+", "-"]);
+          table.add_infix(Assoc::Left, &["*", "/", "%"]);
+          table.add_prefix(&["-"]);
+          table.add_postfix(&[(PostfixKind::Call, "(", ")"), (PostfixKind::Index, "[", "]")]);
+          table
+     }
+
+     /*
+          @Description: Adds a level of binary operators binding tighter than every level added before it
+          @Params: assoc - the associativity of the level, terminals - the operators on the level
+          @Returns: None
+     */
+     pub fn add_infix(&mut self, assoc: Assoc, terminals: &[&str]) {
+          self.count += 1;
+          for terminal in terminals {
+               self.infix.insert(terminal.to_string(), (self.count, assoc));
+          }
+     }
+
+     /*
+          @Description: Adds a level of prefix operators binding tighter than every level added before it
+          @Params: terminals - the operators on the level
+          @Returns: None
+     */
+     pub fn add_prefix(&mut self, terminals: &[&str]) {
+          self.count += 1;
+          for terminal in terminals {
+               self.prefix.insert(terminal.to_string(), self.count);
+          }
+     }
+
+     /*
+          @Description: Adds a level of bracketing postfix operators binding tighter than every level added before it
+          @Params: operators - the kind, opening and closing terminal of each operator on the level
+          @Returns: None
+     */
+     pub fn add_postfix(&mut self, operators: &[(PostfixKind, &str, &str)]) {
+          self.count += 1;
+          for (kind, open, close) in operators {
+               self.postfix.insert(open.to_string(), (self.count, *kind, close.to_string()));
+          }
+     }
+
+     /*
+          @Description: Reads an operator table, one level per line from loosest to tightest; lines are
+                        "%left", "%right" or "%nonassoc" followed by operators, "%prefix" followed by operators,
+                        or "%postfix" followed by triples like "call ( )" or "index [ ]"
+          @Params: source - the table text
+          @Returns: The operator table or an error message
+     */
+     pub fn parse(source: &str) -> Result<OperatorTable, String> {
+          let mut table = OperatorTable::default();
+          for (index, line) in source.lines().enumerate() {
+               let mut words = line.split_whitespace();
+               let directive = match words.next() {
+                    Some(word) if word.starts_with('%') => word,
+                    _ => continue,
+               };
+               let operators: Vec<&str> = words.collect();
+               match directive {
+                    "%left" => table.add_infix(Assoc::Left, &operators),
+                    "%right" => table.add_infix(Assoc::Right, &operators),
+                    "%nonassoc" => table.add_infix(Assoc::NonAssoc, &operators),
+                    "%prefix" => table.add_prefix(&operators),
+                    "%postfix" => {
+                         let mut postfix = Vec::new();
+                         for triple in operators.chunks(3) {
+                              let kind = match triple[0] {
+                                   "call" => PostfixKind::Call,
+                                   "index" => PostfixKind::Index,
+                                   _ => return Err(format!("Error: Unknown postfix kind {} on line {}", triple[0], index + 1)),
+                              };
+                              if triple.len() != 3 {
+                                   return Err(format!("Error: Postfix {} on line {} needs an opening and closing terminal", triple[0], index + 1));
+                              }
+                              postfix.push((kind, triple[1], triple[2]));
+                         }
+                         table.add_postfix(&postfix);
+                    }
+                    _ => return Err(format!("Error: Unknown directive {} on line {}", directive, index + 1)),
+               }
+          }
+          Ok(table)
+     }
+}
+
+/*
+     @Description: Struct for a Pratt parser over classified tokens; it can be started at any token, so a statement parser can hand expressions to it.
+                   Alongside the AST it records the syntax tree events of what it consumed, which the concrete syntax tree is built from
+     @Params: None
+     @Returns: None
+*/
+pub struct ExprParser<'a> {
+     pub input: Vec<(String, String, Span)>,
+     pub position: usize,
+     pub events: Vec<Event>,
+     table: &'a OperatorTable,
+}
+
+impl<'a> ExprParser<'a> {
+     /*
+          @Description: Creates a parser at the start of the input
+          @Params: input - the (terminal, lexeme, span) triples of the tokens, table - the operators to parse
+          @Returns: The parser
+     */
+     pub fn new(input: Vec<(String, String, Span)>, table: &'a OperatorTable) -> ExprParser<'a> {
+          ExprParser { input, position: 0, events: Vec::new(), table }
+     }
+
+     /*
+          @Description: Consumes the current token
+          @Params: None
+          @Returns: None
+     */
+     pub fn bump(&mut self) {
+          self.position += 1;
+          self.events.push(Event::Token);
+     }
+
+     /*
+          @Description: Opens a node at the current position; the tokens consumed until the matching finish become its children
+          @Params: kind - the node kind
+          @Returns: None
+     */
+     pub fn start(&mut self, kind: NodeKind) {
+          self.events.push(Event::Start(kind));
+     }
+
+     /*
+          @Description: Closes the node opened last
+          @Params: None
+          @Returns: None
+     */
+     pub fn finish(&mut self) {
+          self.events.push(Event::Finish);
+     }
+
+     /*
+          @Description: Marks the position where a node may later be opened around what follows, for left-recursive
+                        constructs like a + b or a[i]
+          @Params: None
+          @Returns: The checkpoint
+     */
+     pub fn checkpoint(&self) -> usize {
+          self.events.len()
+     }
+
+     /*
+          @Description: Opens a node that takes over everything recorded since a checkpoint
+          @Params: checkpoint - the checkpoint, kind - the node kind
+          @Returns: None
+     */
+     pub fn start_at(&mut self, checkpoint: usize, kind: NodeKind) {
+          self.events.insert(checkpoint, Event::Start(kind));
+     }
+
+     /*
+          @Description: Gets the terminal of the current token
+          @Params: None
+          @Returns: The terminal, or "$" at the end of input
+     */
+     pub fn peek(&self) -> &str {
+          match self.input.get(self.position) {
+               Some((terminal, _, _)) => terminal,
+               None => "$",
+          }
+     }
+
+     /*
+          @Description: Creates an error at the current token
+          @Params: expected - the terminals that would have been accepted
+          @Returns: The error
+     */
+     pub fn error(&self, mut expected: Vec<String>) -> SyntaxError {
+          expected.sort();
+          expected.dedup();
+          let (found, span) = match self.input.get(self.position) {
+               Some((_, lexeme, span)) => (lexeme.to_string(), *span),
+               None => (String::from("$"), self.input.last().map(|(_, _, span)| *span).unwrap_or(Span { line: 1, column: 1 })),
+          };
+          SyntaxError { span, found, expected, nonterminal: Some(String::from("expression")), repair: None }
+     }
+
+     /*
+          @Description: Consumes a terminal
+          @Params: terminal - the terminal
+          @Returns: An error if the current token is something else
+     */
+     fn expect(&mut self, terminal: &str) -> Result<(), SyntaxError> {
+          if self.peek() != terminal {
+               return Err(self.error(vec![terminal.to_string()]));
+          }
+          self.bump();
+          Ok(())
+     }
+
+     /*
+          @Description: Lists the terminals that can start an operand
+          @Params: None
+          @Returns: The terminals
+     */
+     fn operand_terminals(&self) -> Vec<String> {
+          let mut expected = vec![String::from("ID"), String::from("NUMBER"), String::from("(")];
+          expected.extend(self.table.prefix.keys().cloned());
+          expected
+     }
+
+     /*
+          @Description: Parses an expression whose operators all bind at least as tightly as a binding power;
+                        a level l operator binds with power 2l on its left and 2l + 1 on its right, swapped for right associative levels
+          @Params: min_power - the binding power, 0 for a whole expression
+          @Returns: The expression or the first syntax error; after an error the events recorded since the call are
+                    unbalanced, and the caller drops them
+     */
+     pub fn parse_expr(&mut self, min_power: usize) -> Result<Expr, SyntaxError> {
+          let checkpoint = self.checkpoint();
+          let mut left = self.parse_operand()?;
+          let mut nonassoc_level = None;
+          loop {
+               let terminal = self.peek().to_string();
+               if let Some((level, kind, close)) = self.table.postfix.get(&terminal).cloned() {
+                    if 2 * level < min_power {
+                         break;
+                    }
+                    let span = left.span();
+                    left = match kind {
+                         PostfixKind::Call => {
+                              self.start_at(checkpoint, NodeKind::CallExpr);
+                              self.start(NodeKind::ArgList);
+                              self.bump();
+                              let mut args = Vec::new();
+                              let mut trailing_comma = false;
+                              if self.peek() != close {
+                                   args.push(self.parse_expr(0)?);
+                                   while self.peek() == "," {
+                                        self.bump();
+                                        if self.peek() == close {
+                                             trailing_comma = true;
+                                             break;
+                                        }
+                                        args.push(self.parse_expr(0)?);
+                                   }
+                              }
+                              if self.peek() != close {
+                                   return Err(self.error(vec![close, String::from(",")]));
+                              }
+                              self.bump();
+                              self.finish();
+                              Expr::Call { callee: Box::new(left), args, trailing_comma, span }
+                         }
+                         PostfixKind::Index => {
+                              self.start_at(checkpoint, NodeKind::IndexExpr);
+                              self.bump();
+                              let index = self.parse_expr(0)?;
+                              self.expect(&close)?;
+                              Expr::Index { target: Box::new(left), index: Box::new(index), span }
+                         }
+                    };
+                    self.finish();
+                    continue;
+               }
+
+               if let Some(&(level, assoc)) = self.table.infix.get(&terminal) {
+                    let (left_power, right_power) = match assoc {
+                         Assoc::Right => (2 * level + 1, 2 * level),
+                         _ => (2 * level, 2 * level + 1),
+                    };
+                    if left_power < min_power {
+                         break;
+                    }
+                    if nonassoc_level == Some(level) {
+                         let mut error = self.error(
+                              self.table
+                                   .infix
+                                   .iter()
+                                   .filter(|(_, (other, _))| *other != level)
+                                   .map(|(op, _)| op.clone())
+                                   .collect(),
+                         );
+                         error.repair = Some(format!("`{}` is non-associative, parenthesise one side", terminal));
+                         return Err(error);
+                    }
+                    self.start_at(checkpoint, NodeKind::BinExpr);
+                    self.bump();
+                    let right = self.parse_expr(right_power)?;
+                    self.finish();
+                    let span = left.span();
+                    left = Expr::Binary { op: terminal, left: Box::new(left), right: Box::new(right), span };
+                    nonassoc_level = if assoc == Assoc::NonAssoc { Some(level) } else { None };
+                    continue;
+               }
+               break;
+          }
+          Ok(left)
+     }
+
+     /*
+          @Description: Parses an identifier, number, parenthesised expression or prefix operator application
+          @Params: None
+          @Returns: The operand or the first syntax error
+     */
+     fn parse_operand(&mut self) -> Result<Expr, SyntaxError> {
+          let (terminal, lexeme, span) = match self.input.get(self.position) {
+               Some(token) => token.clone(),
+               None => return Err(self.error(self.operand_terminals())),
+          };
+          if let Some(&level) = self.table.prefix.get(&terminal) {
+               self.start(NodeKind::PrefixExpr);
+               self.bump();
+               let operand = self.parse_expr(2 * level)?;
+               self.finish();
+               return Ok(Expr::Unary { op: terminal, operand: Box::new(operand), span });
+          }
+          let expr = match terminal.as_str() {
+               "ID" => {
+                    self.start(NodeKind::Name);
+                    self.bump();
+                    Expr::Ident { name: lexeme, span }
+               }
+               "NUMBER" => {
+                    self.start(NodeKind::Literal);
+                    self.bump();
+                    Expr::Number { value: lexeme, span }
+               }
+               "(" => {
+                    self.start(NodeKind::ParenExpr);
+                    self.bump();
+                    let inner = self.parse_expr(0)?;
+                    self.expect(")")?;
+                    Expr::Paren { inner: Box::new(inner), span }
+               }
+               _ => return Err(self.error(self.operand_terminals())),
+          };
+          self.finish();
+          Ok(expr)
+     }
+}
+
+/*
+     @Description: Parses tokens holding a single expression, optionally followed by the closing period
+     @Params: tokens - the tokens, terminals - the token to terminal mapping, table - the operators
+     @Returns: The expression or the first lexical or syntax error
+*/
+pub fn parse_expression(tokens: &[Token], terminals: &TerminalMap, table: &OperatorTable) -> Result<Expr, SyntaxError> {
+     let (mut input, errors) = terminals.classify_spanned(tokens);
+     if let Some(error) = errors.into_iter().next() {
+          return Err(error);
+     }
+     if input.last().map(|(terminal, _, _)| terminal == ".").unwrap_or(false) {
+          input.pop();
+     }
+
+     let mut parser = ExprParser::new(input, table);
+     let expr = parser.parse_expr(0)?;
+     if parser.position < parser.input.len() {
+          let mut expected: Vec<String> = table.infix.keys().chain(table.postfix.keys()).cloned().collect();
+          expected.push(String::from("$"));
+          return Err(parser.error(expected));
+     }
+     Ok(expr)
+}
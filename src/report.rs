@@ -0,0 +1,179 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::grammar::{first_sets, follow_sets, nonterminals, predict_sets, EPSILON};
+
+/*
+     @Description: Struct for the output paths of the set reports
+     @Params: None
+     @Returns: None
+*/
+pub struct ReportPaths {
+     pub first: String,
+     pub follow: String,
+     pub predict: String,
+     pub json: String,
+}
+
+impl ReportPaths {
+     /*
+          @Description: Places every report in one directory under its default name
+          @Params: directory - the output directory, e.g. "data/output"
+          @Returns: The paths
+     */
+     pub fn new(directory: &str) -> ReportPaths {
+          let directory = directory.trim_end_matches('/');
+          ReportPaths {
+               first: format!("{}/first.log", directory),
+               follow: format!("{}/follow.log", directory),
+               predict: format!("{}/predict.log", directory),
+               json: format!("{}/sets.json", directory),
+          }
+     }
+}
+
+/*
+     @Description: Sorts the members of a set, spelling epsilon as EPSILON and listing it last
+     @Params: set - the set
+     @Returns: The sorted members
+*/
+pub fn sorted_members(set: &HashSet<String>) -> Vec<String> {
+     let mut members: Vec<String> = set.iter().filter(|s| *s != EPSILON).cloned().collect();
+     members.sort();
+     if set.contains(EPSILON) {
+          members.push(String::from("EPSILON"));
+     }
+     members
+}
+
+/*
+     @Description: Quotes and escapes text as a JSON string
+     @Params: text - the text
+     @Returns: The JSON string literal
+*/
+pub fn json_string(text: &str) -> String {
+     let mut output = String::from("\"");
+     for c in text.chars() {
+          match c {
+               '"' => output.push_str("\\\""),
+               '\\' => output.push_str("\\\\"),
+               '\n' => output.push_str("\\n"),
+               '\t' => output.push_str("\\t"),
+               '\r' => output.push_str("\\r"),
+               c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+               c => output.push(c),
+          }
+     }
+     output.push('"');
+     output
+}
+
+/*
+     @Description: Escapes text for XML and HTML, in element content and in double quoted attributes alike
+     @Params: text - the text
+     @Returns: The escaped text
+*/
+pub fn xml_escape(text: &str) -> String {
+     text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/*
+     @Description: Formats a list of strings as a JSON array on one line
+     @Params: items - the strings
+     @Returns: The JSON array
+*/
+fn json_list(items: &[String]) -> String {
+     let quoted: Vec<String> = items.iter().map(|item| json_string(item)).collect();
+     format!("[{}]", quoted.join(", "))
+}
+
+/*
+     @Description: Formats sets in the first.log / follow.log format, one `"name", ["a", "b"]` line per entry
+     @Params: order - the entries in output order, sets - the sets
+     @Returns: The report text
+*/
+pub fn format_set_log(order: &[String], sets: &HashMap<String, HashSet<String>>) -> String {
+     let mut output = String::new();
+     for name in order {
+          if let Some(set) = sets.get(name) {
+               output.push_str(&format!("{}, {}\n", json_string(name), json_list(&sorted_members(set))));
+          }
+     }
+     output
+}
+
+/*
+     @Description: Formats the predict set of every rule in the same format as the set logs
+     @Params: grammar - the grammar rules, predict - the predict sets
+     @Returns: The report text
+*/
+pub fn format_predict_log(grammar: &[String], predict: &[HashSet<String>]) -> String {
+     let mut output = String::new();
+     for (rule, set) in grammar.iter().zip(predict) {
+          output.push_str(&format!("{}, {}\n", json_string(rule), json_list(&sorted_members(set))));
+     }
+     output
+}
+
+/*
+     @Description: Formats the first, follow and predict sets as one JSON document
+     @Params: grammar - the grammar rules, first - the first sets, follow - the follow sets, predict - the predict sets
+     @Returns: The JSON text
+*/
+pub fn format_sets_json(
+     grammar: &[String],
+     first: &HashMap<String, HashSet<String>>,
+     follow: &HashMap<String, HashSet<String>>,
+     predict: &[HashSet<String>],
+) -> String {
+     let order = nonterminals(grammar);
+     let mut output = String::from("{\n");
+     for (key, sets) in [("first", first), ("follow", follow)] {
+          output.push_str(&format!("  {}: {{\n", json_string(key)));
+          let entries: Vec<String> = order
+               .iter()
+               .map(|name| format!("    {}: {}", json_string(name), json_list(&sorted_members(&sets[name]))))
+               .collect();
+          output.push_str(&entries.join(",\n"));
+          output.push_str("\n  },\n");
+     }
+     output.push_str("  \"predict\": [\n");
+     let entries: Vec<String> = grammar
+          .iter()
+          .zip(predict)
+          .map(|(rule, set)| {
+               format!("    {{\"rule\": {}, \"terminals\": {}}}", json_string(rule), json_list(&sorted_members(set)))
+          })
+          .collect();
+     output.push_str(&entries.join(",\n"));
+     output.push_str("\n  ]\n}\n");
+     output
+}
+
+/*
+     @Description: Writes text to a file
+     @Params: path - the file to write, contents - the text
+     @Returns: An error message if the file could not be written
+*/
+pub fn write_file(path: &str, contents: &str) -> Result<(), String> {
+     let mut file = File::create(path).map_err(|e| format!("Error: Unable to create {}: {}", path, e))?;
+     file.write_all(contents.as_bytes()).map_err(|e| format!("Error: Unable to write {}: {}", path, e))
+}
+
+/*
+     @Description: Writes the first, follow and predict set reports of a grammar
+     @Params: grammar - the grammar rules, paths - where to write each report
+     @Returns: An error message if a report could not be written
+*/
+pub fn write_set_reports(grammar: &[String], paths: &ReportPaths) -> Result<(), String> {
+     let first = first_sets(grammar);
+     let follow = follow_sets(grammar, &first);
+     let predict = predict_sets(grammar, &first, &follow);
+     let order = nonterminals(grammar);
+
+     write_file(&paths.first, &format_set_log(&order, &first))?;
+     write_file(&paths.follow, &format_set_log(&order, &follow))?;
+     write_file(&paths.predict, &format_predict_log(grammar, &predict))?;
+     write_file(&paths.json, &format_sets_json(grammar, &first, &follow, &predict))
+}
@@ -0,0 +1,441 @@
+use crate::ast::{Decl, Expr, FnDef, Param, Program, Stmt, Type};
+use crate::cst::{Event, NodeKind};
+use crate::lexer::Token;
+use crate::pratt::{ExprParser, OperatorTable};
+use crate::syntax_error::SyntaxError;
+use crate::terminals::TerminalMap;
+use crate::tree::Span;
+
+/*
+     @Description: Terminals an expression never continues into; a broken expression is skipped up to one of them
+     @Params: None
+     @Returns: None
+*/
+const EXPR_STOP: [&str; 12] = [";", ".", "then", "do", "else", "fi", "od", "fed", ",", ")", "]", "="];
+
+/*
+     @Description: Terminals that close a statement sequence; a sequence stops at any of them and the construct that
+                   owns it reports the one it expected
+     @Params: None
+     @Returns: None
+*/
+const CLOSERS: [&str; 6] = [".", "else", "fi", "od", "fed", "$"];
+
+/*
+     @Description: Struct for a recursive descent parser that never gives up: a missing piece becomes a Missing node,
+                   tokens it cannot use are skipped into an Error node, and every problem is recorded as a diagnostic.
+                   Expressions, conditions included, go to the Pratt parser with the EZ operator table, which also holds
+                   the syntax tree events both parsers record
+     @Params: None
+     @Returns: None
+*/
+struct ResilientParser<'a> {
+     exprs: ExprParser<'a>,
+     errors: Vec<SyntaxError>,
+}
+
+impl<'a> ResilientParser<'a> {
+     /*
+          @Description: Gets the terminal of the current token
+          @Params: None
+          @Returns: The terminal, or "$" at the end of input
+     */
+     fn peek(&self) -> &str {
+          self.exprs.peek()
+     }
+
+     fn bump(&mut self) {
+          self.exprs.bump();
+     }
+
+     fn start(&mut self, kind: NodeKind) {
+          self.exprs.start(kind);
+     }
+
+     fn finish(&mut self) {
+          self.exprs.finish();
+     }
+
+     /*
+          @Description: Gets the position of the current token, or just after the last token at the end of input
+          @Params: None
+          @Returns: The span
+     */
+     fn span(&self) -> Span {
+          match self.exprs.input.get(self.exprs.position) {
+               Some((_, _, span)) => *span,
+               None => self.exprs.input.last().map_or(Span { line: 1, column: 1 }, |(_, lexeme, span)| Span {
+                    line: span.line,
+                    column: span.column + lexeme.chars().count(),
+               }),
+          }
+     }
+
+     /*
+          @Description: Records an error at the current token
+          @Params: expected - the terminals that would have been accepted, nonterminal - what was being parsed,
+                   repair - what the parser did about it
+          @Returns: None
+     */
+     fn error(&mut self, expected: &[&str], nonterminal: &str, repair: String) {
+          let mut error = self.exprs.error(expected.iter().map(|terminal| terminal.to_string()).collect());
+          error.nonterminal = Some(nonterminal.to_string());
+          error.repair = Some(repair);
+          self.errors.push(error);
+     }
+
+     /*
+          @Description: Consumes a terminal, or records it as missing and carries on as if it had been there
+          @Params: terminal - the terminal, nonterminal - what was being parsed
+          @Returns: None
+     */
+     fn expect(&mut self, terminal: &str, nonterminal: &str) {
+          if self.peek() == terminal {
+               self.bump();
+          } else {
+               self.error(&[terminal], nonterminal, format!("inserted `{}`", terminal));
+          }
+     }
+
+     /*
+          @Description: Skips tokens into an Error node up to one of the stop terminals or the end of input; a comma or
+                   closing bracket only stops outside brackets
+          @Params: stop - the stop terminals
+          @Returns: The lexeme and span of each skipped token
+     */
+     fn skip_to(&mut self, stop: &[&str]) -> Vec<(String, Span)> {
+          self.start(NodeKind::Error);
+          let mut skipped = Vec::new();
+          let mut depth = 0;
+          while let Some((terminal, lexeme, span)) = self.exprs.input.get(self.exprs.position).cloned() {
+               match terminal.as_str() {
+                    "(" | "[" => depth += 1,
+                    ")" | "]" if depth > 0 => depth -= 1,
+                    "," | ")" | "]" if depth > 0 => {}
+                    _ if stop.contains(&terminal.as_str()) => break,
+                    _ => {}
+               }
+               skipped.push((lexeme, span));
+               self.bump();
+          }
+          self.finish();
+          skipped
+     }
+
+     /*
+          @Description: Records an error at the current token and skips tokens up to a stop terminal
+          @Params: expected - the terminals that would have been accepted, nonterminal - what was being parsed,
+                   stop - the stop terminals
+          @Returns: The lexeme and span of each skipped token
+     */
+     fn skip_error(&mut self, expected: &[&str], nonterminal: &str, stop: &[&str]) -> Vec<(String, Span)> {
+          let mut error = self.exprs.error(expected.iter().map(|terminal| terminal.to_string()).collect());
+          let tokens = self.skip_to(stop);
+          error.nonterminal = Some(nonterminal.to_string());
+          error.repair = Some(format!("skipped {} tokens", tokens.len()));
+          self.errors.push(error);
+          tokens
+     }
+
+     /*
+          @Description: Parses an expression; with nothing to parse it is Missing, and a broken one is skipped into an Error node
+          @Params: None
+          @Returns: The expression
+     */
+     fn expr(&mut self) -> Expr {
+          let start = self.exprs.position;
+          let events = self.exprs.events.len();
+          match self.exprs.parse_expr(0) {
+               Ok(expr) => expr,
+               Err(mut error) => {
+                    self.exprs.events.truncate(events);
+                    let span = self.span();
+                    if self.exprs.position == start && (self.peek() == "$" || EXPR_STOP.contains(&self.peek())) {
+                         error.repair = Some(String::from("inserted a missing expression"));
+                         self.errors.push(error);
+                         return Expr::Missing { span };
+                    }
+                    self.exprs.position = start;
+                    let tokens = self.skip_to(&EXPR_STOP);
+                    error.repair = Some(format!("skipped {} tokens", tokens.len()));
+                    self.errors.push(error);
+                    Expr::Error { span: tokens[0].1, tokens }
+               }
+          }
+     }
+
+     /*
+          @Description: Parses a variable, an identifier optionally indexed by an expression
+          @Params: None
+          @Returns: The variable, or a Missing node without an identifier
+     */
+     fn var(&mut self) -> Expr {
+          let span = self.span();
+          let name = match self.exprs.input.get(self.exprs.position) {
+               Some((terminal, lexeme, _)) if terminal == "ID" => lexeme.to_string(),
+               _ => {
+                    self.error(&["ID"], "var", String::from("inserted a missing variable"));
+                    return Expr::Missing { span };
+               }
+          };
+          let checkpoint = self.exprs.checkpoint();
+          self.start(NodeKind::Name);
+          self.bump();
+          self.finish();
+          let target = Expr::Ident { name, span };
+          if self.peek() != "[" {
+               return target;
+          }
+          self.exprs.start_at(checkpoint, NodeKind::IndexExpr);
+          self.bump();
+          let index = self.expr();
+          self.expect("]", "var");
+          self.finish();
+          Expr::Index { target: Box::new(target), index: Box::new(index), span }
+     }
+
+     /*
+          @Description: Parses a type
+          @Params: None
+          @Returns: The type, or Type::Missing
+     */
+     fn ty(&mut self) -> Type {
+          let ty = match self.peek() {
+               "int" => Type::Int,
+               "double" => Type::Double,
+               _ => {
+                    self.error(&["double", "int"], "type", String::from("inserted a missing type"));
+                    return Type::Missing;
+               }
+          };
+          self.bump();
+          ty
+     }
+
+     /*
+          @Description: Parses declarations, each a type and a comma separated variable list ended by a semicolon
+          @Params: None
+          @Returns: The declarations
+     */
+     fn declarations(&mut self) -> Vec<Decl> {
+          let mut declarations = Vec::new();
+          while self.peek() == "int" || self.peek() == "double" {
+               let span = self.span();
+               self.start(NodeKind::Decl);
+               let ty = self.ty();
+               let mut vars = vec![self.var()];
+               while self.peek() == "," {
+                    self.bump();
+                    vars.push(self.var());
+               }
+               self.expect(";", "declarations");
+               self.finish();
+               declarations.push(Decl { ty, vars, span });
+          }
+          declarations
+     }
+
+     /*
+          @Description: Parses a function definition from def to fed; the grammar allows a comma after the last parameter
+          @Params: None
+          @Returns: The function
+     */
+     fn fn_def(&mut self) -> FnDef {
+          let span = self.span();
+          self.start(NodeKind::FnDef);
+          self.expect("def", "fdec");
+          let return_type = self.ty();
+          let name = match self.exprs.input.get(self.exprs.position).cloned() {
+               Some((terminal, lexeme, name_span)) if terminal == "ID" => {
+                    self.bump();
+                    Expr::Ident { name: lexeme, span: name_span }
+               }
+               _ => {
+                    let name_span = self.span();
+                    self.error(&["ID"], "fname", String::from("inserted a missing name"));
+                    Expr::Missing { span: name_span }
+               }
+          };
+          self.start(NodeKind::ParamList);
+          self.expect("(", "fdec");
+          let mut params = Vec::new();
+          let mut trailing_comma = false;
+          while self.peek() == "int" || self.peek() == "double" {
+               let param_span = self.span();
+               self.start(NodeKind::Param);
+               let ty = self.ty();
+               let var = self.var();
+               self.finish();
+               params.push(Param { ty, var, span: param_span });
+               trailing_comma = self.peek() == ",";
+               if !trailing_comma {
+                    break;
+               }
+               self.bump();
+          }
+          self.expect(")", "fdec");
+          self.finish();
+          let declarations = self.declarations();
+          let body = self.statement_seq(&["fed"]);
+          self.expect("fed", "fdec");
+          self.finish();
+          FnDef { return_type, name, params, trailing_comma, declarations, body, span }
+     }
+
+     /*
+          @Description: Parses statements separated by semicolons up to a closing terminal; tokens that cannot follow a
+                        statement are skipped into an Error statement
+          @Params: terminators - the closing terminals the caller expects, for the diagnostics
+          @Returns: The statements
+     */
+     fn statement_seq(&mut self, terminators: &[&str]) -> Vec<Stmt> {
+          self.start(NodeKind::StmtList);
+          let mut body = Vec::new();
+          loop {
+               body.push(self.statement());
+               if self.peek() == ";" {
+                    self.bump();
+                    continue;
+               }
+               if CLOSERS.contains(&self.peek()) {
+                    break;
+               }
+               let mut expected = vec![";"];
+               expected.extend(terminators);
+               let mut stop = vec![";"];
+               stop.extend(CLOSERS);
+               let tokens = self.skip_error(&expected, "statement_seq", &stop);
+               body.push(Stmt::Error { span: tokens[0].1, tokens });
+               if self.peek() != ";" {
+                    break;
+               }
+               self.bump();
+          }
+          self.finish();
+          body
+     }
+
+     /*
+          @Description: Parses one statement; before a semicolon or closing terminal it is the empty statement
+          @Params: None
+          @Returns: The statement
+     */
+     fn statement(&mut self) -> Stmt {
+          let span = self.span();
+          match self.peek() {
+               "ID" => {
+                    self.start(NodeKind::AssignStmt);
+                    let target = self.var();
+                    self.expect("=", "statement");
+                    let value = self.expr();
+                    self.finish();
+                    Stmt::Assign { target, value, span }
+               }
+               "if" => {
+                    self.start(NodeKind::IfStmt);
+                    self.bump();
+                    let cond = self.expr();
+                    self.expect("then", "statement");
+                    let then_branch = self.statement_seq(&["else", "fi"]);
+                    let else_branch = if self.peek() == "else" {
+                         self.start(NodeKind::ElseClause);
+                         self.bump();
+                         let else_branch = self.statement_seq(&["fi"]);
+                         self.finish();
+                         Some(else_branch)
+                    } else {
+                         None
+                    };
+                    self.expect("fi", "statement");
+                    self.finish();
+                    Stmt::If { cond, then_branch, else_branch, span }
+               }
+               "while" => {
+                    self.start(NodeKind::WhileStmt);
+                    self.bump();
+                    let cond = self.expr();
+                    self.expect("do", "statement");
+                    let body = self.statement_seq(&["od"]);
+                    self.expect("od", "statement");
+                    self.finish();
+                    Stmt::While { cond, body, span }
+               }
+               "print" => {
+                    self.start(NodeKind::PrintStmt);
+                    self.bump();
+                    let value = self.expr();
+                    self.finish();
+                    Stmt::Print { value, span }
+               }
+               "return" => {
+                    self.start(NodeKind::ReturnStmt);
+                    self.bump();
+                    let value = self.expr();
+                    self.finish();
+                    Stmt::Return { value, span }
+               }
+               terminal if terminal == ";" || CLOSERS.contains(&terminal) => Stmt::Empty { span },
+               _ => {
+                    let mut stop = vec![";"];
+                    stop.extend(CLOSERS);
+                    let tokens = self.skip_error(&["ID", "if", "print", "return", "while"], "statement", &stop);
+                    Stmt::Error { tokens, span }
+               }
+          }
+     }
+
+     /*
+          @Description: Parses a whole program; a function defined after the declarations is reported and kept, and tokens
+                   left after the closing period are skipped into an Error statement
+          @Params: None
+          @Returns: The program
+     */
+     fn program(&mut self) -> Program {
+          let span = self.span();
+          self.start(NodeKind::Program);
+          let mut functions = Vec::new();
+          while self.peek() == "def" {
+               functions.push(self.fn_def());
+               self.expect(";", "fdecls");
+          }
+          let mut declarations = self.declarations();
+          while self.peek() == "def" {
+               self.error(&["ID", "double", "if", "int", "print", "return", "while"], "program", String::from("kept a function defined out of place"));
+               functions.push(self.fn_def());
+               self.expect(";", "fdecls");
+               declarations.extend(self.declarations());
+          }
+          let mut body = self.statement_seq(&["."]);
+          self.expect(".", "program");
+          if self.peek() != "$" {
+               let tokens = self.skip_error(&["$"], "program", &[]);
+               body.push(Stmt::Error { span: tokens[0].1, tokens });
+          }
+          self.finish();
+          Program { functions, declarations, body, span }
+     }
+}
+
+/*
+     @Description: Parses a program without stopping at errors, so tools get a tree for half-typed input
+     @Params: tokens - the tokens to parse, terminals - the token to terminal mapping, table - the operators for expressions
+     @Returns: The program, with Missing and Error nodes where the input is broken, and every lexical and syntax error found
+*/
+pub fn parse_program(tokens: &[Token], terminals: &TerminalMap, table: &OperatorTable) -> (Program, Vec<SyntaxError>) {
+     let (input, errors) = terminals.classify_spanned(tokens);
+     let (program, _, errors) = parse_classified(input, errors, table);
+     (program, errors)
+}
+
+/*
+     @Description: Parses classified tokens as a program, recording the syntax tree events as it goes: a node is started
+                   before its first token and finished after its last, and a missing piece records nothing
+     @Params: input - the terminal, lexeme and span of each token, errors - the lexical errors found so far,
+              table - the operators for expressions
+     @Returns: The program, the events and every error found
+*/
+pub fn parse_classified(input: Vec<(String, String, Span)>, errors: Vec<SyntaxError>, table: &OperatorTable) -> (Program, Vec<Event>, Vec<SyntaxError>) {
+     let mut parser = ResilientParser { exprs: ExprParser::new(input, table), errors };
+     let program = parser.program();
+     (program, parser.exprs.events, parser.errors)
+}
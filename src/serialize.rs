@@ -0,0 +1,754 @@
+use crate::ast::{Decl, Expr, FnDef, Param, Program, Stmt, Type};
+use crate::report::json_string;
+use crate::tree::Span;
+
+/*
+     @Description: Name and version written at the top of every encoding; the version goes up whenever a node kind
+                   or field changes, and the decoder refuses versions it does not know
+     @Params: None
+     @Returns: None
+*/
+pub const FORMAT_NAME: &str = "ez-ast";
+pub const FORMAT_VERSION: usize = 1;
+
+/*
+     @Description: Width past which the writers spread a node or list over several lines
+     @Params: None
+     @Returns: None
+*/
+const LINE_WIDTH: usize = 100;
+
+/*
+     @Description: Enum for the data model both encodings share: a node is its kind and named fields in a fixed order,
+                   spans are [line, column] lists, literal values are kept as their source text, Bool is a flag such as a
+                   trailing comma, and Null is an absent option
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+     Null,
+     Bool(bool),
+     Int(usize),
+     Str(String),
+     List(Vec<Value>),
+     Node(String, Vec<(String, Value)>),
+}
+
+fn node(kind: &str, span: Span, fields: Vec<(&str, Value)>) -> Value {
+     let mut all = vec![(String::from("span"), Value::List(vec![Value::Int(span.line), Value::Int(span.column)]))];
+     all.extend(fields.into_iter().map(|(name, value)| (name.to_string(), value)));
+     Value::Node(kind.to_string(), all)
+}
+
+fn list<T>(items: &[T], encode: fn(&T) -> Value) -> Value {
+     Value::List(items.iter().map(encode).collect())
+}
+
+fn type_value(ty: Type) -> Value {
+     let name = match ty {
+          Type::Int => "int",
+          Type::Double => "double",
+          Type::Missing => "missing",
+     };
+     Value::Str(name.to_string())
+}
+
+fn tokens_value(tokens: &[(String, Span)]) -> Value {
+     Value::List(tokens.iter().map(|(lexeme, span)| node("Token", *span, vec![("lexeme", Value::Str(lexeme.clone()))])).collect())
+}
+
+/*
+     @Description: Converts an expression to the shared data model
+     @Params: expr - the expression
+     @Returns: The node
+*/
+pub fn expr_value(expr: &Expr) -> Value {
+     match expr {
+          Expr::Number { value, span } => node("Number", *span, vec![("value", Value::Str(value.clone()))]),
+          Expr::Ident { name, span } => node("Ident", *span, vec![("name", Value::Str(name.clone()))]),
+          Expr::Paren { inner, span } => node("Paren", *span, vec![("inner", expr_value(inner))]),
+          Expr::Unary { op, operand, span } => node("Unary", *span, vec![("op", Value::Str(op.clone())), ("operand", expr_value(operand))]),
+          Expr::Binary { op, left, right, span } => {
+               node("Binary", *span, vec![("op", Value::Str(op.clone())), ("left", expr_value(left)), ("right", expr_value(right))])
+          }
+          Expr::Index { target, index, span } => node("Index", *span, vec![("target", expr_value(target)), ("index", expr_value(index))]),
+          Expr::Call { callee, args, trailing_comma, span } => node(
+               "Call",
+               *span,
+               vec![("callee", expr_value(callee)), ("args", list(args, expr_value)), ("trailing_comma", Value::Bool(*trailing_comma))],
+          ),
+          Expr::Missing { span } => node("Missing", *span, vec![]),
+          Expr::Error { tokens, span } => node("Error", *span, vec![("tokens", tokens_value(tokens))]),
+     }
+}
+
+/*
+     @Description: Converts a statement to the shared data model
+     @Params: stmt - the statement
+     @Returns: The node
+*/
+pub fn stmt_value(stmt: &Stmt) -> Value {
+     match stmt {
+          Stmt::Assign { target, value, span } => node("Assign", *span, vec![("target", expr_value(target)), ("value", expr_value(value))]),
+          Stmt::If { cond, then_branch, else_branch, span } => {
+               let else_value = else_branch.as_ref().map_or(Value::Null, |stmts| list(stmts, stmt_value));
+               node("If", *span, vec![("cond", expr_value(cond)), ("then_branch", list(then_branch, stmt_value)), ("else_branch", else_value)])
+          }
+          Stmt::While { cond, body, span } => node("While", *span, vec![("cond", expr_value(cond)), ("body", list(body, stmt_value))]),
+          Stmt::Print { value, span } => node("Print", *span, vec![("value", expr_value(value))]),
+          Stmt::Return { value, span } => node("Return", *span, vec![("value", expr_value(value))]),
+          Stmt::Empty { span } => node("Empty", *span, vec![]),
+          Stmt::Error { tokens, span } => node("Error", *span, vec![("tokens", tokens_value(tokens))]),
+     }
+}
+
+fn decl_value(decl: &Decl) -> Value {
+     node("Decl", decl.span, vec![("type", type_value(decl.ty)), ("vars", list(&decl.vars, expr_value))])
+}
+
+fn param_value(param: &Param) -> Value {
+     node("Param", param.span, vec![("type", type_value(param.ty)), ("var", expr_value(&param.var))])
+}
+
+fn fn_def_value(function: &FnDef) -> Value {
+     node(
+          "FnDef",
+          function.span,
+          vec![
+               ("return_type", type_value(function.return_type)),
+               ("name", expr_value(&function.name)),
+               ("params", list(&function.params, param_value)),
+               ("trailing_comma", Value::Bool(function.trailing_comma)),
+               ("declarations", list(&function.declarations, decl_value)),
+               ("body", list(&function.body, stmt_value)),
+          ],
+     )
+}
+
+/*
+     @Description: Converts a program to the shared data model
+     @Params: program - the program
+     @Returns: The node
+*/
+pub fn program_value(program: &Program) -> Value {
+     node(
+          "Program",
+          program.span,
+          vec![
+               ("functions", list(&program.functions, fn_def_value)),
+               ("declarations", list(&program.declarations, decl_value)),
+               ("body", list(&program.body, stmt_value)),
+          ],
+     )
+}
+
+/*
+     @Description: Struct for a node being decoded, so a missing or mistyped field names the node it belongs to
+     @Params: None
+     @Returns: None
+*/
+struct Fields<'a> {
+     kind: &'a str,
+     fields: &'a [(String, Value)],
+}
+
+impl<'a> Fields<'a> {
+     fn new(value: &'a Value) -> Result<Fields<'a>, String> {
+          match value {
+               Value::Node(kind, fields) => Ok(Fields { kind, fields }),
+               _ => Err(String::from("Error: Expected a node")),
+          }
+     }
+
+     fn get(&self, name: &str) -> Result<&'a Value, String> {
+          match self.fields.iter().find(|(field, _)| field == name) {
+               Some((_, value)) => Ok(value),
+               None => Err(format!("Error: {} node has no {} field", self.kind, name)),
+          }
+     }
+
+     fn string(&self, name: &str) -> Result<String, String> {
+          match self.get(name)? {
+               Value::Str(text) => Ok(text.clone()),
+               _ => Err(format!("Error: {} field of {} node is not a string", name, self.kind)),
+          }
+     }
+
+     fn flag(&self, name: &str) -> Result<bool, String> {
+          match self.get(name)? {
+               Value::Bool(flag) => Ok(*flag),
+               _ => Err(format!("Error: {} field of {} node is not a boolean", name, self.kind)),
+          }
+     }
+
+     fn list(&self, name: &str) -> Result<&'a [Value], String> {
+          match self.get(name)? {
+               Value::List(items) => Ok(items),
+               _ => Err(format!("Error: {} field of {} node is not a list", name, self.kind)),
+          }
+     }
+
+     fn span(&self) -> Result<Span, String> {
+          match self.list("span")? {
+               [Value::Int(line), Value::Int(column)] => Ok(Span { line: *line, column: *column }),
+               _ => Err(format!("Error: span field of {} node is not [line, column]", self.kind)),
+          }
+     }
+
+     fn ty(&self, name: &str) -> Result<Type, String> {
+          match self.string(name)?.as_str() {
+               "int" => Ok(Type::Int),
+               "double" => Ok(Type::Double),
+               "missing" => Ok(Type::Missing),
+               other => Err(format!("Error: Unknown type {} in {} node", other, self.kind)),
+          }
+     }
+
+     fn expr(&self, name: &str) -> Result<Expr, String> {
+          value_expr(self.get(name)?)
+     }
+
+     fn boxed(&self, name: &str) -> Result<Box<Expr>, String> {
+          Ok(Box::new(self.expr(name)?))
+     }
+
+     fn stmts(&self, name: &str) -> Result<Vec<Stmt>, String> {
+          self.list(name)?.iter().map(value_stmt).collect()
+     }
+
+     fn tokens(&self) -> Result<Vec<(String, Span)>, String> {
+          let mut tokens = Vec::new();
+          for item in self.list("tokens")? {
+               let token = Fields::new(item)?;
+               tokens.push((token.string("lexeme")?, token.span()?));
+          }
+          Ok(tokens)
+     }
+}
+
+/*
+     @Description: Rebuilds an expression from the shared data model
+     @Params: value - the node
+     @Returns: The expression, or an error for an unknown kind or a bad field
+*/
+pub fn value_expr(value: &Value) -> Result<Expr, String> {
+     let fields = Fields::new(value)?;
+     let span = fields.span()?;
+     Ok(match fields.kind {
+          "Number" => Expr::Number { value: fields.string("value")?, span },
+          "Ident" => Expr::Ident { name: fields.string("name")?, span },
+          "Paren" => Expr::Paren { inner: fields.boxed("inner")?, span },
+          "Unary" => Expr::Unary { op: fields.string("op")?, operand: fields.boxed("operand")?, span },
+          "Binary" => Expr::Binary { op: fields.string("op")?, left: fields.boxed("left")?, right: fields.boxed("right")?, span },
+          "Index" => Expr::Index { target: fields.boxed("target")?, index: fields.boxed("index")?, span },
+          "Call" => {
+               let args = fields.list("args")?.iter().map(value_expr).collect::<Result<Vec<Expr>, String>>()?;
+               Expr::Call { callee: fields.boxed("callee")?, args, trailing_comma: fields.flag("trailing_comma")?, span }
+          }
+          "Missing" => Expr::Missing { span },
+          "Error" => Expr::Error { tokens: fields.tokens()?, span },
+          kind => return Err(format!("Error: Unknown expression kind {}", kind)),
+     })
+}
+
+/*
+     @Description: Rebuilds a statement from the shared data model
+     @Params: value - the node
+     @Returns: The statement, or an error for an unknown kind or a bad field
+*/
+pub fn value_stmt(value: &Value) -> Result<Stmt, String> {
+     let fields = Fields::new(value)?;
+     let span = fields.span()?;
+     Ok(match fields.kind {
+          "Assign" => Stmt::Assign { target: fields.expr("target")?, value: fields.expr("value")?, span },
+          "If" => {
+               let else_branch = match fields.get("else_branch")? {
+                    Value::Null => None,
+                    _ => Some(fields.stmts("else_branch")?),
+               };
+               Stmt::If { cond: fields.expr("cond")?, then_branch: fields.stmts("then_branch")?, else_branch, span }
+          }
+          "While" => Stmt::While { cond: fields.expr("cond")?, body: fields.stmts("body")?, span },
+          "Print" => Stmt::Print { value: fields.expr("value")?, span },
+          "Return" => Stmt::Return { value: fields.expr("value")?, span },
+          "Empty" => Stmt::Empty { span },
+          "Error" => Stmt::Error { tokens: fields.tokens()?, span },
+          kind => return Err(format!("Error: Unknown statement kind {}", kind)),
+     })
+}
+
+/*
+     @Description: Gets a node's fields, checking its kind
+     @Params: value - the node, kind - the expected kind
+     @Returns: The fields, or an error naming both kinds
+*/
+fn expect_kind<'a>(value: &'a Value, kind: &str) -> Result<Fields<'a>, String> {
+     let fields = Fields::new(value)?;
+     if fields.kind != kind {
+          return Err(format!("Error: Expected a {} node, found {}", kind, fields.kind));
+     }
+     Ok(fields)
+}
+
+fn value_decl(value: &Value) -> Result<Decl, String> {
+     let fields = expect_kind(value, "Decl")?;
+     let vars = fields.list("vars")?.iter().map(value_expr).collect::<Result<Vec<Expr>, String>>()?;
+     Ok(Decl { ty: fields.ty("type")?, vars, span: fields.span()? })
+}
+
+fn value_param(value: &Value) -> Result<Param, String> {
+     let fields = expect_kind(value, "Param")?;
+     Ok(Param { ty: fields.ty("type")?, var: fields.expr("var")?, span: fields.span()? })
+}
+
+fn value_fn_def(value: &Value) -> Result<FnDef, String> {
+     let fields = expect_kind(value, "FnDef")?;
+     Ok(FnDef {
+          return_type: fields.ty("return_type")?,
+          name: fields.expr("name")?,
+          params: fields.list("params")?.iter().map(value_param).collect::<Result<Vec<Param>, String>>()?,
+          trailing_comma: fields.flag("trailing_comma")?,
+          declarations: fields.list("declarations")?.iter().map(value_decl).collect::<Result<Vec<Decl>, String>>()?,
+          body: fields.stmts("body")?,
+          span: fields.span()?,
+     })
+}
+
+/*
+     @Description: Rebuilds a program from the shared data model
+     @Params: value - the node
+     @Returns: The program, or an error for a malformed node
+*/
+pub fn value_program(value: &Value) -> Result<Program, String> {
+     let fields = expect_kind(value, "Program")?;
+     Ok(Program {
+          functions: fields.list("functions")?.iter().map(value_fn_def).collect::<Result<Vec<FnDef>, String>>()?,
+          declarations: fields.list("declarations")?.iter().map(value_decl).collect::<Result<Vec<Decl>, String>>()?,
+          body: fields.stmts("body")?,
+          span: fields.span()?,
+     })
+}
+
+/*
+     @Description: Checks the format name and version of a decoded document
+     @Params: name - the format name read, version - the version read
+     @Returns: An error if either is not this format's
+*/
+fn check_version(name: &str, version: usize) -> Result<(), String> {
+     if name != FORMAT_NAME {
+          return Err(format!("Error: Not an {} document", FORMAT_NAME));
+     }
+     if version != FORMAT_VERSION {
+          return Err(format!("Error: Unsupported {} version {}, expected {}", FORMAT_NAME, version, FORMAT_VERSION));
+     }
+     Ok(())
+}
+
+/*
+     @Description: Writes a value as JSON on one line; a node is an object whose "kind" member comes first
+     @Params: value - the value
+     @Returns: The JSON text
+*/
+fn flat_json(value: &Value) -> String {
+     match value {
+          Value::Null => String::from("null"),
+          Value::Bool(flag) => flag.to_string(),
+          Value::Int(number) => number.to_string(),
+          Value::Str(text) => json_string(text),
+          Value::List(items) => format!("[{}]", items.iter().map(flat_json).collect::<Vec<String>>().join(", ")),
+          Value::Node(kind, fields) => {
+               let mut members = vec![format!("\"kind\": {}", json_string(kind))];
+               members.extend(fields.iter().map(|(name, value)| format!("{}: {}", json_string(name), flat_json(value))));
+               format!("{{{}}}", members.join(", "))
+          }
+     }
+}
+
+/*
+     @Description: Writes a value as JSON, spreading a list or node that does not fit in the line width over lines
+                   indented by two spaces per level
+     @Params: value - the value, depth - the indentation level
+     @Returns: The JSON text
+*/
+fn write_json(value: &Value, depth: usize) -> String {
+     let flat = flat_json(value);
+     if depth * 2 + flat.len() <= LINE_WIDTH {
+          return flat;
+     }
+     let indent = "  ".repeat(depth + 1);
+     match value {
+          Value::List(items) => {
+               let items: Vec<String> = items.iter().map(|item| format!("{}{}", indent, write_json(item, depth + 1))).collect();
+               format!("[\n{}\n{}]", items.join(",\n"), "  ".repeat(depth))
+          }
+          Value::Node(kind, fields) => {
+               let mut members = vec![format!("{}\"kind\": {}", indent, json_string(kind))];
+               members.extend(fields.iter().map(|(name, value)| format!("{}{}: {}", indent, json_string(name), write_json(value, depth + 1))));
+               format!("{{\n{}\n{}}}", members.join(",\n"), "  ".repeat(depth))
+          }
+          _ => flat,
+     }
+}
+
+/*
+     @Description: Encodes a program as a versioned JSON document:
+                   {"format": "ez-ast", "version": 1, "program": {"kind": "Program", "span": [1, 1], ...}}
+     @Params: program - the program
+     @Returns: The JSON text, ending in a newline
+*/
+pub fn to_json(program: &Program) -> String {
+     format!(
+          "{{\n  \"format\": {},\n  \"version\": {},\n  \"program\": {}\n}}\n",
+          json_string(FORMAT_NAME),
+          FORMAT_VERSION,
+          write_json(&program_value(program), 1)
+     )
+}
+
+/*
+     @Description: Writes a value as an S-expression on one line: a node is (Kind :field value ...), a list is (value ...),
+                   a flag is true or false and an absent option is nil
+     @Params: value - the value
+     @Returns: The S-expression text
+*/
+fn flat_sexp(value: &Value) -> String {
+     match value {
+          Value::Null => String::from("nil"),
+          Value::Bool(flag) => flag.to_string(),
+          Value::Int(number) => number.to_string(),
+          Value::Str(text) => json_string(text),
+          Value::List(items) => format!("({})", items.iter().map(flat_sexp).collect::<Vec<String>>().join(" ")),
+          Value::Node(kind, fields) => {
+               let mut parts = vec![kind.clone()];
+               parts.extend(fields.iter().map(|(name, value)| format!(":{} {}", name, flat_sexp(value))));
+               format!("({})", parts.join(" "))
+          }
+     }
+}
+
+/*
+     @Description: Writes a value as an S-expression, spreading a list or node that does not fit in the line width over lines
+     @Params: value - the value, depth - the indentation level
+     @Returns: The S-expression text
+*/
+fn write_sexp(value: &Value, depth: usize) -> String {
+     let flat = flat_sexp(value);
+     if depth * 2 + flat.len() <= LINE_WIDTH {
+          return flat;
+     }
+     let indent = "  ".repeat(depth + 1);
+     match value {
+          Value::List(items) => {
+               let items: Vec<String> = items.iter().map(|item| format!("{}{}", indent, write_sexp(item, depth + 1))).collect();
+               format!("(\n{})", items.join("\n"))
+          }
+          Value::Node(kind, fields) => {
+               let fields: Vec<String> = fields.iter().map(|(name, value)| format!("{}:{} {}", indent, name, write_sexp(value, depth + 1))).collect();
+               format!("({}\n{})", kind, fields.join("\n"))
+          }
+          _ => flat,
+     }
+}
+
+/*
+     @Description: Encodes a program as a versioned S-expression document: (ez-ast 1 (Program :span (1 1) ...))
+     @Params: program - the program
+     @Returns: The S-expression text, ending in a newline
+*/
+pub fn to_sexp(program: &Program) -> String {
+     format!("({} {}\n  {})\n", FORMAT_NAME, FORMAT_VERSION, write_sexp(&program_value(program), 1))
+}
+
+/*
+     @Description: Struct for the reader of either encoding, a cursor over the characters of the text
+     @Params: None
+     @Returns: None
+*/
+struct Reader {
+     chars: Vec<char>,
+     position: usize,
+}
+
+impl Reader {
+     fn new(text: &str) -> Reader {
+          Reader { chars: text.chars().collect(), position: 0 }
+     }
+
+     fn skip_whitespace(&mut self) {
+          while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+               self.position += 1;
+          }
+     }
+
+     fn peek(&mut self) -> Option<char> {
+          self.skip_whitespace();
+          self.chars.get(self.position).cloned()
+     }
+
+     fn error(&self, expected: &str) -> String {
+          match self.chars.get(self.position) {
+               Some(c) => format!("Error: Expected {} at character {}, found '{}'", expected, self.position, c),
+               None => format!("Error: Expected {} at character {}, found the end of the text", expected, self.position),
+          }
+     }
+
+     fn expect(&mut self, c: char) -> Result<(), String> {
+          if self.peek() != Some(c) {
+               return Err(self.error(&format!("'{}'", c)));
+          }
+          self.position += 1;
+          Ok(())
+     }
+
+     /*
+          @Description: Reads a run of characters that are not whitespace or punctuation, e.g. a number, keyword or symbol
+          @Params: None
+          @Returns: The word, empty if none
+     */
+     fn word(&mut self) -> String {
+          self.skip_whitespace();
+          let start = self.position;
+          while self.position < self.chars.len() && !self.chars[self.position].is_whitespace() && !"()[]{},:\"".contains(self.chars[self.position]) {
+               self.position += 1;
+          }
+          self.chars[start..self.position].iter().collect()
+     }
+
+     /*
+          @Description: Reads the word standing for an absent option or a flag, leaving the cursor in place if it is neither
+          @Params: null - "null" or "nil"
+          @Returns: Null or Bool, or an error
+     */
+     fn keyword(&mut self, null: &str) -> Result<Value, String> {
+          let start = self.position;
+          match self.word().as_str() {
+               word if word == null => Ok(Value::Null),
+               "true" => Ok(Value::Bool(true)),
+               "false" => Ok(Value::Bool(false)),
+               _ => {
+                    self.position = start;
+                    Err(self.error(null))
+               }
+          }
+     }
+
+     fn number(&mut self) -> Result<usize, String> {
+          let start = self.position;
+          let word = self.word();
+          word.parse().map_err(|_| {
+               self.position = start;
+               self.error("a number")
+          })
+     }
+
+     /*
+          @Description: Reads a double quoted string with JSON escapes
+          @Params: None
+          @Returns: The unescaped text
+     */
+     fn string(&mut self) -> Result<String, String> {
+          self.expect('"')?;
+          let mut text = String::new();
+          loop {
+               let c = match self.chars.get(self.position) {
+                    Some(&c) => c,
+                    None => return Err(self.error("'\"'")),
+               };
+               self.position += 1;
+               match c {
+                    '"' => return Ok(text),
+                    '\\' => {
+                         let escape = self.chars.get(self.position).cloned();
+                         self.position += 1;
+                         match escape {
+                              Some('n') => text.push('\n'),
+                              Some('t') => text.push('\t'),
+                              Some('r') => text.push('\r'),
+                              Some('b') => text.push('\u{8}'),
+                              Some('f') => text.push('\u{c}'),
+                              Some('u') => text.push(self.unicode_escape()?),
+                              Some(c @ ('"' | '\\' | '/')) => text.push(c),
+                              _ => return Err(self.error("an escape")),
+                         }
+                    }
+                    c => text.push(c),
+               }
+          }
+     }
+
+     /*
+          @Description: Reads the four hex digits after \u, and a second \u escape when they are a high surrogate, so
+                        characters outside the basic plane such as \uD83D\uDE00 decode to one character
+          @Params: None
+          @Returns: The character, or an error for bad digits or an unpaired surrogate
+     */
+     fn unicode_escape(&mut self) -> Result<char, String> {
+          let high = self.hex4()?;
+          if !(0xD800..0xDC00).contains(&high) {
+               return char::from_u32(high).ok_or_else(|| self.error("a \\u escape"));
+          }
+          if self.chars.get(self.position) != Some(&'\\') || self.chars.get(self.position + 1) != Some(&'u') {
+               return Err(self.error("a low surrogate"));
+          }
+          self.position += 2;
+          let low = self.hex4()?;
+          if !(0xDC00..0xE000).contains(&low) {
+               return Err(self.error("a low surrogate"));
+          }
+          char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or_else(|| self.error("a \\u escape"))
+     }
+
+     fn hex4(&mut self) -> Result<u32, String> {
+          let digits: String = self.chars.iter().skip(self.position).take(4).collect();
+          if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+               return Err(self.error("four hex digits"));
+          }
+          self.position += 4;
+          Ok(u32::from_str_radix(&digits, 16).unwrap())
+     }
+
+     /*
+          @Description: Reads a JSON value; an object must have a "kind" string member and becomes a node
+          @Params: None
+          @Returns: The value
+     */
+     fn json(&mut self) -> Result<Value, String> {
+          match self.peek() {
+               Some('"') => Ok(Value::Str(self.string()?)),
+               Some('[') => {
+                    self.position += 1;
+                    let mut items = Vec::new();
+                    if self.peek() != Some(']') {
+                         items.push(self.json()?);
+                         while self.peek() == Some(',') {
+                              self.position += 1;
+                              items.push(self.json()?);
+                         }
+                    }
+                    self.expect(']')?;
+                    Ok(Value::List(items))
+               }
+               Some('{') => {
+                    let members = self.json_object()?;
+                    let kind = match members.iter().position(|(name, _)| name == "kind") {
+                         Some(index) => members[index].1.clone(),
+                         None => return Err(String::from("Error: JSON object has no \"kind\" member")),
+                    };
+                    match kind {
+                         Value::Str(kind) => Ok(Value::Node(kind, members.into_iter().filter(|(name, _)| name != "kind").collect())),
+                         _ => Err(String::from("Error: JSON \"kind\" member is not a string")),
+                    }
+               }
+               Some(c) if c.is_ascii_digit() => Ok(Value::Int(self.number()?)),
+               _ => self.keyword("null").map_err(|_| self.error("a JSON value")),
+          }
+     }
+
+     fn json_object(&mut self) -> Result<Vec<(String, Value)>, String> {
+          self.expect('{')?;
+          let mut members = Vec::new();
+          if self.peek() != Some('}') {
+               loop {
+                    let name = self.string()?;
+                    self.expect(':')?;
+                    members.push((name, self.json()?));
+                    if self.peek() != Some(',') {
+                         break;
+                    }
+                    self.position += 1;
+               }
+          }
+          self.expect('}')?;
+          Ok(members)
+     }
+
+     /*
+          @Description: Reads an S-expression value; a parenthesised form starting with a symbol other than nil is a node
+          @Params: None
+          @Returns: The value
+     */
+     fn sexp(&mut self) -> Result<Value, String> {
+          match self.peek() {
+               Some('"') => Ok(Value::Str(self.string()?)),
+               Some('(') => {
+                    self.position += 1;
+                    let start = self.position;
+                    let head = self.word();
+                    if head.is_empty() || head == "nil" || head.chars().all(|c| c.is_ascii_digit()) {
+                         self.position = start;
+                         let mut items = Vec::new();
+                         while self.peek() != Some(')') {
+                              items.push(self.sexp()?);
+                         }
+                         self.expect(')')?;
+                         return Ok(Value::List(items));
+                    }
+                    let mut fields = Vec::new();
+                    while self.peek() == Some(':') {
+                         self.position += 1;
+                         let name = self.word();
+                         fields.push((name, self.sexp()?));
+                    }
+                    self.expect(')')?;
+                    Ok(Value::Node(head, fields))
+               }
+               Some(c) if c.is_ascii_digit() => Ok(Value::Int(self.number()?)),
+               _ => self.keyword("nil").map_err(|_| self.error("an S-expression")),
+          }
+     }
+
+     fn finish(&mut self) -> Result<(), String> {
+          match self.peek() {
+               None => Ok(()),
+               Some(_) => Err(self.error("the end of the text")),
+          }
+     }
+}
+
+/*
+     @Description: Decodes a JSON document written by to_json; its members may come in any order, and the format and
+                   version are checked before the program tree is decoded
+     @Params: text - the JSON text
+     @Returns: The program, or an error for malformed JSON, another format or version, or a malformed tree
+*/
+pub fn from_json(text: &str) -> Result<Program, String> {
+     let mut reader = Reader::new(text);
+     let members = reader.json_object()?;
+     reader.finish()?;
+     let member = |name: &str| members.iter().find(|(member, _)| member == name).map(|(_, value)| value);
+     match (member("format"), member("version")) {
+          (Some(Value::Str(name)), Some(Value::Int(version))) => check_version(name, *version)?,
+          _ => return Err(format!("Error: Not an {} document", FORMAT_NAME)),
+     }
+     match member("program") {
+          Some(program) => value_program(program),
+          None => Err(String::from("Error: JSON document has no \"program\" member")),
+     }
+}
+
+/*
+     @Description: Decodes an S-expression document written by to_sexp
+     @Params: text - the S-expression text
+     @Returns: The program, or an error for a malformed S-expression, another format or version, or a malformed tree
+*/
+pub fn from_sexp(text: &str) -> Result<Program, String> {
+     let mut reader = Reader::new(text);
+     reader.expect('(')?;
+     let name = reader.word();
+     let version = reader.number()?;
+     check_version(&name, version)?;
+     let program = reader.sexp()?;
+     reader.expect(')')?;
+     reader.finish()?;
+     value_program(&program)
+}
+
+/*
+     @Description: Decodes either encoding, telling them apart by the first character
+     @Params: text - the JSON or S-expression text
+     @Returns: The program, or the decoding error
+*/
+pub fn decode(text: &str) -> Result<Program, String> {
+     if text.trim_start().starts_with('{') { from_json(text) } else { from_sexp(text) }
+}
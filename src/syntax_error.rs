@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::tree::Span;
+
+/*
+     @Description: Struct for a lexical or syntax error; lexical errors have no expected terminals
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+     pub span: Span,
+     pub found: String,
+     pub expected: Vec<String>,
+     pub nonterminal: Option<String>,
+     pub repair: Option<String>,
+}
+
+impl SyntaxError {
+     /*
+          @Description: Creates the error for a token the lexer could not classify
+          @Params: lexeme - the token text, span - the token position
+          @Returns: The error
+     */
+     pub fn invalid_token(lexeme: &str, span: Span) -> SyntaxError {
+          SyntaxError { span, found: lexeme.to_string(), expected: Vec::new(), nonterminal: None, repair: None }
+     }
+
+     /*
+          @Description: Describes where the parser was, using "after x" for the helper nonterminals the grammar transformations introduce
+          @Params: None
+          @Returns: The context, e.g. "after bexpr" for bexprRight or "in statement"
+     */
+     pub fn context(&self) -> Option<String> {
+          let nonterminal = self.nonterminal.as_ref()?;
+          for suffix in ["Right", "Rest"] {
+               if let Some(base) = nonterminal.strip_suffix(suffix) {
+                    if !base.is_empty() {
+                         return Some(format!("after {}", base.trim_end_matches(char::is_numeric)));
+                    }
+               }
+          }
+          Some(format!("in {}", nonterminal))
+     }
+}
+
+/*
+     @Description: Formats a terminal for messages, naming the end of input
+     @Params: terminal - the terminal
+     @Returns: The quoted terminal
+*/
+fn quote(terminal: &str) -> String {
+     if terminal == "$" {
+          String::from("end of input")
+     } else {
+          format!("`{}`", terminal)
+     }
+}
+
+/*
+     @Description: String representation of the errors, e.g. "expected one of `then`, `and`, `or` after bexpr at 4:12, found `x`"
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for SyntaxError {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          if self.expected.is_empty() {
+               return write!(f, "invalid token {} at {}", quote(&self.found), self.span);
+          }
+
+          let expected: Vec<String> = self.expected.iter().map(|terminal| quote(terminal)).collect();
+          if expected.len() == 1 {
+               write!(f, "expected {}", expected[0])?;
+          } else {
+               write!(f, "expected one of {}", expected.join(", "))?;
+          }
+          if let Some(context) = self.context() {
+               write!(f, " {}", context)?;
+          }
+          write!(f, " at {}, found {}", self.span, quote(&self.found))?;
+          if let Some(repair) = &self.repair {
+               write!(f, "; {}", repair)?;
+          }
+          Ok(())
+     }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use comfy_table::{Cell, Color, Table};
+
+use crate::grammar::{first_sets, follow_sets, nonterminals, predict_sets, split_rule, terminals, EPSILON};
+use crate::report::{write_file, xml_escape};
+
+/*
+     @Description: Struct for an LL(1) parse table as a nonterminal x terminal matrix; a cell with more than one rule is a conflict
+     @Params: None
+     @Returns: None
+*/
+pub struct Ll1Table {
+     pub rules: Vec<String>,
+     pub nonterminals: Vec<String>,
+     pub terminals: Vec<String>,
+     pub cells: HashMap<(String, String), Vec<usize>>,
+}
+
+impl Ll1Table {
+     /*
+          @Description: Builds the table from the predict sets, keeping every rule that lands in a cell
+          @Params: grammar - the grammar rules
+          @Returns: The table, with rows in definition order and columns in order of first use followed by $
+     */
+     pub fn new(grammar: &[String]) -> Ll1Table {
+          let first = first_sets(grammar);
+          let follow = follow_sets(grammar, &first);
+          let predict = predict_sets(grammar, &first, &follow);
+
+          let mut cells: HashMap<(String, String), Vec<usize>> = HashMap::new();
+          for (index, (rule, set)) in grammar.iter().zip(&predict).enumerate() {
+               let (lhs, _) = split_rule(rule);
+               for terminal in set {
+                    cells.entry((lhs.clone(), terminal.clone())).or_default().push(index);
+               }
+          }
+          for indexes in cells.values_mut() {
+               indexes.sort();
+          }
+
+          let mut columns = terminals(grammar);
+          columns.push(String::from("$"));
+          Ll1Table { rules: grammar.to_vec(), nonterminals: nonterminals(grammar), terminals: columns, cells }
+     }
+
+     /*
+          @Description: Gets the rules in a cell
+          @Params: nonterminal - the row, terminal - the column
+          @Returns: The rule indexes, empty for an error entry
+     */
+     pub fn find_cell(&self, nonterminal: &str, terminal: &str) -> &[usize] {
+          match self.cells.get(&(nonterminal.to_string(), terminal.to_string())) {
+               Some(indexes) => indexes,
+               None => &[],
+          }
+     }
+
+     /*
+          @Description: Lists the cells holding more than one rule
+          @Params: None
+          @Returns: The (nonterminal, terminal) pairs in table order
+     */
+     pub fn conflicts(&self) -> Vec<(String, String)> {
+          let mut result = Vec::new();
+          for nonterminal in &self.nonterminals {
+               for terminal in &self.terminals {
+                    if self.find_cell(nonterminal, terminal).len() > 1 {
+                         result.push((nonterminal.clone(), terminal.clone()));
+                    }
+               }
+          }
+          result
+     }
+
+     /*
+          @Description: Formats the right hand side of a rule for a cell, e.g. "term exprRight" or "𝛜"
+          @Params: index - the rule index
+          @Returns: The cell text
+     */
+     fn production(&self, index: usize) -> String {
+          let (_, rhs) = split_rule(&self.rules[index]);
+          if rhs.is_empty() {
+               String::from(EPSILON)
+          } else {
+               rhs.join(" ")
+          }
+     }
+
+     /*
+          @Description: Formats every rule of a cell
+          @Params: nonterminal - the row, terminal - the column
+          @Returns: The productions, in rule order
+     */
+     fn cell_productions(&self, nonterminal: &str, terminal: &str) -> Vec<String> {
+          self.find_cell(nonterminal, terminal).iter().map(|&index| self.production(index)).collect()
+     }
+}
+
+/*
+     @Description: Quotes a CSV field when it holds a delimiter, quote or newline
+     @Params: text - the field
+     @Returns: The escaped field
+*/
+fn csv_field(text: &str) -> String {
+     if text.contains(',') || text.contains('"') || text.contains('\n') {
+          format!("\"{}\"", text.replace('"', "\"\""))
+     } else {
+          text.to_string()
+     }
+}
+
+/*
+     @Description: Escapes text for a Markdown table cell
+     @Params: text - the text
+     @Returns: The escaped text
+*/
+fn markdown_escape(text: &str) -> String {
+     let mut output = String::new();
+     for c in text.chars() {
+          if "|\\*_`<>[]".contains(c) {
+               output.push('\\');
+          }
+          output.push(c);
+     }
+     output
+}
+
+/*
+     @Description: Renders the table as CSV, joining conflicting productions with " / "
+     @Params: table - the table
+     @Returns: The CSV text
+*/
+pub fn to_csv(table: &Ll1Table) -> String {
+     let mut header = vec![String::from("nonterminal")];
+     header.extend(table.terminals.iter().map(|terminal| csv_field(terminal)));
+     let mut output = header.join(",") + "\n";
+     for nonterminal in &table.nonterminals {
+          let mut row = vec![csv_field(nonterminal)];
+          for terminal in &table.terminals {
+               row.push(csv_field(&table.cell_productions(nonterminal, terminal).join(" / ")));
+          }
+          output.push_str(&row.join(","));
+          output.push('\n');
+     }
+     output
+}
+
+/*
+     @Description: Renders the table as a Markdown table, marking conflicting cells in bold with a warning sign
+     @Params: table - the table
+     @Returns: The Markdown text
+*/
+pub fn to_markdown(table: &Ll1Table) -> String {
+     let mut output = String::from("| |");
+     for terminal in &table.terminals {
+          output.push_str(&format!(" `{}` |", terminal.replace('`', "\\`").replace('|', "\\|")));
+     }
+     output.push_str("\n|---|");
+     output.push_str(&"---|".repeat(table.terminals.len()));
+     output.push('\n');
+     for nonterminal in &table.nonterminals {
+          output.push_str(&format!("| **{}** |", markdown_escape(nonterminal)));
+          for terminal in &table.terminals {
+               let productions: Vec<String> =
+                    table.cell_productions(nonterminal, terminal).iter().map(|p| markdown_escape(p)).collect();
+               if productions.len() > 1 {
+                    output.push_str(&format!(" ⚠ **{}** |", productions.join("<br>")));
+               } else {
+                    output.push_str(&format!(" {} |", productions.join("")));
+               }
+          }
+          output.push('\n');
+     }
+
+     let conflicts = table.conflicts();
+     if !conflicts.is_empty() {
+          output.push_str(&format!("\n{} conflicting cell(s):\n\n", conflicts.len()));
+          for (nonterminal, terminal) in conflicts {
+               output.push_str(&format!("- {} on `{}`\n", markdown_escape(&nonterminal), terminal));
+          }
+     }
+     output
+}
+
+/*
+     @Description: Renders the table as a standalone HTML page, highlighting conflicting cells
+     @Params: table - the table, title - the page title
+     @Returns: The HTML text
+*/
+pub fn to_html(table: &Ll1Table, title: &str) -> String {
+     let mut output = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+     output.push_str(&format!("<title>{}</title>\n", xml_escape(title)));
+     output.push_str(
+          "<style>\n\
+           body { font-family: sans-serif; }\n\
+           table { border-collapse: collapse; }\n\
+           th, td { border: 1px solid #999; padding: 4px 8px; font-family: monospace; white-space: nowrap; }\n\
+           thead th { position: sticky; top: 0; background: #eee; }\n\
+           tbody th { text-align: left; background: #f6f6f6; }\n\
+           td.conflict { background: #f8c8c8; font-weight: bold; }\n\
+           </style>\n</head>\n<body>\n",
+     );
+     output.push_str(&format!("<h1>{}</h1>\n", xml_escape(title)));
+
+     let conflicts = table.conflicts();
+     if conflicts.is_empty() {
+          output.push_str("<p>The grammar is LL(1): no cell holds more than one rule.</p>\n");
+     } else {
+          output.push_str(&format!("<p>{} conflicting cell(s) are highlighted.</p>\n", conflicts.len()));
+     }
+
+     output.push_str("<table>\n<thead>\n<tr><th></th>");
+     for terminal in &table.terminals {
+          output.push_str(&format!("<th>{}</th>", xml_escape(terminal)));
+     }
+     output.push_str("</tr>\n</thead>\n<tbody>\n");
+     for nonterminal in &table.nonterminals {
+          output.push_str(&format!("<tr><th>{}</th>", xml_escape(nonterminal)));
+          for terminal in &table.terminals {
+               let productions: Vec<String> =
+                    table.cell_productions(nonterminal, terminal).iter().map(|p| xml_escape(p)).collect();
+               if productions.len() > 1 {
+                    output.push_str(&format!(
+                         "<td class=\"conflict\" title=\"conflict on {}\">{}</td>",
+                         xml_escape(terminal),
+                         productions.join("<br>")
+                    ));
+               } else {
+                    output.push_str(&format!("<td>{}</td>", productions.join("")));
+               }
+          }
+          output.push_str("</tr>\n");
+     }
+     output.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+     output
+}
+
+/*
+     @Description: Renders the table for the terminal, conflicting cells in red
+     @Params: table - the table
+     @Returns: The comfy-table table
+*/
+pub fn to_comfy_table(table: &Ll1Table) -> Table {
+     let mut output = Table::new();
+     let mut header = vec![Cell::new("")];
+     header.extend(table.terminals.iter().map(Cell::new));
+     output.set_header(header);
+     for nonterminal in &table.nonterminals {
+          let mut row = vec![Cell::new(nonterminal)];
+          for terminal in &table.terminals {
+               let productions = table.cell_productions(nonterminal, terminal);
+               if productions.len() > 1 {
+                    row.push(Cell::new(productions.join("\n")).fg(Color::Red));
+               } else {
+                    row.push(Cell::new(productions.join("")));
+               }
+          }
+          output.add_row(row);
+     }
+     output
+}
+
+/*
+     @Description: Writes the table as parse-table.csv, parse-table.md and parse-table.html
+     @Params: table - the table, directory - the output directory
+     @Returns: An error message if a file could not be written
+*/
+pub fn write_table_reports(table: &Ll1Table, directory: &str) -> Result<(), String> {
+     let directory = directory.trim_end_matches('/');
+     write_file(&format!("{}/parse-table.csv", directory), &to_csv(table))?;
+     write_file(&format!("{}/parse-table.md", directory), &to_markdown(table))?;
+     write_file(&format!("{}/parse-table.html", directory), &to_html(table, "LL(1) Parse Table"))
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::lexer::{Token, TokenTypes};
+use crate::syntax_error::SyntaxError;
+use crate::tree::Span;
+
+/*
+     @Description: Gets the name of a token type variant without its payload
+     @Params: token_type - the token type
+     @Returns: The variant name, e.g. "Ident" for Ident(x)
+*/
+pub fn variant_name(token_type: &TokenTypes) -> &'static str {
+     match *token_type {
+          TokenTypes::Def => "Def",
+          TokenTypes::Type(_) => "Type",
+          TokenTypes::Ident(_) => "Ident",
+          TokenTypes::LParen => "LParen",
+          TokenTypes::RParen => "RParen",
+          TokenTypes::Comma => "Comma",
+          TokenTypes::Semicolon => "Semicolon",
+          TokenTypes::Assign => "Assign",
+          TokenTypes::Plus => "Plus",
+          TokenTypes::PlusAssign => "PlusAssign",
+          TokenTypes::Minus => "Minus",
+          TokenTypes::MinusEqual => "MinusEqual",
+          TokenTypes::Asterisk => "Asterisk",
+          TokenTypes::AsteriskEqual => "AsteriskEqual",
+          TokenTypes::Divide => "Divide",
+          TokenTypes::DivideEqual => "DivideEqual",
+          TokenTypes::Modulo => "Modulo",
+          TokenTypes::ModuloEqual => "ModuloEqual",
+          TokenTypes::If => "If",
+          TokenTypes::Then => "Then",
+          TokenTypes::Else => "Else",
+          TokenTypes::Fi => "Fi",
+          TokenTypes::While => "While",
+          TokenTypes::Do => "Do",
+          TokenTypes::Od => "Od",
+          TokenTypes::Print => "Print",
+          TokenTypes::Return => "Return",
+          TokenTypes::Eof => "Eof",
+          TokenTypes::Period => "Period",
+          TokenTypes::IntegerLiteral(_) => "IntegerLiteral",
+          TokenTypes::DoubleLiteral(_) => "DoubleLiteral",
+          TokenTypes::Or => "Or",
+          TokenTypes::And => "And",
+          TokenTypes::Not => "Not",
+          TokenTypes::Less => "Less",
+          TokenTypes::Greater => "Greater",
+          TokenTypes::Equal => "Equal",
+          TokenTypes::LessEqual => "LessEqual",
+          TokenTypes::GreaterEqual => "GreaterEqual",
+          TokenTypes::NotEqual => "NotEqual",
+          TokenTypes::LBracket => "LBracket",
+          TokenTypes::RBracket => "RBracket",
+          TokenTypes::Error => "Error",
+     }
+}
+
+/*
+     @Description: Names of every TokenTypes variant, in declaration order, as variant_name spells them
+     @Params: None
+     @Returns: None
+*/
+pub const VARIANT_NAMES: [&str; 43] = [
+     "Def", "Type", "Ident", "LParen", "RParen", "Comma", "Semicolon", "Assign", "Plus", "PlusAssign",
+     "Minus", "MinusEqual", "Asterisk", "AsteriskEqual", "Divide", "DivideEqual", "Modulo", "ModuloEqual",
+     "If", "Then", "Else", "Fi", "While", "Do", "Od", "Print", "Return", "Eof", "Period", "IntegerLiteral",
+     "DoubleLiteral", "Or", "And", "Not", "Less", "Greater", "Equal", "LessEqual", "GreaterEqual", "NotEqual",
+     "LBracket", "RBracket", "Error",
+];
+
+/*
+     @Description: Struct mapping lexer tokens to grammar terminal names; a lexeme entry wins over a variant entry, and unmapped tokens use their lexeme
+     @Params: None
+     @Returns: None
+*/
+pub struct TerminalMap {
+     pub variants: HashMap<String, String>,
+     pub lexemes: HashMap<String, String>,
+}
+
+impl Default for TerminalMap {
+     /*
+          @Description: Creates the mapping used by the EZ grammar files, with ID and NUMBER for identifiers and literals
+          @Params: None
+          @Returns: The default mapping
+     */
+     fn default() -> TerminalMap {
+          let mut terminals = TerminalMap { variants: HashMap::new(), lexemes: HashMap::new() };
+          for (variant, terminal) in [
+               ("Def", "def"),
+               ("Ident", "ID"),
+               ("LParen", "("),
+               ("RParen", ")"),
+               ("Comma", ","),
+               ("Semicolon", ";"),
+               ("Assign", "="),
+               ("Plus", "+"),
+               ("Minus", "-"),
+               ("Asterisk", "*"),
+               ("Divide", "/"),
+               ("Modulo", "%"),
+               ("If", "if"),
+               ("Then", "then"),
+               ("Else", "else"),
+               ("Fi", "fi"),
+               ("While", "while"),
+               ("Do", "do"),
+               ("Od", "od"),
+               ("Print", "print"),
+               ("Return", "return"),
+               ("Eof", "."),
+               ("Period", "."),
+               ("IntegerLiteral", "NUMBER"),
+               ("DoubleLiteral", "NUMBER"),
+               ("Or", "or"),
+               ("And", "and"),
+               ("Not", "not"),
+               ("Less", "<"),
+               ("Greater", ">"),
+               ("Equal", "=="),
+               ("LessEqual", "<="),
+               ("GreaterEqual", ">="),
+               ("NotEqual", "<>"),
+               ("LBracket", "["),
+               ("RBracket", "]"),
+          ] {
+               terminals.add_variant(variant, terminal);
+          }
+          terminals.add_lexeme("fed", "fed");
+          terminals
+     }
+}
+
+impl TerminalMap {
+     /*
+          @Description: Maps every token of a TokenTypes variant to a terminal
+          @Params: variant - the variant name, e.g. "Ident", terminal - the grammar terminal
+          @Returns: None
+     */
+     pub fn add_variant(&mut self, variant: &str, terminal: &str) {
+          self.variants.insert(variant.to_string(), terminal.to_string());
+     }
+
+     /*
+          @Description: Maps every token with a given lexeme to a terminal, e.g. keywords the lexer reads as identifiers
+          @Params: lexeme - the token text, terminal - the grammar terminal
+          @Returns: None
+     */
+     pub fn add_lexeme(&mut self, lexeme: &str, terminal: &str) {
+          self.lexemes.insert(lexeme.to_string(), terminal.to_string());
+     }
+
+     /*
+          @Description: Loads a mapping file on top of the defaults, with lines like `Ident = ID` or `"fed" = fed`;
+                        an unquoted key must be a TokenTypes variant name
+          @Params: path - the path to the mapping file
+          @Returns: The mapping or an error message
+     */
+     pub fn load(path: &str) -> Result<TerminalMap, String> {
+          let mut source = String::new();
+          let mut file = File::open(path).map_err(|e| format!("Error: Unable to open {}: {}", path, e))?;
+          file.read_to_string(&mut source).map_err(|e| format!("Error: Unable to read {}: {}", path, e))?;
+
+          let mut terminals = TerminalMap::default();
+          for (index, line) in source.lines().enumerate() {
+               let trimmed = line.trim();
+               if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+               }
+               let mut iter = trimmed.splitn(2, " = ");
+               let key = iter.next().unwrap().trim();
+               let terminal = match iter.next() {
+                    Some(terminal) => terminal.trim(),
+                    None => return Err(format!("Error: Line {} of {} is not `key = terminal`: {}", index + 1, path, trimmed)),
+               };
+               if key.len() > 1 && key.starts_with('"') && key.ends_with('"') {
+                    terminals.add_lexeme(&key[1..key.len() - 1], terminal);
+               } else if VARIANT_NAMES.contains(&key) {
+                    terminals.add_variant(key, terminal);
+               } else {
+                    return Err(format!("Error: Line {} of {} names {}, which is not a token type; quote it to map a lexeme", index + 1, path, key));
+               }
+          }
+          Ok(terminals)
+     }
+
+     /*
+          @Description: Classifies a token as a grammar terminal
+          @Params: token - the token
+          @Returns: The terminal name or an error message for lexical errors
+     */
+     pub fn find_terminal(&self, token: &Token) -> Result<String, String> {
+          if token.token_type == TokenTypes::Error {
+               return Err(format!(
+                    "Error: Invalid token {} at {}:{}",
+                    token.lexeme, token.line_number, token.column_number
+               ));
+          }
+          if let Some(terminal) = self.lexemes.get(&token.lexeme) {
+               return Ok(terminal.to_string());
+          }
+          match self.variants.get(variant_name(&token.token_type)) {
+               Some(terminal) => Ok(terminal.to_string()),
+               None => Ok(token.lexeme.to_string()),
+          }
+     }
+
+     /*
+          @Description: Classifies every token
+          @Params: tokens - the tokens
+          @Returns: The terminal names or the first lexical error
+     */
+     pub fn classify(&self, tokens: &[Token]) -> Result<Vec<String>, String> {
+          tokens.iter().map(|token| self.find_terminal(token)).collect()
+     }
+
+     /*
+          @Description: Classifies every token, keeping its lexeme and position
+          @Params: tokens - the tokens
+          @Returns: The terminal, lexeme and span of each valid token, and an error for each invalid one
+     */
+     pub fn classify_spanned(&self, tokens: &[Token]) -> (Vec<(String, String, Span)>, Vec<SyntaxError>) {
+          let mut symbols = Vec::new();
+          let mut errors = Vec::new();
+          for token in tokens {
+               let span = Span { line: token.line_number, column: token.column_number };
+               match self.find_terminal(token) {
+                    Ok(terminal) => symbols.push((terminal, token.lexeme.to_string(), span)),
+                    Err(_) => errors.push(SyntaxError::invalid_token(&token.lexeme, span)),
+               }
+          }
+          (symbols, errors)
+     }
+}
@@ -0,0 +1,516 @@
+use crate::grammar::{make_rule, nonterminals, split_rule};
+use crate::table::Ll1Table;
+
+/*
+     @Description: Struct for a grammar derived from another one, remembering where each rule came from
+     @Params: None
+     @Returns: None
+*/
+pub struct TransformedGrammar {
+     pub rules: Vec<String>,
+     pub origins: Vec<Vec<usize>>,
+}
+
+/*
+     @Description: A production during transformation: left hand side, right hand side and original rule indexes
+     @Params: None
+     @Returns: None
+*/
+type Production = (String, Vec<String>, Vec<usize>);
+
+impl TransformedGrammar {
+     /*
+          @Description: Wraps a grammar with an identity mapping
+          @Params: grammar - the grammar rules
+          @Returns: The grammar where every rule maps back to itself
+     */
+     pub fn identity(grammar: &[String]) -> TransformedGrammar {
+          TransformedGrammar {
+               rules: grammar.to_vec(),
+               origins: (0..grammar.len()).map(|index| vec![index]).collect(),
+          }
+     }
+
+     /*
+          @Description: Finds the original rules a derived rule came from
+          @Params: index - the derived rule index, original - the original grammar rules
+          @Returns: The original rules
+     */
+     pub fn original_rules<'a>(&self, index: usize, original: &'a [String]) -> Vec<&'a String> {
+          self.origins[index].iter().map(|&origin| &original[origin]).collect()
+     }
+
+     /*
+          @Description: Splits the rules into productions carrying their origins
+          @Params: None
+          @Returns: The productions, in rule order
+     */
+     fn productions(&self) -> Vec<Production> {
+          self.rules
+               .iter()
+               .zip(self.origins.iter())
+               .map(|(rule, origins)| {
+                    let (lhs, rhs) = split_rule(rule);
+                    (lhs, rhs, origins.clone())
+               })
+               .collect()
+     }
+
+     /*
+          @Description: Joins productions back into rules; a production that repeats an earlier rule adds its origins to it
+          @Params: productions - the productions
+          @Returns: The grammar, in production order
+     */
+     fn from_productions(productions: Vec<Production>) -> TransformedGrammar {
+          let mut rules: Vec<String> = Vec::new();
+          let mut origins: Vec<Vec<usize>> = Vec::new();
+          for (lhs, rhs, origin) in productions {
+               let rule = make_rule(&lhs, &rhs);
+               match rules.iter().position(|r| *r == rule) {
+                    Some(index) => merge_origins(&mut origins[index], &origin),
+                    None => {
+                         rules.push(rule);
+                         origins.push(origin);
+                    }
+               }
+          }
+          TransformedGrammar { rules, origins }
+     }
+}
+
+/*
+     @Description: Adds origins to a list, keeping it sorted and free of duplicates
+     @Params: target - the origins to extend, extra - the origins to add
+     @Returns: None
+*/
+fn merge_origins(target: &mut Vec<usize>, extra: &[usize]) {
+     for origin in extra {
+          if !target.contains(origin) {
+               target.push(*origin);
+          }
+     }
+     target.sort();
+}
+
+/*
+     @Description: Picks a nonterminal name that is not in use yet
+     @Params: base - the preferred name, taken - the names already in use
+     @Returns: The base name, or the base name with the smallest free numeric suffix
+*/
+pub fn fresh_name(base: &str, taken: &[String]) -> String {
+     if !taken.iter().any(|name| name == base) {
+          return base.to_string();
+     }
+     let mut suffix = 2;
+     while taken.iter().any(|name| *name == format!("{}{}", base, suffix)) {
+          suffix += 1;
+     }
+     format!("{}{}", base, suffix)
+}
+
+/*
+     @Description: Replaces every production of a nonterminal, keeping the replacement where the first production was
+     @Params: productions - the productions, lhs - the nonterminal, replacement - the new productions
+     @Returns: The updated productions
+*/
+fn replace_productions(productions: Vec<Production>, lhs: &str, replacement: Vec<Production>) -> Vec<Production> {
+     let mut result = Vec::new();
+     let mut replacement = Some(replacement);
+     for production in productions {
+          if production.0 == lhs {
+               if let Some(new_productions) = replacement.take() {
+                    result.extend(new_productions);
+               }
+          } else {
+               result.push(production);
+          }
+     }
+     result
+}
+
+/*
+     @Description: Finds the nonterminals that derive the empty string
+     @Params: productions - the productions
+     @Returns: The nullable nonterminals
+*/
+fn nullable_nonterminals(productions: &[Production]) -> Vec<String> {
+     let mut result: Vec<String> = Vec::new();
+     let mut changed = true;
+     while changed {
+          changed = false;
+          for (lhs, rhs, _) in productions {
+               if !result.contains(lhs) && rhs.iter().all(|symbol| result.contains(symbol)) {
+                    result.push(lhs.clone());
+                    changed = true;
+               }
+          }
+     }
+     result
+}
+
+/*
+     @Description: Finds the nonterminals a sequence of symbols can start with, looking past a leading nonterminal
+                   only when it is nullable
+     @Params: productions - the productions, symbols - the sequence, nullable - the nullable nonterminals
+     @Returns: The leading nonterminals
+*/
+fn leading_nonterminals(productions: &[Production], symbols: &[String], nullable: &[String]) -> Vec<String> {
+     let mut result = Vec::new();
+     for symbol in symbols {
+          if !productions.iter().any(|p| p.0 == *symbol) {
+               break;
+          }
+          result.push(symbol.clone());
+          if !nullable.contains(symbol) {
+               break;
+          }
+     }
+     result
+}
+
+/*
+     @Description: Finds every nonterminal that can start a sentential form derived from a nonterminal; with nullable
+                   nonterminals given, a left corner hidden behind them counts too
+     @Params: productions - the productions, start - the nonterminal, nullable - the nullable nonterminals to look past
+     @Returns: The left corner nonterminals reachable from start
+*/
+fn left_corners(productions: &[Production], start: &str, nullable: &[String]) -> Vec<String> {
+     let mut result: Vec<String> = Vec::new();
+     let mut pending = vec![start.to_string()];
+     while let Some(lhs) = pending.pop() {
+          for production in productions.iter().filter(|p| p.0 == lhs) {
+               for corner in leading_nonterminals(productions, &production.1, nullable) {
+                    if !result.contains(&corner) {
+                         result.push(corner.clone());
+                         pending.push(corner);
+                    }
+               }
+          }
+     }
+     result
+}
+
+/*
+     @Description: Replaces the first symbol of a production with each right hand side of that nonterminal
+     @Params: production - the production, productions - the productions to take the right hand sides from
+     @Returns: One production per right hand side of the first symbol
+*/
+fn inline_first(production: &Production, productions: &[Production]) -> Vec<Production> {
+     let first = &production.1[0];
+     productions
+          .iter()
+          .filter(|p| p.0 == *first)
+          .map(|(_, rhs, origins)| {
+               let mut expanded = rhs.clone();
+               expanded.extend(production.1[1..].iter().cloned());
+               let mut merged = production.2.clone();
+               merge_origins(&mut merged, origins);
+               (production.0.clone(), expanded, merged)
+          })
+          .collect()
+}
+
+/*
+     @Description: Makes hidden left recursion direct, e.g. A ::= B A x with B nullable becomes A ::= A x plus one
+                   production per non-empty alternative of B, by inlining a nullable first symbol wherever the
+                   nonterminal is a left corner of what follows it. Each round inlines one level, and the rounds are
+                   bounded by the number of nonterminals so a grammar whose nullable symbols recurse still stops
+     @Params: grammar - the grammar to transform
+     @Returns: The transformed grammar with its mapping back to the input rules
+*/
+pub fn expose_hidden_left_recursion(grammar: &TransformedGrammar) -> TransformedGrammar {
+     let mut productions = grammar.productions();
+     for _ in 0..nonterminals(&grammar.rules).len() {
+          let nullable = nullable_nonterminals(&productions);
+          let hidden = |production: &Production| match production.1.split_first() {
+               Some((first, rest)) if *first != production.0 && nullable.contains(first) => {
+                    let mut corners = leading_nonterminals(&productions, rest, &nullable);
+                    for corner in corners.clone() {
+                         corners.extend(left_corners(&productions, &corner, &nullable));
+                    }
+                    corners.contains(&production.0)
+               }
+               _ => false,
+          };
+          if !productions.iter().any(hidden) {
+               break;
+          }
+          let mut next = Vec::new();
+          for production in &productions {
+               if hidden(production) {
+                    next.extend(inline_first(production, &productions));
+               } else {
+                    next.push(production.clone());
+               }
+          }
+          productions = next;
+     }
+     TransformedGrammar::from_productions(productions)
+}
+
+/*
+     @Description: Removes direct and indirect left recursion, substituting only along left corner cycles and introducing a "<name>Right" tail nonterminal for each recursive nonterminal
+     @Params: grammar - the grammar to transform
+     @Returns: The transformed grammar with its mapping back to the input rules
+*/
+pub fn eliminate_left_recursion(grammar: &TransformedGrammar) -> TransformedGrammar {
+     let order = nonterminals(&grammar.rules);
+     let mut names = order.clone();
+     let mut productions = grammar.productions();
+
+     for i in 0..order.len() {
+          let current = &order[i];
+
+          for earlier in order.iter().take(i) {
+               if !left_corners(&productions, earlier, &[]).contains(current) {
+                    continue;
+               }
+               let substitutes: Vec<Production> = productions.iter().filter(|p| p.0 == *earlier).cloned().collect();
+               let mut next = Vec::new();
+               for production in productions {
+                    if production.0 == *current && production.1.first() == Some(earlier) {
+                         for substitute in &substitutes {
+                              let mut rhs = substitute.1.clone();
+                              rhs.extend(production.1[1..].iter().cloned());
+                              let mut origins = production.2.clone();
+                              merge_origins(&mut origins, &substitute.2);
+                              next.push((current.clone(), rhs, origins));
+                         }
+                    } else {
+                         next.push(production);
+                    }
+               }
+               productions = next;
+          }
+
+          let own: Vec<Production> = productions.iter().filter(|p| p.0 == *current).cloned().collect();
+          let (recursive, others): (Vec<Production>, Vec<Production>) =
+               own.into_iter().partition(|p| p.1.first() == Some(current));
+          if recursive.is_empty() {
+               continue;
+          }
+
+          let tail = fresh_name(&format!("{}Right", current), &names);
+          names.push(tail.clone());
+
+          let mut replacement = Vec::new();
+          for (lhs, mut rhs, origins) in others {
+               rhs.push(tail.clone());
+               replacement.push((lhs, rhs, origins));
+          }
+          let mut tail_origins = Vec::new();
+          for (_, rhs, origins) in &recursive {
+               if rhs.len() > 1 {
+                    let mut tail_rhs = rhs[1..].to_vec();
+                    tail_rhs.push(tail.clone());
+                    replacement.push((tail.clone(), tail_rhs, origins.clone()));
+               }
+               merge_origins(&mut tail_origins, origins);
+          }
+          replacement.push((tail, Vec::new(), tail_origins));
+
+          productions = replace_productions(productions, current, replacement);
+     }
+
+     TransformedGrammar::from_productions(productions)
+}
+
+/*
+     @Description: Left factors alternatives sharing a common prefix, introducing a "<name>Rest" nonterminal for the differing suffixes
+     @Params: grammar - the grammar to transform
+     @Returns: The transformed grammar with its mapping back to the input rules
+*/
+pub fn left_factor(grammar: &TransformedGrammar) -> TransformedGrammar {
+     let mut names = nonterminals(&grammar.rules);
+     let mut productions = grammar.productions();
+
+     loop {
+          let mut factored = None;
+
+          'search: for lhs in nonterminals(&productions.iter().map(|p| make_rule(&p.0, &p.1)).collect::<Vec<String>>()) {
+               let own: Vec<&Production> = productions.iter().filter(|p| p.0 == lhs).collect();
+               for (index, production) in own.iter().enumerate() {
+                    let first = match production.1.first() {
+                         Some(first) => first,
+                         None => continue,
+                    };
+                    let group: Vec<&Production> =
+                         own[index..].iter().filter(|p| p.1.first() == Some(first)).cloned().collect();
+                    if group.len() < 2 {
+                         continue;
+                    }
+
+                    let mut prefix_len = 1;
+                    while group.iter().all(|p| p.1.len() > prefix_len && p.1[prefix_len] == group[0].1[prefix_len]) {
+                         prefix_len += 1;
+                    }
+
+                    let rest = fresh_name(&format!("{}Rest", lhs), &names);
+                    let mut prefix = group[0].1[..prefix_len].to_vec();
+                    prefix.push(rest.clone());
+
+                    let mut prefix_origins = Vec::new();
+                    let mut suffixes = Vec::new();
+                    for p in &group {
+                         merge_origins(&mut prefix_origins, &p.2);
+                         suffixes.push((rest.clone(), p.1[prefix_len..].to_vec(), p.2.clone()));
+                    }
+
+                    let mut replacement = Vec::new();
+                    let mut placed = false;
+                    for p in &own {
+                         if group.iter().any(|g| std::ptr::eq(*g, *p)) {
+                              if !placed {
+                                   replacement.push((lhs.clone(), prefix.clone(), prefix_origins.clone()));
+                                   placed = true;
+                              }
+                         } else {
+                              replacement.push((*p).clone());
+                         }
+                    }
+                    replacement.extend(suffixes);
+
+                    factored = Some((lhs.clone(), rest, replacement));
+                    break 'search;
+               }
+          }
+
+          match factored {
+               Some((lhs, rest, replacement)) => {
+                    names.push(rest);
+                    productions = replace_productions(productions, &lhs, replacement);
+               }
+               None => break,
+          }
+     }
+
+     TransformedGrammar::from_productions(productions)
+}
+
+/*
+     @Description: Removes the nonterminals the start symbol no longer reaches, e.g. one whose only use was inlined
+     @Params: grammar - the grammar
+     @Returns: The grammar without unreachable rules
+*/
+fn prune_unreachable(grammar: &TransformedGrammar) -> TransformedGrammar {
+     let productions = grammar.productions();
+     let mut reachable: Vec<String> = productions.first().map(|p| p.0.clone()).into_iter().collect();
+     let mut index = 0;
+     while index < reachable.len() {
+          let lhs = reachable[index].clone();
+          for (_, rhs, _) in productions.iter().filter(|p| p.0 == lhs) {
+               for symbol in rhs {
+                    if productions.iter().any(|p| p.0 == *symbol) && !reachable.contains(symbol) {
+                         reachable.push(symbol.clone());
+                    }
+               }
+          }
+          index += 1;
+     }
+     TransformedGrammar::from_productions(productions.into_iter().filter(|p| reachable.contains(&p.0)).collect())
+}
+
+/*
+     @Description: Counts the rules in conflicting cells beyond the first, so a step that removes one of three rules
+                   from a cell counts as progress
+     @Params: grammar - the grammar rules
+     @Returns: The number of surplus rules
+*/
+fn conflict_count(grammar: &[String]) -> usize {
+     let table = Ll1Table::new(grammar);
+     table.conflicts().iter().map(|(nonterminal, terminal)| table.find_cell(nonterminal, terminal).len() - 1).sum()
+}
+
+/*
+     @Description: Bound on how many levels of left corners one conflicting cell is expanded before giving up on it
+     @Params: None
+     @Returns: None
+*/
+const MAX_EXPANSION_DEPTH: usize = 3;
+
+/*
+     @Description: Inlines the leading nonterminal of every production in a table cell other than the row's own
+     @Params: productions - the productions, nonterminal - the row, terminal - the column
+     @Returns: The productions with the cell expanded, or None if no production in the cell starts with another nonterminal
+*/
+fn expand_cell(productions: &[Production], nonterminal: &str, terminal: &str) -> Option<Vec<Production>> {
+     let rules: Vec<String> = productions.iter().map(|p| make_rule(&p.0, &p.1)).collect();
+     let table = Ll1Table::new(&rules);
+     let cell = table.find_cell(nonterminal, terminal);
+     let expandable =
+          |index: usize| productions[index].1.first().is_some_and(|first| first != nonterminal && productions.iter().any(|p| p.0 == *first));
+     if !cell.iter().any(|&index| expandable(index)) {
+          return None;
+     }
+     let mut expanded = Vec::new();
+     for (index, production) in productions.iter().enumerate() {
+          if cell.contains(&index) && expandable(index) {
+               expanded.extend(inline_first(production, productions));
+          } else {
+               expanded.push(production.clone());
+          }
+     }
+     Some(expanded)
+}
+
+/*
+     @Description: Resolves conflicts between alternatives whose left corners share a terminal, e.g. factor ::= var |
+                   fname ( exprseq ) where both var and fname start with ID: the alternatives in a conflicting cell get
+                   their leading nonterminals inlined, up to MAX_EXPANSION_DEPTH levels, and are factored again. A step
+                   is kept only if it lowers the number of conflicts, so alternatives that stay ambiguous however far
+                   they are expanded, like ( bexpr ) against ( expr comp expr ), are left as they are
+     @Params: grammar - the grammar to transform
+     @Returns: The transformed grammar with its mapping back to the input rules
+*/
+pub fn expand_left_corners(grammar: &TransformedGrammar) -> TransformedGrammar {
+     let mut current = TransformedGrammar { rules: grammar.rules.clone(), origins: grammar.origins.clone() };
+     let mut count = conflict_count(&current.rules);
+     'improve: while count > 0 {
+          for (nonterminal, terminal) in Ll1Table::new(&current.rules).conflicts() {
+               let mut productions = current.productions();
+               for _ in 0..MAX_EXPANSION_DEPTH {
+                    productions = match expand_cell(&productions, &nonterminal, &terminal) {
+                         Some(expanded) => expanded,
+                         None => break,
+                    };
+                    let candidate = prune_unreachable(&left_factor(&TransformedGrammar::from_productions(productions.clone())));
+                    let candidate_count = conflict_count(&candidate.rules);
+                    if candidate_count < count {
+                         current = candidate;
+                         count = candidate_count;
+                         continue 'improve;
+                    }
+               }
+          }
+          break;
+     }
+     current
+}
+
+/*
+     @Description: Removes left recursion, including recursion hidden behind nullable symbols, left factors the grammar
+                   and expands left corners where alternatives still clash, so an LL(1) table can be built from it;
+                   the conflicts that remain are not LL(1) under any of these rewrites. Nonterminals the rewrites leave
+                   unreachable are dropped
+     @Params: grammar - the grammar rules
+     @Returns: The derived grammar with its mapping back to the original rules
+*/
+pub fn transform(grammar: &[String]) -> TransformedGrammar {
+     let exposed = expose_hidden_left_recursion(&TransformedGrammar::identity(grammar));
+     prune_unreachable(&expand_left_corners(&left_factor(&eliminate_left_recursion(&exposed))))
+}
+
+/*
+     @Description: Prints each derived rule with the original rules it came from
+     @Params: original - the original grammar rules, derived - the transformed grammar
+     @Returns: None
+*/
+pub fn print_mapping(original: &[String], derived: &TransformedGrammar) {
+     println!("Derived Grammar:");
+     for (index, rule) in derived.rules.iter().enumerate() {
+          println!("\t{}", rule);
+          for origin in derived.original_rules(index, original) {
+               println!("\t\tfrom {}", origin);
+          }
+     }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+
+/*
+     @Description: Struct for the source position of a token
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+     pub line: usize,
+     pub column: usize,
+}
+
+/*
+     @Description: String representation of a span as line:column
+     @Params: None
+     @Returns: None
+*/
+impl fmt::Display for Span {
+     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+          write!(f, "{}:{}", self.line, self.column)
+     }
+}
+
+/*
+     @Description: Struct for a concrete syntax tree node; nonterminals hold the production they were expanded with, terminals hold their token
+     @Params: None
+     @Returns: None
+*/
+#[derive(Debug, Clone)]
+pub struct ParseTree {
+     pub symbol: String,
+     pub production: Option<String>,
+     pub lexeme: Option<String>,
+     pub span: Option<Span>,
+     pub children: Vec<ParseTree>,
+}
+
+/*
+     @Description: Struct for building a tree top-down while the predictive parser expands its stack
+     @Params: None
+     @Returns: None
+*/
+#[derive(Default)]
+pub struct TreeBuilder {
+     nodes: Vec<ParseTree>,
+     children: Vec<Vec<usize>>,
+}
+
+impl TreeBuilder {
+     /*
+          @Description: Adds a node that is not attached to a parent yet
+          @Params: symbol - the grammar symbol of the node
+          @Returns: The node id
+     */
+     pub fn add_node(&mut self, symbol: &str) -> usize {
+          self.nodes.push(ParseTree {
+               symbol: symbol.to_string(),
+               production: None,
+               lexeme: None,
+               span: None,
+               children: Vec::new(),
+          });
+          self.children.push(Vec::new());
+          self.nodes.len() - 1
+     }
+
+     /*
+          @Description: Records the production a nonterminal was expanded with and adds a child for each symbol
+          @Params: id - the nonterminal node, production - the rule, symbols - the right hand side symbols
+          @Returns: The child ids, in order
+     */
+     pub fn expand(&mut self, id: usize, production: &str, symbols: &[String]) -> Vec<usize> {
+          self.nodes[id].production = Some(production.to_string());
+          let ids: Vec<usize> = symbols.iter().map(|symbol| self.add_node(symbol)).collect();
+          self.children[id].extend(ids.iter().cloned());
+          ids
+     }
+
+     /*
+          @Description: Records the token a terminal node matched
+          @Params: id - the terminal node, lexeme - the token text, span - the token position
+          @Returns: None
+     */
+     pub fn set_token(&mut self, id: usize, lexeme: &str, span: Span) {
+          self.nodes[id].lexeme = Some(lexeme.to_string());
+          self.nodes[id].span = Some(span);
+     }
+
+     /*
+          @Description: Assembles the tree below a node, giving each nonterminal the span of its first token
+          @Params: id - the root node
+          @Returns: The tree
+     */
+     pub fn finish(&self, id: usize) -> ParseTree {
+          let mut node = self.nodes[id].clone();
+          node.children = self.children[id].iter().map(|&child| self.finish(child)).collect();
+          if node.span.is_none() {
+               node.span = node.children.iter().find_map(|child| child.span);
+          }
+          node
+     }
+}
+
+/*
+     @Description: Renders a tree as indented text, one node per line
+     @Params: tree - the tree
+     @Returns: The text dump
+*/
+pub fn dump(tree: &ParseTree) -> String {
+     let mut output = String::new();
+     dump_node(tree, 0, &mut output);
+     output
+}
+
+fn dump_node(tree: &ParseTree, depth: usize, output: &mut String) {
+     output.push_str(&"  ".repeat(depth));
+     match (&tree.lexeme, &tree.production) {
+          (Some(lexeme), _) => output.push_str(&format!("{} \"{}\"", tree.symbol, lexeme)),
+          (None, Some(production)) => output.push_str(&format!("{} [{}]", tree.symbol, production)),
+          (None, None) => output.push_str(&tree.symbol),
+     }
+     if let Some(span) = tree.span {
+          output.push_str(&format!(" @ {}", span));
+     }
+     output.push('\n');
+     for child in &tree.children {
+          dump_node(child, depth + 1, output);
+     }
+}
+
+/*
+     @Description: Escapes text for a double quoted DOT label
+     @Params: text - the text
+     @Returns: The escaped text
+*/
+pub fn dot_escape(text: &str) -> String {
+     text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/*
+     @Description: Renders a tree as a Graphviz DOT digraph, nonterminals as ellipses and tokens as boxes
+     @Params: tree - the tree
+     @Returns: The DOT source
+*/
+pub fn to_dot(tree: &ParseTree) -> String {
+     let mut output = String::from("digraph parse_tree {\n     node [fontname=\"monospace\"];\n");
+     let mut next_id = 0;
+     dot_node(tree, &mut next_id, &mut output);
+     output.push_str("}\n");
+     output
+}
+
+fn dot_node(tree: &ParseTree, next_id: &mut usize, output: &mut String) -> usize {
+     let id = *next_id;
+     *next_id += 1;
+     match &tree.lexeme {
+          Some(lexeme) => output.push_str(&format!(
+               "     n{} [shape=box, label=\"{}\\n{}\"];\n",
+               id,
+               dot_escape(&tree.symbol),
+               dot_escape(lexeme)
+          )),
+          None => output.push_str(&format!("     n{} [label=\"{}\"];\n", id, dot_escape(&tree.symbol))),
+     }
+     for child in &tree.children {
+          let child_id = dot_node(child, next_id, output);
+          output.push_str(&format!("     n{} -> n{};\n", id, child_id));
+     }
+     id
+}
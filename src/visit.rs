@@ -0,0 +1,449 @@
+use crate::ast::{Decl, Expr, FnDef, Param, Program, Stmt};
+
+/*
+     @Description: Trait for a read-only pass over the AST; each method defaults to walking the node's children,
+                   so a pass overrides the methods for the nodes it cares about and calls the walk function to keep descending
+     @Params: None
+     @Returns: None
+*/
+pub trait Visitor {
+     fn visit_program(&mut self, program: &Program) {
+          walk_program(self, program);
+     }
+
+     fn visit_fn_def(&mut self, function: &FnDef) {
+          walk_fn_def(self, function);
+     }
+
+     fn visit_param(&mut self, param: &Param) {
+          walk_param(self, param);
+     }
+
+     fn visit_decl(&mut self, decl: &Decl) {
+          walk_decl(self, decl);
+     }
+
+     fn visit_stmt(&mut self, stmt: &Stmt) {
+          walk_stmt(self, stmt);
+     }
+
+     fn visit_expr(&mut self, expr: &Expr) {
+          walk_expr(self, expr);
+     }
+}
+
+/*
+     @Description: Visits the functions, declarations and main statements of a program, in source order
+     @Params: visitor - the pass, program - the node
+     @Returns: None
+*/
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+     for function in &program.functions {
+          visitor.visit_fn_def(function);
+     }
+     for decl in &program.declarations {
+          visitor.visit_decl(decl);
+     }
+     for stmt in &program.body {
+          visitor.visit_stmt(stmt);
+     }
+}
+
+/*
+     @Description: Visits the name, parameters, declarations and statements of a function
+     @Params: visitor - the pass, function - the node
+     @Returns: None
+*/
+pub fn walk_fn_def<V: Visitor + ?Sized>(visitor: &mut V, function: &FnDef) {
+     visitor.visit_expr(&function.name);
+     for param in &function.params {
+          visitor.visit_param(param);
+     }
+     for decl in &function.declarations {
+          visitor.visit_decl(decl);
+     }
+     for stmt in &function.body {
+          visitor.visit_stmt(stmt);
+     }
+}
+
+/*
+     @Description: Visits the variable of a parameter
+     @Params: visitor - the pass, param - the node
+     @Returns: None
+*/
+pub fn walk_param<V: Visitor + ?Sized>(visitor: &mut V, param: &Param) {
+     visitor.visit_expr(&param.var);
+}
+
+/*
+     @Description: Visits the variables of a declaration
+     @Params: visitor - the pass, decl - the node
+     @Returns: None
+*/
+pub fn walk_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &Decl) {
+     for var in &decl.vars {
+          visitor.visit_expr(var);
+     }
+}
+
+/*
+     @Description: Visits the expressions and nested statements of a statement
+     @Params: visitor - the pass, stmt - the node
+     @Returns: None
+*/
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+     match stmt {
+          Stmt::Assign { target, value, .. } => {
+               visitor.visit_expr(target);
+               visitor.visit_expr(value);
+          }
+          Stmt::If { cond, then_branch, else_branch, .. } => {
+               visitor.visit_expr(cond);
+               for stmt in then_branch {
+                    visitor.visit_stmt(stmt);
+               }
+               for stmt in else_branch.iter().flatten() {
+                    visitor.visit_stmt(stmt);
+               }
+          }
+          Stmt::While { cond, body, .. } => {
+               visitor.visit_expr(cond);
+               for stmt in body {
+                    visitor.visit_stmt(stmt);
+               }
+          }
+          Stmt::Print { value, .. } | Stmt::Return { value, .. } => visitor.visit_expr(value),
+          Stmt::Empty { .. } | Stmt::Error { .. } => {}
+     }
+}
+
+/*
+     @Description: Visits the subexpressions of an expression, left to right
+     @Params: visitor - the pass, expr - the node
+     @Returns: None
+*/
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+     match expr {
+          Expr::Paren { inner, .. } => visitor.visit_expr(inner),
+          Expr::Unary { operand, .. } => visitor.visit_expr(operand),
+          Expr::Binary { left, right, .. } => {
+               visitor.visit_expr(left);
+               visitor.visit_expr(right);
+          }
+          Expr::Index { target, index, .. } => {
+               visitor.visit_expr(target);
+               visitor.visit_expr(index);
+          }
+          Expr::Call { callee, args, .. } => {
+               visitor.visit_expr(callee);
+               for arg in args {
+                    visitor.visit_expr(arg);
+               }
+          }
+          Expr::Number { .. } | Expr::Ident { .. } | Expr::Missing { .. } | Expr::Error { .. } => {}
+     }
+}
+
+/*
+     @Description: Trait for a pass that edits the AST in place, e.g. renaming identifiers; the walks mirror Visitor's
+     @Params: None
+     @Returns: None
+*/
+pub trait VisitorMut {
+     fn visit_program_mut(&mut self, program: &mut Program) {
+          walk_program_mut(self, program);
+     }
+
+     fn visit_fn_def_mut(&mut self, function: &mut FnDef) {
+          walk_fn_def_mut(self, function);
+     }
+
+     fn visit_param_mut(&mut self, param: &mut Param) {
+          walk_param_mut(self, param);
+     }
+
+     fn visit_decl_mut(&mut self, decl: &mut Decl) {
+          walk_decl_mut(self, decl);
+     }
+
+     fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+          walk_stmt_mut(self, stmt);
+     }
+
+     fn visit_expr_mut(&mut self, expr: &mut Expr) {
+          walk_expr_mut(self, expr);
+     }
+}
+
+/*
+     @Description: Visits the functions, declarations and main statements of a program, in source order
+     @Params: visitor - the pass, program - the node
+     @Returns: None
+*/
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+     for function in &mut program.functions {
+          visitor.visit_fn_def_mut(function);
+     }
+     for decl in &mut program.declarations {
+          visitor.visit_decl_mut(decl);
+     }
+     for stmt in &mut program.body {
+          visitor.visit_stmt_mut(stmt);
+     }
+}
+
+/*
+     @Description: Visits the name, parameters, declarations and statements of a function
+     @Params: visitor - the pass, function - the node
+     @Returns: None
+*/
+pub fn walk_fn_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut FnDef) {
+     visitor.visit_expr_mut(&mut function.name);
+     for param in &mut function.params {
+          visitor.visit_param_mut(param);
+     }
+     for decl in &mut function.declarations {
+          visitor.visit_decl_mut(decl);
+     }
+     for stmt in &mut function.body {
+          visitor.visit_stmt_mut(stmt);
+     }
+}
+
+/*
+     @Description: Visits the variable of a parameter
+     @Params: visitor - the pass, param - the node
+     @Returns: None
+*/
+pub fn walk_param_mut<V: VisitorMut + ?Sized>(visitor: &mut V, param: &mut Param) {
+     visitor.visit_expr_mut(&mut param.var);
+}
+
+/*
+     @Description: Visits the variables of a declaration
+     @Params: visitor - the pass, decl - the node
+     @Returns: None
+*/
+pub fn walk_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut Decl) {
+     for var in &mut decl.vars {
+          visitor.visit_expr_mut(var);
+     }
+}
+
+/*
+     @Description: Visits the expressions and nested statements of a statement
+     @Params: visitor - the pass, stmt - the node
+     @Returns: None
+*/
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+     match stmt {
+          Stmt::Assign { target, value, .. } => {
+               visitor.visit_expr_mut(target);
+               visitor.visit_expr_mut(value);
+          }
+          Stmt::If { cond, then_branch, else_branch, .. } => {
+               visitor.visit_expr_mut(cond);
+               for stmt in then_branch {
+                    visitor.visit_stmt_mut(stmt);
+               }
+               for stmt in else_branch.iter_mut().flatten() {
+                    visitor.visit_stmt_mut(stmt);
+               }
+          }
+          Stmt::While { cond, body, .. } => {
+               visitor.visit_expr_mut(cond);
+               for stmt in body {
+                    visitor.visit_stmt_mut(stmt);
+               }
+          }
+          Stmt::Print { value, .. } | Stmt::Return { value, .. } => visitor.visit_expr_mut(value),
+          Stmt::Empty { .. } | Stmt::Error { .. } => {}
+     }
+}
+
+/*
+     @Description: Visits the subexpressions of an expression, left to right
+     @Params: visitor - the pass, expr - the node
+     @Returns: None
+*/
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+     match expr {
+          Expr::Paren { inner, .. } => visitor.visit_expr_mut(inner),
+          Expr::Unary { operand, .. } => visitor.visit_expr_mut(operand),
+          Expr::Binary { left, right, .. } => {
+               visitor.visit_expr_mut(left);
+               visitor.visit_expr_mut(right);
+          }
+          Expr::Index { target, index, .. } => {
+               visitor.visit_expr_mut(target);
+               visitor.visit_expr_mut(index);
+          }
+          Expr::Call { callee, args, .. } => {
+               visitor.visit_expr_mut(callee);
+               for arg in args {
+                    visitor.visit_expr_mut(arg);
+               }
+          }
+          Expr::Number { .. } | Expr::Ident { .. } | Expr::Missing { .. } | Expr::Error { .. } => {}
+     }
+}
+
+/*
+     @Description: Trait for a rewriting pass that consumes the AST and builds a new one; the defaults rebuild each node
+                   from its folded children and keep its span, and fold_stmts lets a pass drop or insert statements
+     @Params: None
+     @Returns: None
+*/
+pub trait Fold {
+     fn fold_program(&mut self, program: Program) -> Program {
+          fold_program(self, program)
+     }
+
+     fn fold_fn_def(&mut self, function: FnDef) -> FnDef {
+          fold_fn_def(self, function)
+     }
+
+     fn fold_param(&mut self, param: Param) -> Param {
+          fold_param(self, param)
+     }
+
+     fn fold_decl(&mut self, decl: Decl) -> Decl {
+          fold_decl(self, decl)
+     }
+
+     fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+          fold_stmts(self, stmts)
+     }
+
+     fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+          fold_stmt(self, stmt)
+     }
+
+     fn fold_expr(&mut self, expr: Expr) -> Expr {
+          fold_expr(self, expr)
+     }
+}
+
+/*
+     @Description: Folds the functions, declarations and main statements of a program
+     @Params: folder - the pass, program - the node
+     @Returns: The rebuilt program
+*/
+pub fn fold_program<F: Fold + ?Sized>(folder: &mut F, program: Program) -> Program {
+     Program {
+          functions: program.functions.into_iter().map(|function| folder.fold_fn_def(function)).collect(),
+          declarations: program.declarations.into_iter().map(|decl| folder.fold_decl(decl)).collect(),
+          body: folder.fold_stmts(program.body),
+          span: program.span,
+     }
+}
+
+/*
+     @Description: Folds the name, parameters, declarations and statements of a function; the return type and the trailing comma are kept
+     @Params: folder - the pass, function - the node
+     @Returns: The rebuilt function
+*/
+pub fn fold_fn_def<F: Fold + ?Sized>(folder: &mut F, function: FnDef) -> FnDef {
+     FnDef {
+          return_type: function.return_type,
+          name: folder.fold_expr(function.name),
+          params: function.params.into_iter().map(|param| folder.fold_param(param)).collect(),
+          trailing_comma: function.trailing_comma,
+          declarations: function.declarations.into_iter().map(|decl| folder.fold_decl(decl)).collect(),
+          body: folder.fold_stmts(function.body),
+          span: function.span,
+     }
+}
+
+/*
+     @Description: Folds the variable of a parameter
+     @Params: folder - the pass, param - the node
+     @Returns: The rebuilt parameter
+*/
+pub fn fold_param<F: Fold + ?Sized>(folder: &mut F, param: Param) -> Param {
+     Param { ty: param.ty, var: folder.fold_expr(param.var), span: param.span }
+}
+
+/*
+     @Description: Folds the variables of a declaration
+     @Params: folder - the pass, decl - the node
+     @Returns: The rebuilt declaration
+*/
+pub fn fold_decl<F: Fold + ?Sized>(folder: &mut F, decl: Decl) -> Decl {
+     Decl { ty: decl.ty, vars: decl.vars.into_iter().map(|var| folder.fold_expr(var)).collect(), span: decl.span }
+}
+
+/*
+     @Description: Folds each statement of a list
+     @Params: folder - the pass, stmts - the statements
+     @Returns: The folded statements
+*/
+pub fn fold_stmts<F: Fold + ?Sized>(folder: &mut F, stmts: Vec<Stmt>) -> Vec<Stmt> {
+     stmts.into_iter().map(|stmt| folder.fold_stmt(stmt)).collect()
+}
+
+/*
+     @Description: Folds the expressions and nested statement lists of a statement; empty and error statements are kept
+     @Params: folder - the pass, stmt - the node
+     @Returns: The rebuilt statement
+*/
+pub fn fold_stmt<F: Fold + ?Sized>(folder: &mut F, stmt: Stmt) -> Stmt {
+     match stmt {
+          Stmt::Assign { target, value, span } => Stmt::Assign { target: folder.fold_expr(target), value: folder.fold_expr(value), span },
+          Stmt::If { cond, then_branch, else_branch, span } => Stmt::If {
+               cond: folder.fold_expr(cond),
+               then_branch: folder.fold_stmts(then_branch),
+               else_branch: else_branch.map(|stmts| folder.fold_stmts(stmts)),
+               span,
+          },
+          Stmt::While { cond, body, span } => Stmt::While { cond: folder.fold_expr(cond), body: folder.fold_stmts(body), span },
+          Stmt::Print { value, span } => Stmt::Print { value: folder.fold_expr(value), span },
+          Stmt::Return { value, span } => Stmt::Return { value: folder.fold_expr(value), span },
+          Stmt::Empty { .. } | Stmt::Error { .. } => stmt,
+     }
+}
+
+/*
+     @Description: Folds the subexpressions of an expression, left to right; leaves are kept
+     @Params: folder - the pass, expr - the node
+     @Returns: The rebuilt expression
+*/
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+     match expr {
+          Expr::Paren { inner, span } => Expr::Paren { inner: Box::new(folder.fold_expr(*inner)), span },
+          Expr::Unary { op, operand, span } => Expr::Unary { op, operand: Box::new(folder.fold_expr(*operand)), span },
+          Expr::Binary { op, left, right, span } => {
+               let left = Box::new(folder.fold_expr(*left));
+               Expr::Binary { op, left, right: Box::new(folder.fold_expr(*right)), span }
+          }
+          Expr::Index { target, index, span } => {
+               let target = Box::new(folder.fold_expr(*target));
+               Expr::Index { target, index: Box::new(folder.fold_expr(*index)), span }
+          }
+          Expr::Call { callee, args, trailing_comma, span } => {
+               let callee = Box::new(folder.fold_expr(*callee));
+               Expr::Call { callee, args: args.into_iter().map(|arg| folder.fold_expr(arg)).collect(), trailing_comma, span }
+          }
+          Expr::Number { .. } | Expr::Ident { .. } | Expr::Missing { .. } | Expr::Error { .. } => expr,
+     }
+}
+
+/*
+     @Description: Builds an expression that replaces another, at the replaced expression's position, so diagnostics
+                   about rewritten code still point at the source
+     @Params: original - the expression being replaced, replacement - the new expression
+     @Returns: The replacement with the original's span
+*/
+pub fn replace_expr(original: &Expr, replacement: Expr) -> Expr {
+     replacement.with_span(original.span())
+}
+
+/*
+     @Description: Builds a statement that replaces another, at the replaced statement's position
+     @Params: original - the statement being replaced, replacement - the new statement
+     @Returns: The replacement with the original's span
+*/
+pub fn replace_stmt(original: &Stmt, replacement: Stmt) -> Stmt {
+     replacement.with_span(original.span())
+}
@@ -0,0 +1,434 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::earley::earley_parse;
+use crate::grammar::{make_rule, nonterminals, split_rule};
+use crate::lr::{Action, Conflict, LrTable};
+use crate::table::Ll1Table;
+
+/*
+     @Description: The input before and after a nonterminal in a sentence
+     @Params: None
+     @Returns: None
+*/
+type Context = (Vec<String>, Vec<String>);
+
+/*
+     @Description: Struct for the shortest terminal strings the grammar derives, used to turn symbols into example input
+     @Params: None
+     @Returns: None
+*/
+pub struct Yields {
+     pub rules: Vec<(String, Vec<String>)>,
+     pub nonterminals: Vec<String>,
+     shortest: HashMap<String, Vec<String>>,
+     starting: HashMap<(String, String), Vec<String>>,
+     contexts: HashMap<String, Context>,
+     follow_contexts: HashMap<(String, String), Context>,
+}
+
+/*
+     @Description: Keeps a candidate if it is shorter than the current entry of a map
+     @Params: map - the map, key - the entry, candidate - the new value, length - the length of a value
+     @Returns: True if the entry changed
+*/
+fn improve<K: std::hash::Hash + Eq, V>(map: &mut HashMap<K, V>, key: K, candidate: V, length: impl Fn(&V) -> usize) -> bool {
+     match map.get(&key) {
+          Some(current) if length(current) <= length(&candidate) => false,
+          _ => {
+               map.insert(key, candidate);
+               true
+          }
+     }
+}
+
+impl Yields {
+     /*
+          @Description: Computes, by iterating to a fixed point, the shortest string each nonterminal derives, the shortest one
+                        starting with each terminal, and the shortest sentence around each nonterminal, optionally with a
+                        given terminal (or $ for the end of input) right after it
+          @Params: grammar - the grammar rules
+          @Returns: The yields
+     */
+     pub fn new(grammar: &[String]) -> Yields {
+          let rules: Vec<(String, Vec<String>)> = grammar.iter().map(|rule| split_rule(rule)).collect();
+          let mut yields = Yields {
+               rules,
+               nonterminals: nonterminals(grammar),
+               shortest: HashMap::new(),
+               starting: HashMap::new(),
+               contexts: HashMap::new(),
+               follow_contexts: HashMap::new(),
+          };
+
+          let mut changed = true;
+          while changed {
+               changed = false;
+               for index in 0..yields.rules.len() {
+                    let (lhs, rhs) = yields.rules[index].clone();
+                    if let Some(sentence) = yields.sequence(&rhs) {
+                         changed |= improve(&mut yields.shortest, lhs, sentence, Vec::len);
+                    }
+               }
+          }
+
+          let mut terminals: Vec<String> =
+               yields.rules.iter().flat_map(|(_, rhs)| rhs.iter()).filter(|s| !yields.is_nonterminal(s)).cloned().collect();
+          terminals.sort();
+          terminals.dedup();
+          changed = true;
+          while changed {
+               changed = false;
+               for index in 0..yields.rules.len() {
+                    let (lhs, rhs) = yields.rules[index].clone();
+                    for terminal in &terminals {
+                         if let Some(sentence) = yields.sequence_starting(&rhs, terminal) {
+                              changed |= improve(&mut yields.starting, (lhs.clone(), terminal.clone()), sentence, Vec::len);
+                         }
+                    }
+               }
+          }
+
+          let start = yields.nonterminals[0].clone();
+          yields.contexts.insert(start.clone(), (Vec::new(), Vec::new()));
+          yields.follow_contexts.insert((start, String::from("$")), (Vec::new(), Vec::new()));
+          let context_length = |(prefix, suffix): &Context| prefix.len() + suffix.len();
+          changed = true;
+          while changed {
+               changed = false;
+               for index in 0..yields.rules.len() {
+                    let (lhs, rhs) = yields.rules[index].clone();
+                    for position in 0..rhs.len() {
+                         if !yields.is_nonterminal(&rhs[position]) {
+                              continue;
+                         }
+                         let (before, after) = match (yields.sequence(&rhs[..position]), yields.sequence(&rhs[position + 1..])) {
+                              (Some(before), Some(after)) => (before, after),
+                              _ => continue,
+                         };
+                         if let Some((prefix, suffix)) = yields.contexts.get(&lhs).cloned() {
+                              let candidate = ([prefix.clone(), before.clone()].concat(), [after, suffix.clone()].concat());
+                              changed |= improve(&mut yields.contexts, rhs[position].clone(), candidate, context_length);
+
+                              for terminal in &terminals {
+                                   if let Some(after) = yields.sequence_starting(&rhs[position + 1..], terminal) {
+                                        let candidate = ([prefix.clone(), before.clone()].concat(), [after, suffix.clone()].concat());
+                                        changed |= improve(
+                                             &mut yields.follow_contexts,
+                                             (rhs[position].clone(), terminal.clone()),
+                                             candidate,
+                                             context_length,
+                                        );
+                                   }
+                              }
+                         }
+                         if yields.sequence(&rhs[position + 1..]).is_some_and(|after| after.is_empty()) {
+                              let mut inherited: Vec<(String, Context)> = yields
+                                   .follow_contexts
+                                   .iter()
+                                   .filter(|((nonterminal, _), _)| *nonterminal == lhs)
+                                   .map(|((_, terminal), context)| (terminal.clone(), context.clone()))
+                                   .collect();
+                              inherited.sort();
+                              for (terminal, (prefix, suffix)) in inherited {
+                                   let candidate = ([prefix, before.clone()].concat(), suffix);
+                                   changed |=
+                                        improve(&mut yields.follow_contexts, (rhs[position].clone(), terminal), candidate, context_length);
+                              }
+                         }
+                    }
+               }
+          }
+          yields
+     }
+
+     fn is_nonterminal(&self, symbol: &str) -> bool {
+          self.nonterminals.iter().any(|nonterminal| nonterminal == symbol)
+     }
+
+     /*
+          @Description: Gets the shortest string a sequence of symbols derives
+          @Params: symbols - the symbols
+          @Returns: The terminals, or None if some nonterminal derives nothing (yet)
+     */
+     pub fn sequence(&self, symbols: &[String]) -> Option<Vec<String>> {
+          let mut sentence = Vec::new();
+          for symbol in symbols {
+               if self.is_nonterminal(symbol) {
+                    sentence.extend(self.shortest.get(symbol)?.iter().cloned());
+               } else {
+                    sentence.push(symbol.clone());
+               }
+          }
+          Some(sentence)
+     }
+
+     /*
+          @Description: Gets the shortest string a sequence of symbols derives that starts with a terminal
+          @Params: symbols - the symbols, terminal - the first terminal
+          @Returns: The terminals, or None if the sequence cannot start with the terminal
+     */
+     pub fn sequence_starting(&self, symbols: &[String], terminal: &str) -> Option<Vec<String>> {
+          for (position, symbol) in symbols.iter().enumerate() {
+               let before = self.sequence(&symbols[..position]);
+               if before.as_ref().is_none_or(|before| !before.is_empty()) {
+                    return None;
+               }
+               let rest = self.sequence(&symbols[position + 1..]);
+               let head = if self.is_nonterminal(symbol) {
+                    self.starting.get(&(symbol.clone(), terminal.to_string())).cloned()
+               } else if symbol == terminal {
+                    Some(vec![symbol.clone()])
+               } else {
+                    None
+               };
+               if let (Some(head), Some(rest)) = (head, rest) {
+                    return Some([head, rest].concat());
+               }
+          }
+          None
+     }
+
+     /*
+          @Description: Gets the shortest sentence in which a nonterminal derives the given symbols, optionally followed by a terminal
+          @Params: nonterminal - the nonterminal, symbols - what it derives, next - the terminal that must follow it, or $ for the end of input
+          @Returns: The input before the symbols' yield, the sentence, or None if there is no such sentence
+     */
+     pub fn sentence_through(&self, nonterminal: &str, symbols: &[String], next: Option<&str>) -> Option<(Vec<String>, Vec<String>)> {
+          match next {
+               Some(terminal) => {
+                    if let Some(middle) = self.sequence_starting(symbols, terminal) {
+                         let (prefix, suffix) = self.contexts.get(nonterminal)?;
+                         return Some((prefix.clone(), [prefix.clone(), middle, suffix.clone()].concat()));
+                    }
+                    let middle = self.sequence(symbols)?;
+                    if !middle.is_empty() {
+                         return None;
+                    }
+                    let (prefix, suffix) = self.follow_contexts.get(&(nonterminal.to_string(), terminal.to_string()))?;
+                    Some((prefix.clone(), [prefix.clone(), suffix.clone()].concat()))
+               }
+               None => {
+                    let (prefix, suffix) = self.contexts.get(nonterminal)?;
+                    Some((prefix.clone(), [prefix.clone(), self.sequence(symbols)?, suffix.clone()].concat()))
+               }
+          }
+     }
+}
+
+/*
+     @Description: Struct for the counterexamples of one conflict: the shortest input reaching it, an example sentence for each
+                   conflicting choice with its parse, and a sentence with two parses if one of the examples is ambiguous
+     @Params: None
+     @Returns: None
+*/
+pub struct Witness {
+     pub conflict: String,
+     pub prefix: Vec<String>,
+     pub examples: Vec<(String, Vec<String>, Option<String>)>,
+     pub ambiguity: Option<(Vec<String>, String, String)>,
+}
+
+/*
+     @Description: Parses example sentences with the Earley parser, keeping a tree sketch of each, preferring a tree that uses
+                   the rule the example is for, and the first ambiguous sentence
+     @Params: grammar - the grammar rules, witness - the witness whose examples to parse
+     @Returns: None
+*/
+fn parse_examples(grammar: &[String], witness: &mut Witness) {
+     for (label, sentence, sketch) in witness.examples.iter_mut() {
+          let forest = match earley_parse(grammar, sentence) {
+               Ok(forest) => forest,
+               Err(_) => continue,
+          };
+          let trees = forest.trees(8);
+          let uses_rule = trees
+               .iter()
+               .find(|tree| forest.derivation_rules(tree).iter().any(|rule| label.ends_with(rule.as_str())));
+          if let Some(tree) = uses_rule.or(trees.first()) {
+               *sketch = Some(forest.format_tree(tree));
+          }
+          if trees.len() > 1 && witness.ambiguity.is_none() {
+               witness.ambiguity = Some((sentence.clone(), forest.format_tree(&trees[0]), forest.format_tree(&trees[1])));
+          }
+     }
+}
+
+/*
+     @Description: Builds a witness for each LL(1) conflict; the prefix ends with the lookahead on which the parser cannot choose
+     @Params: grammar - the grammar rules the table was built from, table - the table
+     @Returns: The witnesses, in table order
+*/
+pub fn ll1_witnesses(grammar: &[String], table: &Ll1Table) -> Vec<Witness> {
+     let yields = Yields::new(grammar);
+     let mut witnesses = Vec::new();
+     for (nonterminal, terminal) in table.conflicts() {
+          let rules = table.find_cell(&nonterminal, &terminal);
+          let labels: Vec<String> = rules.iter().map(|&rule| grammar[rule].clone()).collect();
+          let mut witness = Witness {
+               conflict: format!("{} on {} between {}", nonterminal, terminal, labels.join(" and ")),
+               prefix: Vec::new(),
+               examples: Vec::new(),
+               ambiguity: None,
+          };
+          for &rule in rules {
+               let (_, rhs) = split_rule(&grammar[rule]);
+               if let Some((prefix, sentence)) = yields.sentence_through(&nonterminal, &rhs, Some(&terminal)) {
+                    if witness.prefix.is_empty() {
+                         witness.prefix = [prefix, vec![terminal.clone()]].concat();
+                    }
+                    witness.examples.push((grammar[rule].clone(), sentence, None));
+               }
+          }
+          parse_examples(grammar, &mut witness);
+          witnesses.push(witness);
+     }
+     witnesses
+}
+
+/*
+     @Description: Finds the shortest symbol sequence that takes the LR automaton from the start state to a state
+     @Params: table - the parse table, target - the state
+     @Returns: The symbols shifted or reduced to on the way
+*/
+fn access_symbols(table: &LrTable, target: usize) -> Vec<String> {
+     let mut previous: HashMap<usize, (usize, String)> = HashMap::new();
+     let mut frontier = vec![0];
+     let mut visited: HashSet<usize> = HashSet::from([0]);
+     while !frontier.is_empty() && !visited.contains(&target) {
+          let mut next = Vec::new();
+          for &state in &frontier {
+               let mut edges: Vec<(String, usize)> = Vec::new();
+               for ((from, symbol), action) in &table.action {
+                    if let (true, Action::Shift(to)) = (*from == state, action) {
+                         edges.push((symbol.clone(), *to));
+                    }
+               }
+               for ((from, symbol), to) in &table.goto {
+                    if *from == state {
+                         edges.push((symbol.clone(), *to));
+                    }
+               }
+               edges.sort();
+               for (symbol, to) in edges {
+                    if visited.insert(to) {
+                         previous.insert(to, (state, symbol));
+                         next.push(to);
+                    }
+               }
+          }
+          frontier = next;
+     }
+
+     let mut symbols = Vec::new();
+     let mut state = target;
+     while let Some((from, symbol)) = previous.get(&state) {
+          symbols.push(symbol.clone());
+          state = *from;
+     }
+     symbols.reverse();
+     symbols
+}
+
+/*
+     @Description: Builds a witness for each LR conflict not resolved by precedence; a reduction is shown with the reduced
+                   nonterminal followed by the conflict terminal, a shift with an item of the state that shifts the terminal
+     @Params: grammar - the grammar rules the table was built from, table - the table
+     @Returns: The witnesses, in table order
+*/
+pub fn lr_witnesses(grammar: &[String], table: &LrTable) -> Vec<Witness> {
+     let yields = Yields::new(grammar);
+     let mut witnesses = Vec::new();
+     let conflicts: Vec<&Conflict> = table.conflicts.iter().filter(|conflict| !conflict.by_precedence).collect();
+     for conflict in conflicts {
+          let actions: Vec<String> = conflict.actions.iter().map(|action| action.to_string()).collect();
+          let prefix = yields.sequence(&access_symbols(table, conflict.state)).unwrap_or_default();
+          let mut witness = Witness {
+               conflict: format!("state {} on {}: {} conflict between {}", conflict.state, conflict.terminal, conflict.kind(), actions.join(" and ")),
+               prefix: [prefix, vec![conflict.terminal.clone()]].concat(),
+               examples: Vec::new(),
+               ambiguity: None,
+          };
+          for action in &conflict.actions {
+               let example = match action {
+                    Action::Reduce(rule) => {
+                         let (lhs, rhs) = &table.rules[*rule];
+                         let label = format!("r{}: {}", rule, make_rule(lhs, rhs));
+                         let sentence = yields
+                              .follow_contexts
+                              .get(&(lhs.clone(), conflict.terminal.clone()))
+                              .and_then(|(prefix, suffix)| Some([prefix.clone(), yields.sequence(rhs)?, suffix.clone()].concat()));
+                         sentence.map(|sentence| (label, sentence))
+                    }
+                    Action::Shift(_) => shift_example(table, &yields, conflict),
+                    _ => None,
+               };
+               if let Some((label, sentence)) = example {
+                    witness.examples.push((label, sentence, None));
+               }
+          }
+          parse_examples(grammar, &mut witness);
+          witnesses.push(witness);
+     }
+     witnesses
+}
+
+/*
+     @Description: Finds an item of a conflict state with the conflict terminal after the dot and an example sentence using it
+     @Params: table - the parse table, yields - the grammar yields, conflict - the conflict
+     @Returns: The item and the sentence, if the grammar has one
+*/
+fn shift_example(table: &LrTable, yields: &Yields, conflict: &Conflict) -> Option<(String, Vec<String>)> {
+     let mut items: Vec<(usize, usize)> = table.states[conflict.state].iter().map(|(rule, dot, _)| (*rule, *dot)).collect();
+     let mut index = 0;
+     while index < items.len() {
+          let (rule, dot) = items[index];
+          if let Some(symbol) = table.rules[rule].1.get(dot) {
+               for (other, (lhs, _)) in table.rules.iter().enumerate() {
+                    if lhs == symbol && !items.contains(&(other, 0)) {
+                         items.push((other, 0));
+                    }
+               }
+          }
+          index += 1;
+     }
+
+     for (rule, dot) in items {
+          let (lhs, rhs) = &table.rules[rule];
+          if rule == 0 || rhs.get(dot) != Some(&conflict.terminal) {
+               continue;
+          }
+          if let Some((_, sentence)) = yields.sentence_through(lhs, rhs, None) {
+               let mut symbols: Vec<&str> = rhs.iter().map(|symbol| symbol.as_str()).collect();
+               symbols.insert(dot, "•");
+               return Some((format!("shift in {} ::= {}", lhs, symbols.join(" ")), sentence));
+          }
+     }
+     None
+}
+
+/*
+     @Description: Formats witnesses as token sequences and tree sketches
+     @Params: witnesses - the witnesses
+     @Returns: The report text
+*/
+pub fn format_witnesses(witnesses: &[Witness]) -> String {
+     let mut output = String::new();
+     for witness in witnesses {
+          output.push_str(&format!("Conflict: {}\n", witness.conflict));
+          output.push_str(&format!("\tshortest prefix: {}\n", witness.prefix.join(" ")));
+          for (label, sentence, sketch) in &witness.examples {
+               output.push_str(&format!("\t{}\n\t\tinput: {}\n", label, sentence.join(" ")));
+               if let Some(sketch) = sketch {
+                    output.push_str(&format!("\t\ttree: {}\n", sketch));
+               }
+          }
+          match &witness.ambiguity {
+               Some((sentence, first, second)) => {
+                    output.push_str(&format!("\tambiguous input: {}\n", sentence.join(" ")));
+                    output.push_str(&format!("\t\t1: {}\n\t\t2: {}\n", first, second));
+               }
+               None => output.push_str("\tno example has two parses; the choices differ after more lookahead\n"),
+          }
+     }
+     output
+}
@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+
+use compiler::ast::Program;
+use compiler::generator::Generator;
+use compiler::grammar::load_grammar;
+use compiler::lexer::{Lexer, Token};
+use compiler::parser::Parser;
+use compiler::pratt::OperatorTable;
+use compiler::resilient::parse_program;
+use compiler::syntax_error::SyntaxError;
+use compiler::terminals::TerminalMap;
+use compiler::transform::transform;
+
+/*
+     @Description: Gets the path of a file under src/data
+     @Params: path - the path relative to src/data, e.g. "tests/Test2.ez"
+     @Returns: The absolute path
+*/
+pub fn data_path(path: &str) -> String {
+     format!("{}/src/data/{}", env!("CARGO_MANIFEST_DIR"), path)
+}
+
+pub fn read_data(path: &str) -> String {
+     fs::read_to_string(data_path(path)).expect("Unable to read file")
+}
+
+/*
+     @Description: Lexes source text; the lexer writes its logs to data/output under the working directory, so the
+                   tests run in a scratch directory rather than rewriting the committed logs
+     @Params: source - the source text
+     @Returns: The tokens
+*/
+pub fn lex(source: &str) -> Vec<Token> {
+     let directory = env::temp_dir().join("ez-compiler-tests");
+     fs::create_dir_all(directory.join("data/output")).expect("Unable to create directory");
+     env::set_current_dir(&directory).expect("Unable to change directory");
+     Lexer::get_next_token(source).unwrap()
+}
+
+/*
+     @Description: Builds the LL(1) parser of the EZ grammar the CLI uses
+     @Params: None
+     @Returns: The parser
+*/
+pub fn ez_parser() -> Parser {
+     let rules = load_grammar(&data_path("grammar/ez.bnf")).unwrap();
+     Parser::new(&transform(&rules).rules)
+}
+
+/*
+     @Description: Parses source text into an AST with the resilient parser and the EZ operators
+     @Params: source - the source text
+     @Returns: The program and the syntax errors
+*/
+pub fn parse_ez(source: &str) -> (Program, Vec<SyntaxError>) {
+     parse_program(&lex(source), &TerminalMap::default(), &OperatorTable::ez())
+}
+
+/*
+     @Description: Generates random EZ programs the way fuzz does, with the committed weights
+     @Params: count - the number of programs, seed - the random seed
+     @Returns: The source texts
+*/
+pub fn generated_programs(count: usize, seed: u64) -> Vec<String> {
+     let rules = load_grammar(&data_path("grammar/ez.bnf")).unwrap();
+     let mut generator = Generator::new(&rules, 12, seed);
+     generator.load_weights(&data_path("grammar/ez.weights")).unwrap();
+     (0..count)
+          .map(|_| {
+               let sentence = generator.sentence();
+               generator.render(&sentence)
+          })
+          .collect()
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use compiler::coverage::Coverage;
+use compiler::grammar::split_rule;
+use compiler::terminals::TerminalMap;
+
+#[test]
+fn hits_are_recorded_against_the_parser_table() {
+     let parser = common::ez_parser();
+     let tokens = common::lex(&common::read_data("tests/Test5.ez"));
+     let mut coverage = Coverage::new();
+     parser.parse_tokens_covered(&tokens, &TerminalMap::default(), &mut coverage).unwrap();
+
+     assert!(!coverage.productions.is_empty());
+     for (nonterminal, terminal) in coverage.cells.keys() {
+          assert!(!parser.table.find_cell(nonterminal, terminal).is_empty(), "{} / {} is not a table cell", nonterminal, terminal);
+     }
+     for rule in coverage.productions.keys() {
+          let (lhs, _) = split_rule(&parser.table.rules[*rule]);
+          let used = coverage.cells.keys().any(|(nonterminal, terminal)| {
+               nonterminal == &lhs && parser.table.find_cell(nonterminal, terminal).contains(rule)
+          });
+          assert!(used, "production {} has no recorded cell", rule);
+     }
+}
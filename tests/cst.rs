@@ -0,0 +1,89 @@
+mod common;
+
+use std::rc::Rc;
+
+use compiler::cst::{lossless_tokens, parse_cst, AssignStmt, FnDef, GreenElement, GreenToken, IfStmt, NodeKind, Program, SyntaxNode, WhileStmt};
+use compiler::pratt::OperatorTable;
+use compiler::terminals::TerminalMap;
+
+fn cst(source: &str) -> SyntaxNode {
+     let tokens = common::lex(source);
+     parse_cst(source, &tokens, &TerminalMap::default(), &OperatorTable::ez()).unwrap()
+}
+
+const SOURCE: &str = "def int f(int x)\n\treturn x * 2\nfed;\nint a;\nif (a < 1) then a = f(a) else a = 2 fi;\nwhile a > 0 do a = a - 1 od.\n";
+
+#[test]
+fn round_trips_every_test_program() {
+     for number in 1..=10 {
+          let source = common::read_data(&format!("tests/Test{}.ez", number));
+          assert_eq!(cst(&source).text(), source, "Test{}.ez", number);
+     }
+     for source in ["int a;\na = 1 @ 2;\nprint(a).", "b=1r5;\n print(b <= 2)  .  \n\n", "", "fed fed"] {
+          assert_eq!(cst(source).text(), source);
+     }
+}
+
+#[test]
+fn refuses_tokens_that_are_not_in_the_source() {
+     let tokens = common::lex("int a;\nprint(a).");
+     let error = lossless_tokens("int b;\nprint(b).", &tokens, &TerminalMap::default()).unwrap_err();
+     assert_eq!(error, "Error: Token `a` at 1:2 is not in the source");
+}
+
+#[test]
+fn typed_accessors_find_the_pieces() {
+     let root = cst(SOURCE);
+     let program = Program::cast(root.clone()).unwrap();
+     assert_eq!(program.declarations().len(), 1);
+
+     let functions = program.functions();
+     let function = &functions[0];
+     assert_eq!(function.name().unwrap().text(), "f");
+     assert_eq!(function.return_type().unwrap().text(), "int");
+     assert_eq!(function.params().iter().map(SyntaxNode::text).collect::<Vec<_>>(), ["int x"]);
+     assert_eq!(function.body().unwrap().text(), "return x * 2");
+
+     let statements = program.body().unwrap().children();
+     let branch = IfStmt::cast(statements[0].clone()).unwrap();
+     assert_eq!(branch.condition().unwrap().text(), "(a < 1)");
+     assert_eq!(branch.then_branch().unwrap().text(), "a = f(a)");
+     assert_eq!(branch.else_branch().unwrap().text(), "a = 2");
+     let assign = AssignStmt::cast(branch.then_branch().unwrap().children()[0].clone()).unwrap();
+     assert_eq!(assign.target().unwrap().text(), "a");
+     assert_eq!(assign.value().unwrap().kind(), NodeKind::CallExpr);
+
+     let repeat = WhileStmt::cast(statements[1].clone()).unwrap();
+     assert_eq!(repeat.condition().unwrap().kind(), NodeKind::BinExpr);
+     assert_eq!(repeat.body().unwrap().text(), "a = a - 1");
+     assert!(FnDef::cast(statements[1].clone()).is_none());
+}
+
+#[test]
+fn edits_rebuild_only_the_path_to_the_root() {
+     let root = cst(SOURCE);
+     let offset = SOURCE.find("2 fi").unwrap();
+     let two = root.token_at_offset(offset).unwrap();
+     assert_eq!(two.kind(), "NUMBER");
+     assert!(!two.is_trivia());
+     assert!(root.token_at_offset(offset + 1).unwrap().is_trivia());
+     assert_eq!(two.parent().kind(), NodeKind::Literal);
+     assert_eq!(two.parent().ancestors().last().unwrap().kind(), NodeKind::Program);
+
+     let edited = two.replace_text("20");
+     assert_eq!(edited.text(), SOURCE.replace("2 fi", "20 fi"));
+     assert_eq!(root.text(), SOURCE);
+
+     let old_functions = root.children()[0].green();
+     let new_functions = edited.children()[0].green();
+     assert!(Rc::ptr_eq(&old_functions, &new_functions));
+
+     let node = root.node_at_offset(offset).unwrap();
+     assert_eq!(node.kind(), NodeKind::Literal);
+     let function = root.children()[0].clone();
+     let green = function.green();
+     let name = green.children.iter().position(|child| matches!(child, GreenElement::Token(token) if token.kind == "ID")).unwrap();
+     let renamed = GreenToken { kind: String::from("ID"), text: String::from("g") };
+     let green = green.replace_child(name, GreenElement::Token(Rc::new(renamed)));
+     assert_eq!(function.replace_with(green).text(), SOURCE.replacen("f(int", "g(int", 1));
+}
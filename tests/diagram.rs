@@ -0,0 +1,42 @@
+use compiler::diagram::{alternatives, dependency_dot, railroad_svg};
+use compiler::grammar::parse_grammar;
+
+fn grammar() -> Vec<String> {
+     parse_grammar("<s> ::= <e> . | \n<e> ::= <e> < <t> | <t>\n<t> ::= ID").unwrap()
+}
+
+#[test]
+fn dot_has_one_edge_per_dependency_and_a_bold_start() {
+     let dot = dependency_dot(&grammar());
+     assert_eq!(
+          dot,
+          "digraph grammar {\n     node [fontname=\"monospace\", shape=box];\n     \"s\" [style=bold];\n     \"e\";\n     \"t\";\n     \
+           \"s\" -> \"e\";\n     \"e\" -> \"e\";\n     \"e\" -> \"t\";\n}\n"
+     );
+}
+
+#[test]
+fn svg_links_nonterminals_and_escapes_terminals() {
+     let grammar = grammar();
+     let groups = alternatives(&grammar);
+     let names: Vec<String> = groups.iter().map(|(nonterminal, _)| nonterminal.clone()).collect();
+     let (nonterminal, rhs_list) = &groups[1];
+     let svg = railroad_svg(nonterminal, rhs_list, &names);
+     assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+     assert!(svg.ends_with("</svg>\n"));
+     assert!(svg.contains("<text class=\"title\" x=\"20\" y=\"20\">e</text>"));
+     assert_eq!(svg.matches("<a xlink:href=\"e.svg\">").count(), 1);
+     assert_eq!(svg.matches("<a xlink:href=\"t.svg\">").count(), 2);
+     assert_eq!(svg.matches("<rect class=\"terminal\"").count(), 1);
+     assert!(svg.contains(">&lt;</text>"));
+     assert!(!svg.contains("><</text>"));
+}
+
+#[test]
+fn svg_size_follows_the_widest_alternative_and_the_row_count() {
+     let names = vec![String::from("s")];
+     let svg = railroad_svg("s", &[vec![String::from("ab")], vec![]], &names);
+     // One box of 2 characters: 2 * 8 + 2 * 10 = 36 wide, with a 16 gap on both sides; 2 * 20 margin and 2 * 30 rails
+     // around it, and 20 + 24 above 2 rows of 40 with 10 below
+     assert!(svg.contains("width=\"168\" height=\"134\" viewBox=\"0 0 168 134\""), "{}", svg);
+}
@@ -0,0 +1,51 @@
+use compiler::earley::{earley_parse, Disambiguation};
+
+fn sums(operands: usize) -> Vec<String> {
+     let mut input = vec![String::from("ID")];
+     for _ in 1..operands {
+          input.push(String::from("+"));
+          input.push(String::from("ID"));
+     }
+     input
+}
+
+fn ambiguous() -> Vec<String> {
+     vec![String::from("E ::= E + E"), String::from("E ::= ID")]
+}
+
+#[test]
+fn lists_trees_of_a_highly_ambiguous_sum_up_to_the_limit() {
+     let forest = earley_parse(&ambiguous(), &sums(16)).unwrap();
+     assert_eq!(forest.count(), 9694845);
+     let trees = forest.trees(10);
+     assert_eq!(trees.len(), 10);
+     for (index, tree) in trees.iter().enumerate() {
+          assert!(!trees[..index].contains(tree));
+     }
+     assert!(forest.trees(0).is_empty());
+}
+
+#[test]
+fn preferred_rules_pick_the_tree() {
+     let forest = earley_parse(&ambiguous(), &sums(3)).unwrap();
+     assert_eq!(forest.trees(10).len(), 2);
+
+     let longest = forest.disambiguate(&Disambiguation::default()).unwrap();
+     assert_eq!(forest.format_tree(&longest), "E(E(E(ID) + E(ID)) + E(ID))");
+     let shortest = Disambiguation { longest_first: false, ..Disambiguation::default() };
+     let shortest = forest.disambiguate(&shortest).unwrap();
+     assert_eq!(forest.format_tree(&shortest), "E(E(ID) + E(E(ID) + E(ID)))");
+}
+
+#[test]
+fn preferred_rules_outrank_grammar_order() {
+     let grammar: Vec<String> = ["S ::= A", "S ::= B", "A ::= ID", "B ::= ID"].iter().map(|rule| rule.to_string()).collect();
+     let forest = earley_parse(&grammar, &[String::from("ID")]).unwrap();
+     let chosen = forest.disambiguate(&Disambiguation::default()).unwrap();
+     assert_eq!(forest.format_tree(&chosen), "S(A(ID))");
+
+     let mut rules = Disambiguation::default();
+     rules.add_priority(1);
+     let chosen = forest.disambiguate(&rules).unwrap();
+     assert_eq!(forest.format_tree(&chosen), "S(B(ID))");
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use compiler::earley::earley_parse;
+use compiler::generator::Generator;
+use compiler::grammar::{load_grammar, parse_grammar};
+use compiler::terminals::TerminalMap;
+
+#[test]
+fn desugars_each_operator_into_a_generated_nonterminal() {
+     let rules = parse_grammar("%ebnf\n<l> ::= a { , a } [ b ] ( c | d )+ \"(\" | <l>?").unwrap();
+     assert_eq!(
+          rules,
+          [
+               "l ::= a lRepeat lOpt lGroup lRepeat2 (",
+               "l ::= lOpt2",
+               "lRepeat ::= , a lRepeat",
+               "lRepeat ::= 𝛜",
+               "lOpt ::= b",
+               "lOpt ::= 𝛜",
+               "lGroup ::= c",
+               "lGroup ::= d",
+               "lRepeat2 ::= lGroup lRepeat2",
+               "lRepeat2 ::= 𝛜",
+               "lOpt2 ::= l",
+               "lOpt2 ::= 𝛜",
+          ]
+     );
+}
+
+#[test]
+fn suffixes_apart_from_a_group_are_terminals() {
+     assert_eq!(parse_grammar("%ebnf\n<l> ::= e* + [ ? ]").unwrap(), ["l ::= e* + lOpt", "lOpt ::= ?", "lOpt ::= 𝛜"]);
+     assert_eq!(parse_grammar("<l> ::= { a }").unwrap(), ["l ::= { a }"]);
+}
+
+#[test]
+fn reports_unbalanced_groups() {
+     assert!(parse_grammar("%ebnf\n<l> ::= { a").is_err());
+     assert!(parse_grammar("%ebnf\n<l> ::= a ]").is_err());
+}
+
+#[test]
+fn ez_ebnf_accepts_the_programs_ez_bnf_accepts() {
+     let ebnf = load_grammar(&common::data_path("grammar/ez.ebnf")).unwrap();
+     let bnf = load_grammar(&common::data_path("grammar/ez.bnf")).unwrap();
+     for index in 1..=10 {
+          let tokens = common::lex(&common::read_data(&format!("tests/Test{}.ez", index)));
+          let terminals = match TerminalMap::default().classify(&tokens) {
+               Ok(terminals) => terminals,
+               Err(_) => continue,
+          };
+          assert_eq!(earley_parse(&ebnf, &terminals).is_ok(), earley_parse(&bnf, &terminals).is_ok(), "Test{}.ez", index);
+     }
+}
+
+#[test]
+fn ez_ebnf_sentences_are_ez_sentences() {
+     let ebnf = load_grammar(&common::data_path("grammar/ez.ebnf")).unwrap();
+     let bnf = load_grammar(&common::data_path("grammar/ez.bnf")).unwrap();
+     let mut generator = Generator::new(&ebnf, 10, 7);
+     for _ in 0..50 {
+          let sentence = generator.sentence();
+          assert!(earley_parse(&bnf, &sentence).is_ok(), "{}", sentence.join(" "));
+     }
+}
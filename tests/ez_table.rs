@@ -0,0 +1,44 @@
+mod common;
+
+use compiler::ez_table;
+use compiler::terminals::TerminalMap;
+
+/*
+     @Description: Classifies a program into the terminal, line and column input of the static parser
+     @Params: source - the source text
+     @Returns: The input
+*/
+fn static_input(source: &str) -> Vec<(String, usize, usize)> {
+     let tokens = common::lex(source);
+     let (symbols, errors) = TerminalMap::default().classify_spanned(&tokens);
+     assert!(errors.is_empty());
+     symbols.into_iter().map(|(terminal, _, span)| (terminal, span.line, span.column)).collect()
+}
+
+#[test]
+fn lists_every_conflicting_cell() {
+     let cells: Vec<(&str, &str)> = ez_table::CONFLICTS
+          .iter()
+          .map(|(nonterminal, terminal, _)| (ez_table::NONTERMINALS[*nonterminal], ez_table::TERMINALS[*terminal]))
+          .collect();
+     assert_eq!(cells, [("bfactorRest", "(")]);
+     assert!(ez_table::CONFLICTS.iter().all(|(_, _, rules)| rules.len() > 1));
+}
+
+#[test]
+fn parses_calls_and_indexing_after_the_same_identifier() {
+     assert!(ez_table::parse(&static_input(&common::read_data("tests/Test4.ez"))).is_ok());
+}
+
+#[test]
+fn errors_name_the_line_and_the_conflict_taken() {
+     let error = ez_table::parse(&static_input("if ((a) < b) then print a fi.")).unwrap_err();
+     assert!(error.contains("in comp at 1:5, found )"), "{}", error);
+     assert!(error.contains("(conflict: bfactorRest on ( at 1:3 predicts rules [59, 60], the table used rule 59)"), "{}", error);
+}
+
+#[test]
+fn errors_at_the_end_of_input() {
+     let error = ez_table::parse(&[("ID", 1, 1), ("=", 1, 2), ("NUMBER", 1, 3)]).unwrap_err();
+     assert!(error.ends_with("in termRight at the end of input, found $"), "{}", error);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use std::fs;
+
+use compiler::formatter::{format_source, FormatConfig};
+
+/*
+     @Description: Formats a source text from the scratch directory, where the lexer may write its logs
+     @Params: source - the source text, config - the settings
+     @Returns: The formatted text or the error
+*/
+fn format(source: &str, config: &FormatConfig) -> Result<String, String> {
+     common::lex(source);
+     format_source(source, config)
+}
+
+/*
+     @Description: Checks that a program formats at each width, keeping its tokens, and that formatting again changes nothing;
+                   a program the resilient parser rejects must be refused instead
+     @Params: name - the name to report, source - the source text, widths - the line widths to try
+     @Returns: None
+*/
+fn check_formats(name: &str, source: &str, widths: &[usize]) {
+     let (_, errors) = common::parse_ez(source);
+     for &width in widths {
+          let config = FormatConfig { width, ..FormatConfig::default() };
+          match format(source, &config) {
+               Ok(once) => assert_eq!(format(&once, &config).unwrap(), once, "{} at width {}", name, width),
+               Err(error) => assert!(!errors.is_empty(), "{} at width {}: {}", name, width, error),
+          }
+     }
+}
+
+#[test]
+fn formats_every_data_program() {
+     let directory = common::data_path("tests");
+     let mut paths: Vec<_> = fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path()).collect();
+     paths.sort();
+     for path in paths {
+          check_formats(&path.display().to_string(), &fs::read_to_string(&path).unwrap(), &[80, 30, 12]);
+     }
+}
+
+#[test]
+fn formats_every_generated_program() {
+     for (index, source) in common::generated_programs(100, 3).iter().enumerate() {
+          check_formats(&format!("program {}", index + 1), source, &[30]);
+     }
+}
+
+#[test]
+fn keeps_trailing_commas_and_a_final_number() {
+     let formatted = format("def int c ( int y , ) return y fed ; print c ( 699 , ) + 180 .", &FormatConfig::default()).unwrap();
+     assert_eq!(formatted, "def int c(int y,)\n    return y\nfed;\n\nprint c(699,) + 180 .\n");
+}
+
+#[test]
+fn breaks_long_argument_lists() {
+     let config = FormatConfig { width: 24, ..FormatConfig::default() };
+     let source = "print gcd(alpha + beta, gamma * delta, epsilon).";
+     let formatted = format(source, &config).unwrap();
+     assert_eq!(formatted, "print gcd(\n    alpha + beta,\n    gamma * delta,\n    epsilon\n).\n");
+     assert_eq!(format(&formatted, &config).unwrap(), formatted);
+}
+
+#[test]
+fn breaks_operators_before_arguments() {
+     let config = FormatConfig { width: 20, ..FormatConfig::default() };
+     let formatted = format("x = first + f(second, third) - fourth.", &config).unwrap();
+     assert_eq!(formatted, "x = first +\n    f(second, third) -\n    fourth.\n");
+}
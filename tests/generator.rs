@@ -0,0 +1,54 @@
+mod common;
+
+use compiler::earley::earley_parse;
+use compiler::generator::{Generator, Rng};
+use compiler::grammar::load_grammar;
+
+fn ez_rules() -> Vec<String> {
+     load_grammar(&common::data_path("grammar/ez.bnf")).unwrap()
+}
+
+#[test]
+fn a_seed_always_gives_the_same_programs() {
+     let first = common::generated_programs(20, 5);
+     assert_eq!(common::generated_programs(20, 5), first);
+     assert_ne!(common::generated_programs(20, 6), first);
+     let mut rng = Rng::new(0);
+     assert_ne!(rng.next_u64(), 0);
+}
+
+#[test]
+fn sentences_belong_to_the_grammar() {
+     let rules = ez_rules();
+     let mut generator = Generator::new(&rules, 8, 11);
+     for _ in 0..30 {
+          let sentence = generator.sentence();
+          assert!(earley_parse(&rules, &sentence).is_ok(), "{}", sentence.join(" "));
+     }
+}
+
+#[test]
+fn weight_zero_rules_are_left_out() {
+     let rules = ez_rules();
+     let mut generator = Generator::new(&rules, 12, 2);
+     generator.set_weight("statement ::= print expr", 0).unwrap();
+     for _ in 0..30 {
+          assert!(!generator.sentence().contains(&String::from("print")));
+     }
+     assert!(generator.set_weight("statement ::= goto ID", 1).is_err());
+}
+
+#[test]
+fn mutations_are_rejected_by_the_grammar() {
+     let rules = ez_rules();
+     let mut generator = Generator::new(&rules, 8, 4);
+     let mut mutated_any = false;
+     for _ in 0..10 {
+          let sentence = generator.sentence();
+          if let Some((mutated, description)) = generator.mutate(&rules, &sentence) {
+               mutated_any = true;
+               assert!(earley_parse(&rules, &mutated).is_err(), "{}: {}", description, mutated.join(" "));
+          }
+     }
+     assert!(mutated_any);
+}
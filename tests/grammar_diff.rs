@@ -0,0 +1,71 @@
+use compiler::grammar::parse_grammar;
+use compiler::grammar_diff::{diff_grammars, format_diff, GrammarDiff};
+
+const CLOSED_IF: &str = "<s> ::= if <s> fi | x";
+const DANGLING_ELSE: &str = "<s> ::= if <s> <tail> | x\n<tail> ::= else <s> |";
+
+fn diff(old: &str, new: &str) -> GrammarDiff {
+     diff_grammars(&parse_grammar(old).unwrap(), &parse_grammar(new).unwrap(), 50, 1)
+}
+
+#[test]
+fn lists_rule_and_set_changes() {
+     let diff = diff("<s> ::= a <t>\n<t> ::= b | c", "<s> ::= a <t>\n<t> ::= b | d");
+     assert_eq!(diff.added_rules, ["t ::= d"]);
+     assert_eq!(diff.removed_rules, ["t ::= c"]);
+     assert_eq!(diff.first_changes.len(), 1);
+     assert_eq!(diff.first_changes[0].nonterminal, "t");
+     assert_eq!(diff.first_changes[0].added, ["d"]);
+     assert_eq!(diff.first_changes[0].removed, ["c"]);
+     assert!(diff.follow_changes.is_empty());
+     assert_eq!(diff.only_old, ["a c"]);
+     assert_eq!(diff.only_new, ["a d"]);
+}
+
+#[test]
+fn names_epsilon_and_follow_changes() {
+     let diff = diff(CLOSED_IF, DANGLING_ELSE);
+     let tail = diff.first_changes.iter().find(|change| change.nonterminal == "tail").unwrap();
+     assert_eq!(tail.added, ["EPSILON", "else"]);
+     let s = diff.follow_changes.iter().find(|change| change.nonterminal == "s").unwrap();
+     assert_eq!(s.added, ["else"]);
+     assert_eq!(s.removed, ["fi"]);
+}
+
+#[test]
+fn reports_new_and_resolved_conflicts() {
+     let added = diff(CLOSED_IF, DANGLING_ELSE);
+     assert!(!added.new_conflicts.is_empty());
+     assert!(added.resolved_conflicts.is_empty());
+     let removed = diff(DANGLING_ELSE, CLOSED_IF);
+     assert_eq!(removed.resolved_conflicts, added.new_conflicts);
+     assert!(removed.new_conflicts.is_empty());
+}
+
+#[test]
+fn finds_sentences_only_one_version_accepts() {
+     let diff = diff(CLOSED_IF, DANGLING_ELSE);
+     assert_eq!(diff.only_old.first().map(String::as_str), Some("if x fi"));
+     assert_eq!(diff.only_new.first().map(String::as_str), Some("if x"));
+     assert!(diff.only_new.iter().all(|sentence| !sentence.contains("fi")));
+}
+
+#[test]
+fn formats_every_section() {
+     let report = format_diff(&diff("<s> ::= a <t>\n<t> ::= b | c", "<s> ::= a <t>\n<t> ::= b | d"));
+     assert_eq!(
+          report,
+          "Added productions:\n  t ::= d\nRemoved productions:\n  t ::= c\nFIRST set changes:\n  t: +d -c\n\
+           FOLLOW set changes:\n  none\nNew LL(1) conflicts:\n  none\nResolved LL(1) conflicts:\n  none\n\
+           Sentences only the old grammar accepts:\n  a c\nSentences only the new grammar accepts:\n  a d\n"
+     );
+}
+
+#[test]
+fn an_unchanged_grammar_has_no_differences() {
+     let diff = diff(DANGLING_ELSE, DANGLING_ELSE);
+     assert!(diff.added_rules.is_empty() && diff.removed_rules.is_empty());
+     assert!(diff.first_changes.is_empty() && diff.follow_changes.is_empty());
+     assert!(diff.new_conflicts.is_empty() && diff.resolved_conflicts.is_empty());
+     assert!(diff.only_old.is_empty() && diff.only_new.is_empty());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use compiler::grammar::parse_grammar_source;
+use compiler::hygiene::{check_grammar, format_findings, FindingKind};
+
+const UNTIDY: &str = "<s> ::= <a> x | <u>\n<a> ::= <a> | <b> |\n<b> ::= <a>\n<s> ::= <a> x\n<u> ::= <u> y\n<lost> ::= z\n<c> ::= <missing>\n";
+
+#[test]
+fn finds_each_kind_with_its_rules() {
+     let rules = parse_grammar_source(UNTIDY).unwrap();
+     let findings = check_grammar(&rules);
+     let findings: Vec<(FindingKind, &str, Vec<usize>)> =
+          findings.iter().map(|finding| (finding.kind, finding.symbol.as_str(), finding.rules.clone())).collect();
+     assert_eq!(
+          findings,
+          [
+               (FindingKind::Undefined, "missing", vec![9]),
+               (FindingKind::Unreachable, "lost", vec![8]),
+               (FindingKind::Unreachable, "c", vec![9]),
+               (FindingKind::Unproductive, "u", vec![7]),
+               (FindingKind::Unproductive, "c", vec![9]),
+               (FindingKind::Duplicate, "s", vec![0, 6]),
+               (FindingKind::EpsilonCycle, "a", vec![2]),
+               (FindingKind::EpsilonCycle, "b", vec![5, 3]),
+          ]
+     );
+}
+
+#[test]
+fn cites_the_rule_number_and_line() {
+     let rules = parse_grammar_source(UNTIDY).unwrap();
+     let report = format_findings(&check_grammar(&rules), &rules);
+     assert!(report.starts_with(
+          "Error: undefined nonterminal: <missing> is used but never defined, so it would be read as a terminal\n\trule 9 (line 7): c ::= missing\n"
+     ));
+     assert!(report.contains("Warning: duplicate production: s ::= a x is written more than once\n\trule 0 (line 1): s ::= a x\n\trule 6 (line 4): s ::= a x\n"));
+     assert!(report.contains("Warning: useless epsilon cycle: b derives itself through b => a => b\n"));
+}
+
+#[test]
+fn only_undefined_and_unproductive_are_errors() {
+     let errors: Vec<FindingKind> = [
+          FindingKind::Undefined,
+          FindingKind::Unreachable,
+          FindingKind::Unproductive,
+          FindingKind::Duplicate,
+          FindingKind::EpsilonCycle,
+     ]
+     .into_iter()
+     .filter(|kind| kind.is_error())
+     .collect();
+     assert_eq!(errors, [FindingKind::Undefined, FindingKind::Unproductive]);
+}
+
+#[test]
+fn ez_grammars_are_clean() {
+     for path in ["grammar/ez.bnf", "grammar/ez.ebnf"] {
+          let rules = parse_grammar_source(&common::read_data(path)).unwrap();
+          assert!(check_grammar(&rules).is_empty(), "{}", format_findings(&check_grammar(&rules), &rules));
+     }
+}
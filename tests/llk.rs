@@ -0,0 +1,43 @@
+mod common;
+
+use compiler::grammar::{load_grammar, parse_grammar};
+use compiler::llk::{format_minimal_k, llk_parse, minimal_k, LlkTable};
+use compiler::terminals::TerminalMap;
+use compiler::transform::transform;
+
+fn k_of(source: &str, max_k: usize) -> Vec<(String, Option<usize>)> {
+     minimal_k(&parse_grammar(source).unwrap(), max_k)
+}
+
+#[test]
+fn finds_the_smallest_k_of_each_nonterminal() {
+     let found = k_of("<s> ::= <p> <q>\n<p> ::= a b | a c\n<q> ::= x x y | x x z", 4);
+     assert_eq!(found, [(String::from("s"), Some(1)), (String::from("p"), Some(2)), (String::from("q"), Some(3))]);
+     assert_eq!(format_minimal_k(&found, 4), "s: LL(1)\np: LL(2)\nq: LL(3)\n");
+}
+
+#[test]
+fn reports_nonterminals_that_no_k_up_to_the_limit_resolves() {
+     let found = k_of("<s> ::= <l> | <l> x\n<l> ::= a <l> | a", 3);
+     assert_eq!(found[0], (String::from("s"), None));
+     assert!(format_minimal_k(&found, 3).starts_with("s: not LL(k) for any k up to 3\n"));
+}
+
+#[test]
+fn ez_needs_more_than_one_token_only_in_parenthesized_conditions() {
+     let rules = transform(&load_grammar(&common::data_path("grammar/ez.bnf")).unwrap()).rules;
+     let unresolved: Vec<String> = minimal_k(&rules, 3).into_iter().filter(|(_, k)| *k != Some(1)).map(|(nonterminal, _)| nonterminal).collect();
+     assert_eq!(unresolved, ["bfactorRest"]);
+}
+
+#[test]
+fn parses_with_two_tokens_of_lookahead() {
+     let rules = parse_grammar("<s> ::= ID = NUMBER | ID ( )").unwrap();
+     let table = LlkTable::new(&rules, 2);
+     assert!(table.conflicts().is_empty());
+     assert!(llk_parse(&table, &common::lex("f()"), &TerminalMap::default()).is_ok());
+     assert!(llk_parse(&table, &common::lex("x = 1"), &TerminalMap::default()).is_ok());
+     let one = LlkTable::new(&rules, 1);
+     assert_eq!(one.conflicts(), [(String::from("s"), vec![String::from("ID")])]);
+     assert!(llk_parse(&one, &common::lex("f()"), &TerminalMap::default()).is_err());
+}
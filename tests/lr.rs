@@ -0,0 +1,50 @@
+mod common;
+
+use compiler::grammar::parse_grammar;
+use compiler::lr::{build_lr_table, lr_parse, Action, LrMode, LrTable, Precedence};
+use compiler::terminals::TerminalMap;
+
+fn table(source: &str, mode: LrMode) -> LrTable {
+     build_lr_table(&parse_grammar(source).unwrap(), &Precedence::parse(source), mode)
+}
+
+/*
+     @Description: A grammar that is LR(1) but not LALR(1): merging the two states reached on c after a and after b
+                   joins the lookaheads d and e of both reductions
+     @Params: None
+     @Returns: None
+*/
+const LALR_ONLY_CONFLICT: &str = "<s> ::= a <x> d | b <y> d | a <y> e | b <x> e\n<x> ::= c\n<y> ::= c";
+
+#[test]
+fn lalr_merges_states_that_canonical_keeps_apart() {
+     let canonical = table(LALR_ONLY_CONFLICT, LrMode::Canonical);
+     assert!(canonical.conflicts.is_empty());
+     let lalr = table(LALR_ONLY_CONFLICT, LrMode::Lalr);
+     assert!(lalr.states.len() < canonical.states.len());
+     assert_eq!(lalr.conflicts.len(), 2);
+     assert!(lalr.conflicts.iter().all(|conflict| conflict.kind() == "reduce/reduce" && !conflict.by_precedence));
+}
+
+#[test]
+fn precedence_resolves_shift_reduce_conflicts() {
+     let source = "%left + -\n%left *\n<e> ::= <e> + <e> | <e> - <e> | <e> * <e> | ID";
+     let lr = table(source, LrMode::Lalr);
+     assert!(!lr.conflicts.is_empty());
+     assert!(lr.conflicts.iter().all(|conflict| conflict.kind() == "shift/reduce" && conflict.by_precedence));
+     let reductions = lr_parse(&lr, &common::lex("a - b * c + d"), &TerminalMap::default()).unwrap();
+     let operators: Vec<&str> = reductions.iter().filter(|rule| rule.contains("e ::= e")).map(|rule| rule.as_str()).collect();
+     assert_eq!(operators, ["e ::= e * e", "e ::= e - e", "e ::= e + e"]);
+
+     let unresolved = table("<e> ::= <e> + <e> | ID", LrMode::Lalr);
+     assert!(unresolved.conflicts.iter().all(|conflict| !conflict.by_precedence && matches!(conflict.resolution, Action::Shift(_))));
+}
+
+#[test]
+fn nonassoc_operators_do_not_chain() {
+     let lr = table("%nonassoc <\n<e> ::= <e> < <e> | ID", LrMode::Lalr);
+     assert!(lr.conflicts.iter().all(|conflict| conflict.resolution == Action::Error));
+     assert!(lr_parse(&lr, &common::lex("a < b"), &TerminalMap::default()).is_ok());
+     let error = lr_parse(&lr, &common::lex("a < b < c"), &TerminalMap::default()).unwrap_err();
+     assert!(error.starts_with("Error: Unexpected < at 1:4"), "{}", error);
+}
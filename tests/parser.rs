@@ -0,0 +1,49 @@
+mod common;
+
+use compiler::parser::trace_table;
+use compiler::terminals::TerminalMap;
+
+#[test]
+fn recovers_from_every_error_in_test2() {
+     let parser = common::ez_parser();
+     let tokens = common::lex(&common::read_data("tests/Test2.ez"));
+     let (_, errors) = parser.parse_tokens_recovering(&tokens, &TerminalMap::default());
+     assert!(errors.len() > 1, "expected more than one error, got {:?}", errors.iter().map(|e| e.to_string()).collect::<Vec<_>>());
+     assert!(errors.iter().any(|error| error.found == "r5"));
+}
+
+#[test]
+fn expected_terminals_come_from_the_table_row() {
+     let parser = common::ez_parser();
+     let tokens = common::lex("int a;\na = ;\nprint(a).");
+     let error = parser.parse_tokens(&tokens, &TerminalMap::default()).unwrap_err();
+     let row: Vec<String> = parser
+          .table
+          .terminals
+          .iter()
+          .filter(|terminal| !parser.table.find_cell(error.nonterminal.as_ref().unwrap(), terminal).is_empty())
+          .cloned()
+          .collect();
+     assert_eq!(error.expected, row);
+     assert_eq!(error.to_string(), "expected one of `(`, `ID`, `NUMBER` in expr at 2:3, found `;`");
+}
+
+#[test]
+fn traces_a_small_program() {
+     let parser = common::ez_parser();
+     let tokens = common::lex("print(1).");
+     let (tree, steps) = parser.parse_tokens_traced(&tokens, &TerminalMap::default());
+     assert!(tree.is_ok());
+     assert_eq!(steps.len(), 22);
+     assert_eq!(steps[0].stack, "$ program");
+     assert_eq!(steps[0].input, "print ( NUMBER ) . $");
+     assert_eq!(steps[0].action, "expand with production 0: program ::= fdecls declarations statement_seq .");
+     let matches: Vec<&str> = steps.iter().map(|step| step.action.as_str()).filter(|action| action.starts_with("match")).collect();
+     assert_eq!(matches, ["match `print`", "match `(`", "match `1`", "match `)`", "match `.`"]);
+     let last = steps.last().unwrap();
+     assert_eq!((last.stack.as_str(), last.input.as_str(), last.action.as_str()), ("$", "$", "accept"));
+
+     let table = trace_table(&steps).to_string();
+     assert!(table.contains("Step") && table.contains("Stack") && table.contains("Input") && table.contains("Action"));
+     assert!(table.contains("expand with production 44: factor ::= ( expr )"));
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use compiler::pratt::{parse_expression, OperatorTable};
+use compiler::terminals::TerminalMap;
+
+fn parse(source: &str, table: &OperatorTable) -> String {
+     parse_expression(&common::lex(source), &TerminalMap::default(), table).unwrap().to_string()
+}
+
+#[test]
+fn not_binds_looser_than_comparisons_and_tighter_than_and() {
+     assert_eq!(parse("not a < b and c", &OperatorTable::ez()), "((not (a < b)) and c)");
+     assert_eq!(parse("-a < b", &OperatorTable::ez()), "((-a) < b)");
+}
+
+#[test]
+fn ops_file_matches_the_built_in_table() {
+     let file = OperatorTable::parse(&common::read_data("grammar/ez.ops")).unwrap();
+     let built_in = OperatorTable::ez();
+     assert_eq!(file.prefix, built_in.prefix);
+     assert_eq!(file.infix, built_in.infix);
+     assert_eq!(parse("not a < b and c", &file), "((not (a < b)) and c)");
+}
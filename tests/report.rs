@@ -0,0 +1,23 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use compiler::grammar::load_grammar;
+use compiler::report::{write_set_reports, ReportPaths};
+use compiler::transform::transform;
+
+#[test]
+fn committed_set_logs_match_the_writers() {
+     let rules = load_grammar(&common::data_path("grammar/ez.bnf")).unwrap();
+     let directory = env::temp_dir().join(format!("ez-compiler-sets-{}", process::id()));
+     fs::create_dir_all(&directory).unwrap();
+     write_set_reports(&transform(&rules).rules, &ReportPaths::new(directory.to_str().unwrap())).unwrap();
+
+     let logs: Vec<(&str, String)> = ["first.log", "follow.log"].iter().map(|log| (*log, fs::read_to_string(directory.join(log)).unwrap())).collect();
+     fs::remove_dir_all(&directory).unwrap();
+     for (log, generated) in logs {
+          assert_eq!(generated, common::read_data(&format!("output/{}", log)), "{} is out of date, rerun `cargo run sets data/grammar/ez.bnf`", log);
+     }
+}
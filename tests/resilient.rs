@@ -0,0 +1,37 @@
+mod common;
+
+use compiler::ast::{Expr, Stmt};
+
+#[test]
+fn parses_every_generated_program() {
+     for (index, source) in common::generated_programs(300, 3).iter().enumerate() {
+          let (_, errors) = common::parse_ez(source);
+          assert!(errors.is_empty(), "program {}: {}\n{}", index + 1, errors[0], source);
+     }
+}
+
+#[test]
+fn keeps_trailing_commas() {
+     let (program, errors) = common::parse_ez("def int c(int y,) return y fed;\nprint c(699,);\nprint c(1, 2).");
+     assert!(errors.is_empty());
+     assert!(program.functions[0].trailing_comma);
+     let calls: Vec<String> = program
+          .body
+          .iter()
+          .map(|stmt| match stmt {
+               Stmt::Print { value, .. } => value.to_string(),
+               _ => panic!("expected print statements"),
+          })
+          .collect();
+     assert_eq!(calls, ["c(699,)", "c(1, 2)"]);
+}
+
+#[test]
+fn parses_parenthesized_comparisons_in_conditions() {
+     let (program, errors) = common::parse_ez("while (a <= b) and not (c <> d) do print a od.");
+     assert!(errors.is_empty());
+     match &program.body[0] {
+          Stmt::While { cond: cond @ Expr::Binary { .. }, .. } => assert_eq!(cond.to_string(), "((a <= b) and (not (c <> d)))"),
+          stmt => panic!("expected a while statement, found {:?}", stmt),
+     }
+}
@@ -0,0 +1,39 @@
+use compiler::ast::{Expr, Stmt};
+use compiler::serialize::{from_json, to_json};
+
+#[test]
+fn reads_members_in_any_order_and_every_escape() {
+     let text = r#"{
+          "program": {
+               "body": [{"value": {"name": "a\b\f\uD83D\uDE00\u00e9\/", "span": [1, 7], "kind": "Ident"}, "kind": "Print", "span": [1, 1]}],
+               "declarations": [],
+               "span": [1, 1],
+               "kind": "Program",
+               "functions": []
+          },
+          "version": 1,
+          "format": "ez-ast"
+     }"#;
+     let program = from_json(text).unwrap();
+     match &program.body[..] {
+          [Stmt::Print { value: Expr::Ident { name, .. }, .. }] => assert_eq!(name, "a\u{8}\u{c}\u{1F600}é/"),
+          body => panic!("unexpected body {:?}", body),
+     }
+     assert_eq!(from_json(&to_json(&program)).unwrap(), program);
+}
+
+#[test]
+fn checks_the_header_wherever_it_is() {
+     let text = r#"{"program": {"kind": "Program"}, "format": "ez-ast", "version": 2}"#;
+     assert_eq!(from_json(text).unwrap_err(), "Error: Unsupported ez-ast version 2, expected 1");
+     let text = r#"{"program": {"kind": "Program"}, "version": 1}"#;
+     assert_eq!(from_json(text).unwrap_err(), "Error: Not an ez-ast document");
+}
+
+#[test]
+fn refuses_unpaired_surrogates() {
+     let text = r#"{"format": "ez-ast", "version": 1, "program": {"kind": "Ident", "name": "\uD83Dx"}}"#;
+     assert!(from_json(text).unwrap_err().contains("low surrogate"));
+     let text = r#"{"format": "ez-ast", "version": 1, "program": {"kind": "Ident", "name": "\uDE00"}}"#;
+     assert!(from_json(text).is_err());
+}
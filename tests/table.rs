@@ -0,0 +1,38 @@
+use compiler::grammar::parse_grammar;
+use compiler::table::{to_csv, to_html, to_markdown, Ll1Table};
+
+/*
+     @Description: A grammar whose table has one conflicting cell and terminals that need escaping in every format
+     @Params: None
+     @Returns: The table
+*/
+fn conflicting_table() -> Ll1Table {
+     Ll1Table::new(&parse_grammar("<s> ::= a <t> | a ,\n<t> ::= < |").unwrap())
+}
+
+#[test]
+fn csv_quotes_fields_with_commas() {
+     assert_eq!(to_csv(&conflicting_table()), "nonterminal,a,\",\",<,$\ns,\"a t / a ,\",,,\nt,,,<,𝛜\n");
+}
+
+#[test]
+fn markdown_marks_and_lists_conflicts() {
+     let markdown = to_markdown(&conflicting_table());
+     assert!(markdown.starts_with("| | `a` | `,` | `<` | `$` |\n|---|---|---|---|---|\n"), "{}", markdown);
+     assert!(markdown.contains("| **s** | ⚠ **a t<br>a ,** |  |  |  |\n"), "{}", markdown);
+     assert!(markdown.contains("| **t** |  |  | \\< | 𝛜 |\n"), "{}", markdown);
+     assert!(markdown.ends_with("\n1 conflicting cell(s):\n\n- s on `a`\n"), "{}", markdown);
+}
+
+#[test]
+fn html_escapes_text_and_highlights_conflicts() {
+     let html = to_html(&conflicting_table(), "a < b");
+     assert!(html.contains("<title>a &lt; b</title>"));
+     assert!(html.contains("<tr><th></th><th>a</th><th>,</th><th>&lt;</th><th>$</th></tr>"));
+     assert!(html.contains("<td class=\"conflict\" title=\"conflict on a\">a t<br>a ,</td>"));
+     assert!(html.contains("<p>1 conflicting cell(s) are highlighted.</p>"));
+
+     let ll1 = to_html(&Ll1Table::new(&parse_grammar("<s> ::= a | b").unwrap()), "LL(1)");
+     assert!(ll1.contains("<p>The grammar is LL(1): no cell holds more than one rule.</p>"));
+     assert!(!ll1.contains("class=\"conflict\""));
+}
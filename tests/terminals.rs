@@ -0,0 +1,47 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use compiler::terminals::TerminalMap;
+
+/*
+     @Description: Writes a terminal map file to the scratch directory, named after the test process so parallel runs
+                   do not share it
+     @Params: name - the file name, source - the mapping lines
+     @Returns: The path
+*/
+fn write_map(name: &str, source: &str) -> String {
+     let path = env::temp_dir().join(format!("{}-{}", process::id(), name));
+     fs::write(&path, source).expect("Unable to write file");
+     path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn custom_map_overrides_the_defaults() {
+     let path = write_map("ez-custom.terminals", "# renamed terminals\nIdent = NAME\n\"fed\" = END\n");
+     let terminals = TerminalMap::load(&path).unwrap();
+     let tokens = common::lex("def int f(int x) return x fed; print(f(1)).");
+     let input = terminals.classify(&tokens).unwrap();
+     assert!(input.contains(&String::from("NAME")));
+     assert!(input.contains(&String::from("END")));
+     assert!(!input.contains(&String::from("ID")));
+     assert!(input.contains(&String::from("NUMBER")));
+}
+
+#[test]
+fn rejects_lines_without_a_terminal() {
+     let path = write_map("ez-broken.terminals", "Ident NAME\n");
+     let error = TerminalMap::load(&path).err().unwrap();
+     assert!(error.starts_with("Error: Line 1 of"), "{}", error);
+}
+
+#[test]
+fn rejects_names_that_are_not_token_types() {
+     let path = write_map("ez-misspelled.terminals", "Ident = NAME\nIdentifier = NAME\n");
+     let error = TerminalMap::load(&path).err().unwrap();
+     assert!(error.starts_with("Error: Line 2 of") && error.contains("Identifier, which is not a token type"), "{}", error);
+     let path = write_map("ez-quoted.terminals", "\"Identifier\" = NAME\n");
+     assert!(TerminalMap::load(&path).is_ok());
+}
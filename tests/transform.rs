@@ -0,0 +1,71 @@
+mod common;
+
+use compiler::grammar::{first_sets, load_grammar, nonterminals, parse_grammar, split_rule, EPSILON};
+use compiler::table::Ll1Table;
+use compiler::transform::transform;
+
+fn rules(source: &str) -> Vec<String> {
+     parse_grammar(source).unwrap()
+}
+
+/*
+     @Description: Checks that no nonterminal of a grammar can derive a sentential form starting with itself, following
+                   nullable symbols as well as first symbols
+     @Params: grammar - the grammar rules
+     @Returns: None
+*/
+fn assert_no_left_recursion(grammar: &[String]) {
+     let first = first_sets(grammar);
+     let nullable = |symbol: &String| first.get(symbol).is_some_and(|set| set.contains(EPSILON));
+     for start in &nonterminals(grammar) {
+          let mut reached: Vec<String> = Vec::new();
+          let mut pending = vec![start.clone()];
+          while let Some(lhs) = pending.pop() {
+               for rule in grammar.iter().filter(|rule| split_rule(rule).0 == lhs) {
+                    for symbol in split_rule(rule).1 {
+                         assert_ne!(symbol, *start, "{} is left recursive through {}", start, rule);
+                         if first.contains_key(&symbol) && !reached.contains(&symbol) {
+                              reached.push(symbol.clone());
+                              pending.push(symbol.clone());
+                         }
+                         if !nullable(&symbol) {
+                              break;
+                         }
+                    }
+               }
+          }
+     }
+}
+
+#[test]
+fn removes_direct_and_indirect_left_recursion() {
+     let derived = transform(&rules("<s> ::= <a> x | y\n<a> ::= <s> z | <a> w | v"));
+     assert_no_left_recursion(&derived.rules);
+}
+
+#[test]
+fn removes_left_recursion_hidden_behind_a_nullable_symbol() {
+     let derived = transform(&rules("<s> ::= <n> <s> x | y\n<n> ::= m |"));
+     assert_no_left_recursion(&derived.rules);
+     assert_eq!(derived.rules, ["s ::= m s x sRight", "s ::= y sRight", "sRight ::= x sRight", "sRight ::= 𝛜"]);
+     assert_eq!(derived.origins[2], [0, 3]);
+}
+
+#[test]
+fn factors_alternatives_whose_left_corners_share_a_terminal() {
+     let derived = transform(&rules("<factor> ::= <var> | NUMBER | <fname> ( <args> )\n<var> ::= ID | ID [ NUMBER ]\n<fname> ::= ID\n<args> ::= NUMBER |"));
+     assert!(Ll1Table::new(&derived.rules).conflicts().is_empty(), "{:?}", derived.rules);
+     assert!(derived.rules.contains(&String::from("factor ::= ID factorRest")), "{:?}", derived.rules);
+     assert!(!derived.rules.iter().any(|rule| rule.starts_with("fname ::=")), "fname is no longer reachable: {:?}", derived.rules);
+}
+
+#[test]
+fn ez_grammar_keeps_only_the_parenthesized_condition_conflict() {
+     let original = load_grammar(&common::data_path("grammar/ez.bnf")).unwrap();
+     let derived = transform(&original);
+     assert_no_left_recursion(&derived.rules);
+     assert_eq!(Ll1Table::new(&derived.rules).conflicts(), [(String::from("bfactorRest"), String::from("("))]);
+     for index in 0..derived.rules.len() {
+          assert!(!derived.original_rules(index, &original).is_empty(), "{} has no origin", derived.rules[index]);
+     }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use compiler::ast::{Expr, Stmt};
+use compiler::constant_fold::ConstantFolder;
+use compiler::visit::{replace_expr, replace_stmt, walk_expr, walk_expr_mut, Fold, Visitor, VisitorMut};
+
+struct Names(Vec<String>);
+
+impl Visitor for Names {
+     fn visit_expr(&mut self, expr: &Expr) {
+          if let Expr::Ident { name, .. } = expr {
+               self.0.push(name.clone());
+          }
+          walk_expr(self, expr);
+     }
+}
+
+struct Rename<'a>(&'a str, &'a str);
+
+impl VisitorMut for Rename<'_> {
+     fn visit_expr_mut(&mut self, expr: &mut Expr) {
+          if let Expr::Ident { name, .. } = expr {
+               if name == self.0 {
+                    *name = self.1.to_string();
+               }
+          }
+          walk_expr_mut(self, expr);
+     }
+}
+
+fn names(source: &str) -> Vec<String> {
+     let (program, errors) = common::parse_ez(source);
+     assert!(errors.is_empty());
+     let mut names = Names(Vec::new());
+     names.visit_program(&program);
+     names.0
+}
+
+#[test]
+fn visitor_sees_every_identifier_in_source_order() {
+     assert_eq!(names("int a, b;\na = 1;\nb = a + c[a];\nprint(b)."), ["a", "b", "a", "b", "a", "c", "a", "b"]);
+}
+
+#[test]
+fn visitor_mut_renames_in_place() {
+     let (mut program, _) = common::parse_ez("int a, b;\na = 1;\nb = a * a;\nprint(b).");
+     Rename("a", "x").visit_program_mut(&mut program);
+     let mut names = Names(Vec::new());
+     names.visit_program(&program);
+     assert_eq!(names.0, ["x", "b", "x", "b", "x", "x", "b"]);
+}
+
+fn fold(source: &str) -> (String, usize) {
+     let (program, errors) = common::parse_ez(source);
+     assert!(errors.is_empty());
+     let mut folder = ConstantFolder::new();
+     let program = folder.fold_program(program);
+     match &program.body[..] {
+          [Stmt::Print { value, .. }] => (value.to_string(), folder.folded),
+          body => panic!("unexpected body {:?}", body),
+     }
+}
+
+#[test]
+fn folds_integer_operations_and_counts_only_those() {
+     assert_eq!(fold("print(2 * (3 + 4) - x)."), (String::from("(14 - x)"), 2));
+     assert_eq!(fold("print((7))."), (String::from("7"), 0));
+     assert_eq!(fold("print(7 % 0 + 1.5)."), (String::from("((7 % 0) + 1.5)"), 0));
+}
+
+#[test]
+fn leaves_int_overflow_for_run_time() {
+     assert_eq!(fold("print(65536 * 65536)."), (String::from("(65536 * 65536)"), 0));
+     assert_eq!(fold("print(2147483647 + 0)."), (String::from("2147483647"), 1));
+}
+
+#[test]
+fn replacements_take_the_position_of_what_they_replace() {
+     let (program, _) = common::parse_ez("int a;\na = 1;\nprint(a).");
+     let assign = &program.body[0];
+     let print = &program.body[1];
+     assert_ne!(assign.span(), print.span());
+
+     let moved = replace_stmt(assign, print.clone());
+     assert_eq!(moved.span(), assign.span());
+     assert!(matches!(moved, Stmt::Print { .. }));
+
+     if let (Stmt::Assign { value, .. }, Stmt::Print { value: printed, .. }) = (assign, print) {
+          let moved = replace_expr(value, printed.clone());
+          assert_eq!(moved.span(), value.span());
+          assert_eq!(moved.to_string(), printed.to_string());
+     }
+}
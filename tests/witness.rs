@@ -0,0 +1,53 @@
+mod common;
+
+use compiler::earley::earley_parse;
+use compiler::grammar::{load_grammar, parse_grammar};
+use compiler::lr::{build_lr_table, LrMode, Precedence};
+use compiler::table::Ll1Table;
+use compiler::transform::transform;
+use compiler::witness::{format_witnesses, ll1_witnesses, lr_witnesses};
+
+#[test]
+fn one_ll1_witness_per_conflict_with_sentences_the_grammar_accepts() {
+     let rules = transform(&load_grammar(&common::data_path("grammar/ez.bnf")).unwrap()).rules;
+     let table = Ll1Table::new(&rules);
+     let witnesses = ll1_witnesses(&rules, &table);
+     assert_eq!(witnesses.len(), table.conflicts().len());
+     for (witness, (nonterminal, terminal)) in witnesses.iter().zip(table.conflicts()) {
+          assert!(witness.conflict.starts_with(&format!("{} on {} between", nonterminal, terminal)), "{}", witness.conflict);
+          assert_eq!(witness.prefix.last(), Some(&terminal));
+          assert_eq!(witness.examples.len(), table.find_cell(&nonterminal, &terminal).len());
+          for (label, sentence, sketch) in &witness.examples {
+               assert!(sentence.starts_with(&witness.prefix), "{}: {}", label, sentence.join(" "));
+               assert!(earley_parse(&rules, sentence).is_ok(), "{}: {}", label, sentence.join(" "));
+               assert!(sketch.is_some());
+          }
+     }
+     assert_eq!(witnesses[0].prefix, ["if", "(", "("]);
+     assert!(witnesses[0].ambiguity.is_none());
+}
+
+#[test]
+fn one_lr_witness_per_conflict_and_ambiguous_input() {
+     let rules = parse_grammar("<e> ::= <e> + <e> | ID").unwrap();
+     let table = build_lr_table(&rules, &Precedence::default(), LrMode::Lalr);
+     let witnesses = lr_witnesses(&rules, &table);
+     assert_eq!(witnesses.len(), table.conflicts.len());
+     let witness = &witnesses[0];
+     assert_eq!(witness.prefix, ["ID", "+", "ID", "+"]);
+     for (label, sentence, _) in &witness.examples {
+          assert!(earley_parse(&rules, sentence).is_ok(), "{}: {}", label, sentence.join(" "));
+     }
+     let (sentence, first, second) = witness.ambiguity.as_ref().unwrap();
+     assert_eq!(sentence, &["ID", "+", "ID", "+", "ID"]);
+     assert_ne!(first, second);
+     assert!(format_witnesses(&witnesses).contains("\tambiguous input: ID + ID + ID\n"));
+}
+
+#[test]
+fn no_witnesses_without_conflicts() {
+     let rules = parse_grammar("<e> ::= ID <rest>\n<rest> ::= + ID <rest> |").unwrap();
+     assert!(ll1_witnesses(&rules, &Ll1Table::new(&rules)).is_empty());
+     assert!(lr_witnesses(&rules, &build_lr_table(&rules, &Precedence::default(), LrMode::Lalr)).is_empty());
+     assert_eq!(format_witnesses(&[]), "");
+}